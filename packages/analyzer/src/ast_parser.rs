@@ -1,8 +1,9 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use tree_sitter::{Language, Parser, Query, QueryCursor};
+use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
 
 /// AST parsing result
 #[napi(object)]
@@ -44,6 +45,11 @@ pub struct QueryCapture {
 static mut PARSERS: Option<HashMap<String, Parser>> = None;
 static mut LANGUAGES: Option<HashMap<String, Language>> = None;
 
+thread_local! {
+    /// Per-thread parsers used by the analyzers, which may run on Rayon workers
+    static THREAD_PARSERS: RefCell<HashMap<String, Parser>> = RefCell::new(HashMap::new());
+}
+
 /// Initialize parser cache
 fn init_cache() {
     unsafe {
@@ -67,19 +73,8 @@ fn get_language(language_id: &str) -> Result<Language> {
             }
             
             // Load language
-            let lang = match language_id {
-                "typescript" | "typescriptreact" => tree_sitter_typescript::language_typescript(),
-                "javascript" | "javascriptreact" => tree_sitter_javascript::language(),
-                "python" => tree_sitter_python::language(),
-                "rust" => tree_sitter_rust::language(),
-                "go" => tree_sitter_go::language(),
-                "java" => tree_sitter_java::language(),
-                "cpp" | "c" => tree_sitter_cpp::language(),
-                "csharp" => tree_sitter_c_sharp::language(),
-                "ruby" => tree_sitter_ruby::language(),
-                "php" => tree_sitter_php::language(),
-                _ => return Err(Error::from_reason(format!("Unsupported language: {}", language_id))),
-            };
+            let lang = load_language(language_id)
+                .ok_or_else(|| Error::from_reason(format!("Unsupported language: {}", language_id)))?;
             
             languages.insert(language_id.to_string(), lang);
             Ok(lang)
@@ -89,6 +84,47 @@ fn get_language(language_id: &str) -> Result<Language> {
     }
 }
 
+/// Map a language ID to its compiled Tree-sitter grammar
+fn load_language(language_id: &str) -> Option<Language> {
+    let lang = match language_id {
+        "typescript" => tree_sitter_typescript::language_typescript(),
+        "typescriptreact" => tree_sitter_typescript::language_tsx(),
+        "javascript" | "javascriptreact" => tree_sitter_javascript::language(),
+        "python" => tree_sitter_python::language(),
+        "rust" => tree_sitter_rust::language(),
+        "go" => tree_sitter_go::language(),
+        "java" => tree_sitter_java::language(),
+        "cpp" | "c" => tree_sitter_cpp::language(),
+        "csharp" => tree_sitter_c_sharp::language(),
+        "ruby" => tree_sitter_ruby::language(),
+        "php" => tree_sitter_php::language(),
+        _ => return None,
+    };
+    Some(lang)
+}
+
+/// Parse code with the calling thread's parser for the language
+///
+/// Unlike `get_parser`, this never touches the global cache, so it is safe to
+/// call from parallel extractors.
+pub(crate) fn parse_tree(code: &str, language_id: &str) -> Result<Tree> {
+    THREAD_PARSERS.with(|parsers| {
+        let mut parsers = parsers.borrow_mut();
+        if !parsers.contains_key(language_id) {
+            let language = load_language(language_id)
+                .ok_or_else(|| Error::from_reason(format!("Unsupported language: {}", language_id)))?;
+            let mut parser = Parser::new();
+            parser.set_language(language)
+                .map_err(|e| Error::from_reason(format!("Failed to set language: {}", e)))?;
+            parsers.insert(language_id.to_string(), parser);
+        }
+        
+        parsers.get_mut(language_id).unwrap()
+            .parse(code, None)
+            .ok_or_else(|| Error::from_reason("Failed to parse code"))
+    })
+}
+
 /// Get or create parser for language
 fn get_parser(language_id: &str) -> Result<&'static mut Parser> {
     init_cache();
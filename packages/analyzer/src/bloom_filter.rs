@@ -0,0 +1,99 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use xxhash_rust::xxh3::xxh3_128;
+
+/// Marks serialized filters, with a format version
+const MAGIC: &[u8; 4] = b"BLM1";
+const HEADER_LEN: usize = 16;
+
+/// Probabilistic set membership: `contains` never misses an inserted item,
+/// and wrongly reports a missing one at about the configured rate
+/// 
+/// Sized from the expected item count, so inserting far more than
+/// `capacity` items raises the false positive rate
+#[napi]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+#[napi]
+impl BloomFilter {
+    /// Filter for `capacity` items with the given false positive rate
+    #[napi(constructor)]
+    pub fn new(capacity: u32, false_positive_rate: f64) -> Result<Self> {
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(Error::from_reason(format!(
+                "False positive rate must be between 0 and 1, got {}",
+                false_positive_rate
+            )));
+        }
+        let capacity = capacity.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / capacity) * ln2).round().max(1.0) as u32;
+        Ok(Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        })
+    }
+
+    #[napi]
+    pub fn insert(&mut self, item: String) {
+        for bit in self.bit_indices(&item) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    #[napi]
+    pub fn contains(&self, item: String) -> bool {
+        self.bit_indices(&item)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Bytes for `deserialize`, portable across machines
+    #[napi]
+    pub fn serialize(&self) -> Buffer {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.bits.len() * 8);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        bytes.extend_from_slice(&self.num_bits.to_le_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes.into()
+    }
+
+    #[napi(factory)]
+    pub fn deserialize(data: Buffer) -> Result<Self> {
+        let invalid = || Error::from_reason("Invalid bloom filter data");
+        let data: &[u8] = &data;
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(invalid());
+        }
+        let num_hashes = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        let num_bits = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let words = &data[HEADER_LEN..];
+        if num_hashes == 0 || num_bits == 0 || words.len() as u64 != num_bits.div_ceil(64) * 8 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            bits: words
+                .chunks_exact(8)
+                .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                .collect(),
+            num_bits,
+            num_hashes,
+        })
+    }
+
+    /// Bits set for an item, by double hashing one 128-bit hash
+    fn bit_indices(&self, item: &str) -> impl Iterator<Item = u64> {
+        let hash = xxh3_128(item.as_bytes());
+        let (h1, h2) = (hash as u64, (hash >> 64) as u64 | 1);
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
@@ -0,0 +1,400 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use xxhash_rust::xxh3::xxh3_128;
+
+use crate::file_io::atomic_write;
+
+/// Bumped whenever the stored layout or chunk ids change
+const FORMAT_VERSION: u32 = 2;
+
+/// A chunk of a file passed to `ChunkStore.updateFile`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkInput {
+    pub text: String,
+    /// Name of the symbol the chunk holds, if any
+    pub symbol: Option<String>,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    /// Exclusive
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    #[napi(js_name = "languageId")]
+    pub language_id: Option<String>,
+    /// Where the chunk's embedding is stored, such as a `VectorStore` id
+    #[napi(js_name = "embeddingId")]
+    pub embedding_id: Option<String>,
+}
+
+/// Where a stored chunk appears in a file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkLocation {
+    pub path: String,
+    /// Name of the symbol the chunk holds there, if any
+    pub symbol: Option<String>,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    /// Exclusive
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+}
+
+/// A chunk held by a `ChunkStore`, with every place its text appears
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredChunk {
+    pub id: String,
+    pub text: String,
+    #[napi(js_name = "languageId")]
+    pub language_id: Option<String>,
+    #[napi(js_name = "embeddingId")]
+    pub embedding_id: Option<String>,
+    /// Sorted by path, then line
+    pub locations: Vec<ChunkLocation>,
+}
+
+/// What `ChunkStore.updateFile` changed
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkFileUpdate {
+    /// Chunks new to the store, which need embedding
+    pub added: Vec<String>,
+    /// Chunks no file holds any more, whose embeddings can be dropped
+    pub removed: Vec<String>,
+    /// Chunks of the file already stored, which keep their embeddings
+    pub unchanged: Vec<String>,
+}
+
+/// Counts from `ChunkStore.stats`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkStoreStats {
+    /// Distinct chunk texts
+    pub chunks: u32,
+    pub files: u32,
+    /// Chunks with an embedding id
+    pub embedded: u32,
+    /// UTF-8 bytes of distinct chunk text
+    #[napi(js_name = "textBytes")]
+    pub text_bytes: i64,
+    /// Chunks by language ID, '' for none
+    pub languages: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChunkContent {
+    text: String,
+    language_id: Option<String>,
+    embedding_id: Option<String>,
+}
+
+/// A chunk's place in a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Placement {
+    id: String,
+    symbol: Option<String>,
+    start_line: u32,
+    end_line: u32,
+}
+
+#[derive(Deserialize)]
+struct FormatHeader {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredChunks {
+    version: u32,
+    chunks: HashMap<String, ChunkContent>,
+    files: HashMap<String, Vec<Placement>>,
+}
+
+/// Chunks of workspace files for retrieval, with where each came from
+///
+/// Chunk ids hash the chunk's text alone, so text repeated across files is
+/// stored and embedded once, and a chunk keeps its id, and so its embedding,
+/// while edits elsewhere move it to other lines. With a storage path the
+/// chunks are loaded on construction and written by `save`
+#[napi]
+pub struct ChunkStore {
+    storage_path: Option<PathBuf>,
+    chunks: HashMap<String, ChunkContent>,
+    /// Chunk placements by file, in the order they were given
+    by_file: HashMap<String, Vec<Placement>>,
+    /// Files holding each chunk
+    paths: HashMap<String, HashSet<String>>,
+}
+
+#[napi]
+impl ChunkStore {
+    /// Open a store, loading `storagePath` when it exists
+    #[napi(constructor)]
+    pub fn new(storage_path: Option<String>) -> Result<Self> {
+        let mut store = Self {
+            storage_path: storage_path.map(PathBuf::from),
+            chunks: HashMap::new(),
+            by_file: HashMap::new(),
+            paths: HashMap::new(),
+        };
+        let Some(path) = store.storage_path.as_ref().filter(|path| path.exists()) else {
+            return Ok(store);
+        };
+        let data = fs::read(path)
+            .map_err(|e| Error::from_reason(format!("Failed to read chunks {}: {}", path.display(), e)))?;
+        let invalid = |e: serde_json::Error| Error::from_reason(format!("Invalid chunks {}: {}", path.display(), e));
+        // Chunks from another format version are rebuilt from scratch
+        let header: FormatHeader = serde_json::from_slice(&data).map_err(invalid)?;
+        if header.version != FORMAT_VERSION {
+            return Ok(store);
+        }
+        let stored: StoredChunks = serde_json::from_slice(&data).map_err(invalid)?;
+        for (file, placements) in &stored.files {
+            for placement in placements {
+                store.paths.entry(placement.id.clone()).or_default().insert(file.clone());
+            }
+        }
+        store.chunks = stored.chunks;
+        store.by_file = stored.files;
+        Ok(store)
+    }
+
+    /// Replace the chunks of a file
+    ///
+    /// Chunks whose text is already stored keep their ids and embedding ids
+    /// unless new ones are given, and take their new lines and symbol
+    #[napi]
+    pub fn update_file(&mut self, path: String, chunks: Vec<ChunkInput>) -> ChunkFileUpdate {
+        let old = self.by_file.remove(&path).unwrap_or_default();
+        let mut update = ChunkFileUpdate::default();
+        let mut placements = Vec::with_capacity(chunks.len());
+        let mut kept = HashSet::new();
+        for chunk in chunks {
+            let id = chunk_id(&chunk.text);
+            match self.chunks.get_mut(&id) {
+                Some(content) => {
+                    if chunk.embedding_id.is_some() {
+                        content.embedding_id = chunk.embedding_id;
+                    }
+                    if chunk.language_id.is_some() {
+                        content.language_id = chunk.language_id;
+                    }
+                    if kept.insert(id.clone()) {
+                        update.unchanged.push(id.clone());
+                    }
+                }
+                None => {
+                    let content = ChunkContent {
+                        text: chunk.text,
+                        language_id: chunk.language_id,
+                        embedding_id: chunk.embedding_id,
+                    };
+                    self.chunks.insert(id.clone(), content);
+                    kept.insert(id.clone());
+                    update.added.push(id.clone());
+                }
+            }
+            self.paths.entry(id.clone()).or_default().insert(path.clone());
+            placements.push(Placement {
+                id,
+                symbol: chunk.symbol,
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+            });
+        }
+        for placement in old {
+            if !kept.contains(&placement.id) && self.release(&placement.id, &path) {
+                update.removed.push(placement.id);
+            }
+        }
+        update.removed.sort();
+        update.removed.dedup();
+        if !placements.is_empty() {
+            self.by_file.insert(path, placements);
+        }
+        update
+    }
+
+    /// Chunks by id, with null for ids not stored
+    #[napi]
+    pub fn get_by_ids(&self, ids: Vec<String>) -> Vec<Option<StoredChunk>> {
+        ids.iter().map(|id| self.stored_chunk(id)).collect()
+    }
+
+    /// Distinct chunks of a file, in the order they were given
+    #[napi]
+    pub fn get_file_chunks(&self, path: String) -> Vec<StoredChunk> {
+        let mut seen = HashSet::new();
+        self.by_file
+            .get(&path)
+            .into_iter()
+            .flatten()
+            .filter(|placement| seen.insert(&placement.id))
+            .filter_map(|placement| self.stored_chunk(&placement.id))
+            .collect()
+    }
+
+    /// Drop a file's chunks, returning the ids of those no other file holds
+    #[napi]
+    pub fn invalidate_file(&mut self, path: String) -> Vec<String> {
+        let mut removed: Vec<String> = self
+            .by_file
+            .remove(&path)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|placement| self.release(&placement.id, &path))
+            .map(|placement| placement.id)
+            .collect();
+        removed.sort();
+        removed.dedup();
+        removed
+    }
+
+    /// Record where a chunk's embedding is stored, returning false when the
+    /// chunk is not stored
+    #[napi]
+    pub fn set_embedding(&mut self, id: String, embedding_id: Option<String>) -> bool {
+        let Some(chunk) = self.chunks.get_mut(&id) else {
+            return false;
+        };
+        chunk.embedding_id = embedding_id;
+        true
+    }
+
+    #[napi]
+    pub fn stats(&self) -> ChunkStoreStats {
+        let mut languages: HashMap<String, u32> = HashMap::new();
+        for chunk in self.chunks.values() {
+            *languages.entry(chunk.language_id.clone().unwrap_or_default()).or_default() += 1;
+        }
+        ChunkStoreStats {
+            chunks: self.chunks.len() as u32,
+            files: self.by_file.len() as u32,
+            embedded: self.chunks.values().filter(|chunk| chunk.embedding_id.is_some()).count() as u32,
+            text_bytes: self.chunks.values().map(|chunk| chunk.text.len() as i64).sum(),
+            languages,
+        }
+    }
+
+    /// Write the chunks to the storage path
+    #[napi]
+    pub fn save(&self) -> Result<()> {
+        let path = self
+            .storage_path
+            .as_ref()
+            .ok_or_else(|| Error::from_reason("Chunk store has no storage path"))?;
+        let stored = StoredChunks {
+            version: FORMAT_VERSION,
+            chunks: self.chunks.clone(),
+            files: self.by_file.clone(),
+        };
+        let data = serde_json::to_vec(&stored)
+            .map_err(|e| Error::from_reason(format!("Serialization error: {}", e)))?;
+        atomic_write(path, &data)
+            .map_err(|e| Error::from_reason(format!("Failed to write chunks {}: {}", path.display(), e)))
+    }
+
+    /// Number of stored chunks
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.chunks.len() as u32
+    }
+
+    /// Paths with stored chunks, sorted
+    #[napi]
+    pub fn files(&self) -> Vec<String> {
+        let mut files: Vec<String> = self.by_file.keys().cloned().collect();
+        files.sort();
+        files
+    }
+}
+
+impl ChunkStore {
+    fn stored_chunk(&self, id: &str) -> Option<StoredChunk> {
+        let content = self.chunks.get(id)?;
+        let mut locations: Vec<ChunkLocation> = self
+            .paths
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|path| Some((path, self.by_file.get(path)?)))
+            .flat_map(|(path, placements)| {
+                placements.iter().filter(|placement| placement.id == id).map(|placement| ChunkLocation {
+                    path: path.clone(),
+                    symbol: placement.symbol.clone(),
+                    start_line: placement.start_line,
+                    end_line: placement.end_line,
+                })
+            })
+            .collect();
+        locations.sort_by(|a, b| a.path.cmp(&b.path).then(a.start_line.cmp(&b.start_line)));
+        Some(StoredChunk {
+            id: id.to_string(),
+            text: content.text.clone(),
+            language_id: content.language_id.clone(),
+            embedding_id: content.embedding_id.clone(),
+            locations,
+        })
+    }
+
+    /// Forget that a file holds a chunk, dropping the chunk when no file
+    /// does; returns whether it was dropped
+    fn release(&mut self, id: &str, path: &str) -> bool {
+        let Some(paths) = self.paths.get_mut(id) else {
+            return false;
+        };
+        paths.remove(path);
+        if !paths.is_empty() {
+            return false;
+        }
+        self.paths.remove(id);
+        self.chunks.remove(id);
+        true
+    }
+}
+
+/// Hash of a chunk's text
+fn chunk_id(text: &str) -> String {
+    format!("{:032x}", xxh3_128(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(text: &str, start_line: u32) -> ChunkInput {
+        ChunkInput {
+            text: text.to_string(),
+            symbol: None,
+            start_line,
+            end_line: start_line + 1,
+            language_id: None,
+            embedding_id: None,
+        }
+    }
+
+    #[test]
+    fn identical_chunks_in_different_files_are_stored_once() {
+        let mut store = ChunkStore::new(None).unwrap();
+        let a = store.update_file("a.rs".to_string(), vec![chunk("shared", 0), chunk("only a", 1)]);
+        let b = store.update_file("b.rs".to_string(), vec![chunk("shared", 4)]);
+        assert_eq!(a.added.len(), 2);
+        assert!(b.added.is_empty());
+        assert_eq!(b.unchanged, [a.added[0].clone()]);
+        assert_eq!(store.size(), 2);
+
+        let shared = store.get_by_ids(vec![a.added[0].clone()]).remove(0).unwrap();
+        let paths: Vec<&str> = shared.locations.iter().map(|location| location.path.as_str()).collect();
+        assert_eq!(paths, ["a.rs", "b.rs"]);
+
+        // Only chunks no file holds any more are dropped
+        assert_eq!(store.invalidate_file("a.rs".to_string()), [a.added[1].clone()]);
+        let removed = store.update_file("b.rs".to_string(), Vec::new()).removed;
+        assert_eq!(removed, [a.added[0].clone()]);
+        assert_eq!(store.size(), 0);
+    }
+}
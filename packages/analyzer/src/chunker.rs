@@ -0,0 +1,275 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::semantic_analyzer::LineIndex;
+use crate::text_processor::{bpe, ceil_char_boundary, floor_char_boundary, token_offsets};
+
+/// Options for sliding-window chunking
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkOptions {
+    /// Maximum tokens per chunk (default: 512)
+    #[napi(js_name = "maxTokens")]
+    pub max_tokens: Option<u32>,
+    /// Tokens shared between consecutive chunks (default: 64)
+    #[napi(js_name = "overlapTokens")]
+    pub overlap_tokens: Option<u32>,
+    /// Encoding or model name, as for `count_tokens` (default: cl100k_base)
+    pub encoding: Option<String>,
+}
+
+/// A chunk of text ready for embedding
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChunk {
+    pub text: String,
+    #[napi(js_name = "startByte")]
+    pub start_byte: u32,
+    #[napi(js_name = "endByte")]
+    pub end_byte: u32,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    #[napi(js_name = "tokenCount")]
+    pub token_count: u32,
+}
+
+/// Split text into overlapping windows of at most `maxTokens` tokens
+///
+/// Each window starts `maxTokens - overlapTokens` tokens after the previous one.
+/// Window edges fall on token boundaries, moved forward to a character boundary
+/// where a token splits a multi-byte character. A character split across more
+/// tokens than fit in a window is taken whole, so no chunk is empty.
+#[napi]
+pub fn chunk_text(code: String, options: Option<ChunkOptions>) -> Result<Vec<TextChunk>> {
+    let options = options.unwrap_or_default();
+    let max_tokens = options.max_tokens.unwrap_or(512) as usize;
+    let overlap_tokens = options.overlap_tokens.unwrap_or(64) as usize;
+    if max_tokens == 0 || overlap_tokens >= max_tokens {
+        return Err(Error::from_reason("maxTokens must be greater than overlapTokens"));
+    }
+
+    let bpe = bpe(options.encoding.as_deref())?;
+    let offsets = token_offsets(bpe, bpe.encode_ordinary(&code));
+    let token_total = offsets.len() - 1;
+    let line_index = LineIndex::new(&code);
+
+    let bounds: Vec<usize> = offsets.iter().map(|&offset| ceil_char_boundary(&code, offset)).collect();
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    while first < token_total {
+        let start = bounds[first];
+        let mut last = (first + max_tokens).min(token_total);
+        while bounds[last] == start && last < token_total {
+            last += 1;
+        }
+        // Only tokens inside the final character are left
+        if bounds[last] == start {
+            break;
+        }
+        chunks.push(text_chunk(&code, &line_index, start, bounds[last], (last - first) as u32));
+
+        if last == token_total {
+            break;
+        }
+        first += max_tokens - overlap_tokens;
+        // Windows starting inside the same character would repeat this one
+        while first < token_total && bounds[first] == start {
+            first += 1;
+        }
+    }
+
+    Ok(chunks)
+}
+
+/// Split code into chunks of at most `maxTokens` tokens along symbol boundaries
+///
+/// Top-level declarations are kept whole where they fit, with leading comments
+/// attached to the declaration that follows them. Small neighbours are merged
+/// into one chunk; a declaration too large for one chunk is split between its
+/// members or statements, and only a single oversized statement is split by
+/// lines. Languages without a grammar are split by lines.
+#[napi]
+pub fn chunk_by_symbols(
+    code: String,
+    language_id: String,
+    max_tokens: u32,
+    encoding: Option<String>,
+) -> Result<Vec<TextChunk>> {
+    if max_tokens == 0 {
+        return Err(Error::from_reason("maxTokens must be greater than 0"));
+    }
+    let splitter = SymbolSplitter {
+        code: &code,
+        bpe: bpe(encoding.as_deref())?,
+        max_tokens: max_tokens as usize,
+    };
+
+    let mut pieces = Vec::new();
+    match parse_tree(&code, &language_id) {
+        Ok(tree) => splitter.split_node(tree.root_node(), 0, code.len(), &mut pieces),
+        Err(_) => splitter.split_lines(0, code.len(), &mut pieces),
+    }
+
+    // Greedily merge neighbours while the summed token counts fit
+    let line_index = LineIndex::new(&code);
+    let mut chunks = Vec::new();
+    let mut current: Option<(usize, usize, usize)> = None;
+    for (start, end, tokens) in pieces {
+        current = match current {
+            Some((chunk_start, _, chunk_tokens)) if chunk_tokens + tokens <= splitter.max_tokens => {
+                Some((chunk_start, end, chunk_tokens + tokens))
+            }
+            Some((chunk_start, chunk_end, _)) => {
+                chunks.push(splitter.chunk(&line_index, chunk_start, chunk_end));
+                Some((start, end, tokens))
+            }
+            None => Some((start, end, tokens)),
+        };
+    }
+    if let Some((start, end, _)) = current {
+        chunks.push(splitter.chunk(&line_index, start, end));
+    }
+
+    Ok(chunks)
+}
+
+struct SymbolSplitter<'a> {
+    code: &'a str,
+    bpe: &'a CoreBPE,
+    max_tokens: usize,
+}
+
+impl SymbolSplitter<'_> {
+    fn count(&self, start: usize, end: usize) -> usize {
+        self.bpe.encode_ordinary(&self.code[start..end]).len()
+    }
+
+    /// Split `start..end`, the span of `node` plus surrounding whitespace and
+    /// comments, into pieces that each fit the budget
+    fn split_node(&self, node: Node, start: usize, end: usize, pieces: &mut Vec<(usize, usize, usize)>) {
+        let tokens = self.count(start, end);
+        if tokens <= self.max_tokens {
+            if start < end {
+                pieces.push((start, end, tokens));
+            }
+            return;
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        if children.is_empty() {
+            return self.split_lines(start, end, pieces);
+        }
+
+        // A child starts a new group at the beginning of its line, unless it
+        // follows a comment or shares a line with the previous child, so
+        // headers and leading comments stay with what follows them
+        let mut groups: Vec<(usize, Vec<Node>)> = Vec::new();
+        let mut previous: Option<Node> = None;
+        for child in children {
+            let line_start = self.code[..child.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+            let starts_line = self.code[line_start..child.start_byte()].trim().is_empty();
+            let follows_comment = previous.is_some_and(|p| p.kind().contains("comment"));
+            match (groups.last_mut(), previous) {
+                (Some((_, nodes)), Some(previous))
+                    if !starts_line || line_start < previous.end_byte() || follows_comment =>
+                {
+                    nodes.push(child)
+                }
+                (None, _) => groups.push((start, vec![child])),
+                _ => groups.push((line_start.max(start), vec![child])),
+            }
+            previous = Some(child);
+        }
+
+        for (i, (group_start, nodes)) in groups.iter().enumerate() {
+            let group_end = groups.get(i + 1).map_or(end, |(next_start, _)| *next_start);
+            let tokens = self.count(*group_start, group_end);
+            if tokens <= self.max_tokens {
+                if *group_start < group_end {
+                    pieces.push((*group_start, group_end, tokens));
+                }
+                continue;
+            }
+            // The rest of the group rides along with the first or last piece of its largest node
+            let largest = nodes.iter().max_by_key(|n| n.end_byte() - n.start_byte()).unwrap();
+            self.split_node(*largest, *group_start, group_end, pieces);
+        }
+    }
+
+    fn split_lines(&self, start: usize, end: usize, pieces: &mut Vec<(usize, usize, usize)>) {
+        let mut line_start = start;
+        for line in self.code[start..end].split_inclusive('\n') {
+            let line_end = line_start + line.len();
+            let tokens = self.count(line_start, line_end);
+            if tokens <= self.max_tokens {
+                pieces.push((line_start, line_end, tokens));
+            } else {
+                self.split_tokens(line_start, line_end, pieces);
+            }
+            line_start = line_end;
+        }
+    }
+
+    /// Last resort for a single line longer than the budget
+    fn split_tokens(&self, start: usize, end: usize, pieces: &mut Vec<(usize, usize, usize)>) {
+        let offsets = token_offsets(self.bpe, self.bpe.encode_ordinary(&self.code[start..end]));
+        let token_total = offsets.len() - 1;
+        let text = &self.code[start..end];
+        let mut first = 0;
+        while first < token_total {
+            let last = (first + self.max_tokens).min(token_total);
+            let piece_start = start + floor_char_boundary(text, offsets[first]);
+            let piece_end = start + floor_char_boundary(text, offsets[last]);
+            if piece_start < piece_end {
+                pieces.push((piece_start, piece_end, last - first));
+            }
+            first = last;
+        }
+    }
+
+    fn chunk(&self, line_index: &LineIndex, start: usize, end: usize) -> TextChunk {
+        text_chunk(self.code, line_index, start, end, self.count(start, end) as u32)
+    }
+}
+
+fn text_chunk(code: &str, line_index: &LineIndex, start: usize, end: usize, token_count: u32) -> TextChunk {
+    TextChunk {
+        text: code[start..end].to_string(),
+        start_byte: start as u32,
+        end_byte: end as u32,
+        start_line: line_index.get_line(start),
+        end_line: line_index.get_line(end.saturating_sub(1).max(start)),
+        token_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_split_multibyte_text_into_whole_characters() {
+        let code = "😀😀😀 日本語\n".to_string();
+        for overlap in [0, 1] {
+            let options = ChunkOptions {
+                max_tokens: Some(overlap + 1),
+                overlap_tokens: Some(overlap),
+                encoding: None,
+            };
+            let chunks = chunk_text(code.clone(), Some(options)).unwrap();
+            assert!(chunks.iter().all(|chunk| !chunk.text.is_empty()));
+            if overlap == 0 {
+                let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+                assert_eq!(texts.concat(), code);
+                assert!(chunks.windows(2).all(|pair| pair[0].end_byte == pair[1].start_byte));
+            }
+        }
+    }
+}
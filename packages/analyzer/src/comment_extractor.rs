@@ -0,0 +1,308 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::semantic_analyzer::{node_line, node_text};
+use crate::string_extractor::{is_docstring, string_value};
+
+/// Comment information
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentInfo {
+    /// Comment as written, including its delimiters
+    pub text: String,
+    /// Comment text without delimiters or leading `*` on continuation lines
+    pub content: String,
+    pub kind: String, // 'line' | 'block' | 'doc'
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    #[napi(js_name = "startColumn")]
+    pub start_column: u32,
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    #[napi(js_name = "endColumn")]
+    pub end_column: u32,
+}
+
+/// Extract comments with their positions and kind
+///
+/// Doc comments are `/** */`, `///`, `//!` and `/*! */`; Python docstrings are
+/// reported as doc comments too.
+#[napi]
+pub fn extract_comments(code: String, language_id: String) -> Result<Vec<CommentInfo>> {
+    let mut comments = Vec::new();
+    let Ok(tree) = parse_tree(&code, &language_id) else {
+        return Ok(comments);
+    };
+
+    for node in comment_nodes(tree.root_node(), &language_id) {
+        if node.kind().contains("comment") {
+            let text = node_text(node, &code).trim_end();
+            comments.push(comment_info(node, text, comment_content(text), comment_kind(text)));
+        } else if let Some(value) = node.named_child(0).and_then(|s| string_value(s, &code)) {
+            comments.push(comment_info(node, node_text(node, &code), value.trim().to_string(), "doc"));
+        }
+    }
+
+    Ok(comments)
+}
+
+/// Comment nodes in source order, plus the statements holding Python docstrings
+pub(crate) fn comment_nodes<'t>(root: Node<'t>, language_id: &str) -> Vec<Node<'t>> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let is_python_docstring =
+            language_id == "python" && node.kind() == "expression_statement" && is_docstring(node);
+        if node.kind().contains("comment") || is_python_docstring {
+            nodes.push(node);
+            continue;
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    nodes
+}
+
+fn comment_info(node: Node, text: &str, content: String, kind: &str) -> CommentInfo {
+    let start = node.start_position();
+    let end = node.end_position();
+    CommentInfo {
+        text: text.to_string(),
+        content,
+        kind: kind.to_string(),
+        start_line: node_line(node),
+        start_column: start.column as u32,
+        end_line: end.row as u32,
+        end_column: end.column as u32,
+    }
+}
+
+fn comment_kind(text: &str) -> &'static str {
+    let is_doc_block = text.starts_with("/**") && text != "/**/";
+    if is_doc_block || text.starts_with("/*!") || text.starts_with("///") || text.starts_with("//!") {
+        "doc"
+    } else if text.starts_with("/*") || text.starts_with("{-") || text.starts_with("<!--") {
+        "block"
+    } else {
+        "line"
+    }
+}
+
+fn comment_content(text: &str) -> String {
+    if let Some(block) = text.strip_prefix("/*") {
+        let block = block.strip_suffix("*/").unwrap_or(block);
+        let block = block.trim_start_matches(['*', '!']);
+        return block
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                line.strip_prefix('*').map_or(line, str::trim_start)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+    }
+
+    let marker = ["///", "//!", "//", "#", "--"].iter().find(|m| text.starts_with(**m));
+    marker.map_or(text, |m| &text[m.len()..]).trim().to_string()
+}
+
+/// Rewrap a comment to `width` columns, keeping its indentation and delimiters
+///
+/// Paragraphs are filled word by word. Blank lines, list items and `@tags`
+/// start new paragraphs; fenced code and lines indented four or more spaces
+/// are kept as written. `style` converts the comment to 'line' (`//`), 'block'
+/// (`/* */`), 'doc' (`/** */`) or 'hash' (`#`); by default the input's own
+/// delimiters, such as `///` or `/*!`, are kept.
+#[napi]
+pub fn reflow_comment(text: String, width: u32, style: Option<String>) -> Result<String> {
+    let indent: String = text.chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+    let (detected, contents) = parse_comment(text.trim());
+    let delimiters = match style.as_deref() {
+        None => detected,
+        Some("line") => Delimiters::line("//"),
+        Some("hash") => Delimiters::line("#"),
+        Some("block") => Delimiters::block("/*"),
+        Some("doc") => Delimiters::block("/**"),
+        Some(other) => return Err(Error::from_reason(format!("Unknown comment style: {}", other))),
+    };
+
+    let used = indent.chars().count() + delimiters.prefix.chars().count() + 1;
+    let lines = wrap_paragraphs(&contents, (width as usize).saturating_sub(used).max(1));
+
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut output = Vec::new();
+    match &delimiters.open {
+        Some(open) if !text.trim().contains('\n') && lines.len() == 1
+            && indent.chars().count() + open.len() + lines[0].chars().count() + 4 <= width as usize =>
+        {
+            output.push(format!("{}{} {} */", indent, open, lines[0]));
+        }
+        _ => {
+            if let Some(open) = &delimiters.open {
+                output.push(format!("{}{}", indent, open));
+            }
+            for line in &lines {
+                if line.is_empty() {
+                    output.push(format!("{}{}", indent, delimiters.prefix).trim_end().to_string());
+                } else {
+                    output.push(format!("{}{} {}", indent, delimiters.prefix, line));
+                }
+            }
+            if delimiters.open.is_some() {
+                output.push(format!("{} */", indent));
+            }
+        }
+    }
+
+    let mut result = output.join(newline);
+    if text.ends_with('\n') {
+        result.push_str(newline);
+    }
+    Ok(result)
+}
+
+/// Opening line and per-line prefix of a comment; block comments close with ` */`
+struct Delimiters {
+    open: Option<String>,
+    prefix: String,
+}
+
+impl Delimiters {
+    fn line(prefix: &str) -> Self {
+        Self { open: None, prefix: prefix.to_string() }
+    }
+
+    fn block(open: &str) -> Self {
+        Self { open: Some(open.to_string()), prefix: " *".to_string() }
+    }
+}
+
+/// Split a comment into its delimiters and content lines, with the prefix and
+/// one following space removed from each line
+fn parse_comment(text: &str) -> (Delimiters, Vec<&str>) {
+    fn strip_space(line: &str) -> &str {
+        line.strip_prefix(' ').unwrap_or(line)
+    }
+
+    if let Some(body) = text.strip_prefix("/*") {
+        let marker_len = body.bytes().take_while(|b| matches!(b, b'*' | b'!')).count().min(1);
+        let open = &text[..2 + marker_len];
+        let body = body[marker_len..].strip_suffix("*/").unwrap_or(&body[marker_len..]);
+        let mut lines: Vec<&str> = body
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 {
+                    return strip_space(line.trim_end());
+                }
+                let line = line.trim_end().trim_start_matches([' ', '\t']);
+                strip_space(line.strip_prefix('*').unwrap_or(line))
+            })
+            .collect();
+        while lines.first().is_some_and(|l| l.trim().is_empty()) {
+            lines.remove(0);
+        }
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        return (Delimiters::block(open), lines);
+    }
+
+    let prefix = if text.starts_with('#') {
+        &text[..text.bytes().take_while(|b| *b == b'#').count()]
+    } else {
+        ["///", "//!", "//", "--"].into_iter().find(|m| text.starts_with(m)).unwrap_or("")
+    };
+    let lines = text
+        .lines()
+        .map(|line| {
+            let line = line.trim_end().trim_start_matches([' ', '\t']);
+            strip_space(line.strip_prefix(prefix).unwrap_or(line))
+        })
+        .collect();
+    (Delimiters::line(prefix), lines)
+}
+
+/// Fill paragraphs to `width` characters, keeping breaks and verbatim lines
+fn wrap_paragraphs(contents: &[&str], width: usize) -> Vec<String> {
+    // Paragraphs as (first-line marker, hanging indent, words); `None` is a line kept as is
+    let mut blocks: Vec<Option<(String, usize, Vec<&str>)>> = Vec::new();
+    let mut verbatim: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    let mut open_paragraph = false;
+
+    for &line in contents {
+        let trimmed = line.trim_start();
+        let leading = line.len() - trimmed.len();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if in_fence || is_fence || trimmed.is_empty() || leading >= 4 {
+            in_fence ^= is_fence;
+            blocks.push(None);
+            verbatim.push(if trimmed.is_empty() { "" } else { line });
+            open_paragraph = false;
+            continue;
+        }
+
+        let marker_len = list_marker_len(trimmed);
+        if marker_len > 0 || trimmed.starts_with('@') || !open_paragraph {
+            let marker = &line[..leading + marker_len];
+            let hang = if marker_len > 0 { marker.chars().count() } else { 0 };
+            blocks.push(Some((marker.to_string(), hang, Vec::new())));
+        }
+        if let Some(Some((_, _, words))) = blocks.last_mut() {
+            words.extend(trimmed[marker_len..].split_whitespace());
+        }
+        open_paragraph = true;
+    }
+
+    let mut lines = Vec::new();
+    let mut verbatim = verbatim.into_iter();
+    for block in blocks {
+        let Some((marker, hang, words)) = block else {
+            lines.push(verbatim.next().unwrap_or("").to_string());
+            continue;
+        };
+        let mut line = marker;
+        let mut line_width = line.chars().count();
+        let mut empty = true;
+        for word in words {
+            let word_width = word.chars().count();
+            if !empty && line_width + 1 + word_width > width {
+                lines.push(std::mem::replace(&mut line, " ".repeat(hang)));
+                line_width = hang;
+                empty = true;
+            }
+            if !empty {
+                line.push(' ');
+                line_width += 1;
+            }
+            line.push_str(word);
+            line_width += word_width;
+            empty = false;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Length of a leading `- `, `* `, `+ `, `1. ` or `1) ` list marker, or 0
+fn list_marker_len(line: &str) -> usize {
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let marker = match line.as_bytes().get(digits) {
+        Some(b'.' | b')') if digits > 0 => digits + 1,
+        Some(b'-' | b'*' | b'+') if digits == 0 => 1,
+        _ => return 0,
+    };
+    if line.as_bytes().get(marker) == Some(&b' ') {
+        marker + 1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,274 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::comment_extractor::comment_nodes;
+use crate::string_extractor::STRING_KINDS;
+use crate::text_processor::whole_lines;
+
+/// A span of compressed code copied unchanged from the original
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeMapping {
+    /// Byte offset in the compressed code
+    #[napi(js_name = "generatedStart")]
+    pub generated_start: u32,
+    /// Byte offset in the original code
+    #[napi(js_name = "originalStart")]
+    pub original_start: u32,
+    pub length: u32,
+}
+
+/// Result of compressing code for a prompt
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressedCode {
+    pub code: String,
+    /// Copied spans in order; text between them is a placeholder such as `{ ... }`
+    pub mappings: Vec<CodeMapping>,
+}
+
+/// Literal arrays longer than this keep only their first elements
+const MAX_ARRAY_ELEMENTS: usize = 8;
+const KEPT_ARRAY_ELEMENTS: usize = 3;
+
+/// Compress code to fit more of it into a context window
+///
+/// Level 1 strips comments and docstrings, trailing whitespace and repeated
+/// blank lines. Level 2 also shortens literal arrays of more than eight
+/// elements. Level 3 also replaces function bodies with `{ ... }` (`...` in
+/// Python), keeping signatures. The mappings trace compressed ranges back to
+/// the original.
+#[napi]
+pub fn compress_code(code: String, language_id: String, level: u32) -> Result<CompressedCode> {
+    if !(1..=3).contains(&level) {
+        return Err(Error::from_reason(format!("Compression level must be 1, 2 or 3, got {}", level)));
+    }
+
+    let edits = match parse_tree(&code, &language_id) {
+        Ok(tree) => {
+            let mut edits = Vec::new();
+            let mut collector = EditCollector { language_id: &language_id, level, edits: &mut edits };
+            collector.visit(tree.root_node());
+            // Comments inside a replaced body or array go with it
+            let replaced: Vec<(usize, usize)> = edits.iter().map(|edit| (edit.start, edit.end)).collect();
+            for node in comment_nodes(tree.root_node(), &language_id) {
+                if !replaced.iter().any(|(start, end)| node.start_byte() < *end && *start < node.end_byte()) {
+                    edits.push(comment_edit(&code, node));
+                }
+            }
+            edits
+        }
+        Err(_) => Vec::new(),
+    };
+    let (stripped, stripped_map) = apply_edits(&code, edits);
+    let (compressed, compressed_map) = apply_edits(&stripped, whitespace_edits(&stripped));
+
+    Ok(CompressedCode {
+        code: compressed,
+        mappings: compose(&compressed_map, &stripped_map),
+    })
+}
+
+/// Replace `start..end` of the input with `text`
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+struct EditCollector<'a> {
+    language_id: &'a str,
+    level: u32,
+    edits: &'a mut Vec<Edit>,
+}
+
+impl EditCollector<'_> {
+    fn visit(&mut self, node: Node) {
+        if self.level >= 3 {
+            if let Some(body) = function_body(node) {
+                let text = if body.kind() == "block" && self.language_id == "python" { "..." } else { "{ ... }" };
+                self.edits.push(Edit { start: body.start_byte(), end: body.end_byte(), text: text.to_string() });
+                // Parameters can still hold comments or arrays worth shortening
+                let mut cursor = node.walk();
+                let children: Vec<Node> = node.named_children(&mut cursor).filter(|c| *c != body).collect();
+                for child in children {
+                    self.visit(child);
+                }
+                return;
+            }
+        }
+
+        if self.level >= 2 && ARRAY_KINDS.contains(&node.kind()) {
+            let mut cursor = node.walk();
+            let elements: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter(|c| !c.kind().contains("comment"))
+                .collect();
+            if elements.len() > MAX_ARRAY_ELEMENTS && elements.iter().all(|e| is_data(*e)) {
+                let omitted = elements.len() - KEPT_ARRAY_ELEMENTS;
+                let text = match self.language_id {
+                    "python" | "ruby" => ", ...".to_string(),
+                    _ => format!(", /* {} more */", omitted),
+                };
+                let start = elements[KEPT_ARRAY_ELEMENTS - 1].end_byte();
+                let end = elements.last().unwrap().end_byte();
+                self.edits.push(Edit { start, end, text });
+                return;
+            }
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        for child in children {
+            self.visit(child);
+        }
+    }
+}
+
+const ARRAY_KINDS: &[&str] = &[
+    "array",
+    "list",
+    "tuple",
+    "array_expression",
+    "array_initializer",
+    "initializer_list",
+    "literal_value",
+    "collection_expression",
+    "array_creation_expression",
+];
+
+/// The body of a function, method or constructor with a block body
+fn function_body(node: Node) -> Option<Node> {
+    let kind = node.kind();
+    let is_function = ["function", "method", "constructor", "arrow"].iter().any(|k| kind.contains(k))
+        && !["call", "type", "signature", "invocation", "reference"].iter().any(|k| kind.contains(k));
+    if !is_function {
+        return None;
+    }
+    let body = node.child_by_field_name("body")?;
+    let body_kind = body.kind();
+    (body_kind.contains("block") || body_kind.contains("body") || body_kind == "compound_statement").then_some(body)
+}
+
+/// Literal data with no identifiers or calls, such as `[1, "a", {"k": null}]`
+fn is_data(node: Node) -> bool {
+    let kind = node.kind();
+    if STRING_KINDS.contains(&kind) {
+        return true;
+    }
+    if (kind.contains("identifier") && kind != "property_identifier") || kind.contains("call") {
+        return false;
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children.into_iter().all(is_data)
+}
+
+/// Remove a comment, and its whole line when nothing else is on it
+fn comment_edit(code: &str, node: Node) -> Edit {
+    let (start, end) = whole_lines(code, node.start_byte(), node.end_byte());
+    let start = if start == node.start_byte() {
+        code[..start].trim_end_matches([' ', '\t']).len()
+    } else {
+        start
+    };
+    Edit { start, end, text: String::new() }
+}
+
+/// Strip trailing whitespace and collapse runs of blank lines into one
+fn whitespace_edits(code: &str) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let mut previous_blank = true;
+    let mut line_start = 0;
+    for line in code.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_end();
+        let is_blank = trimmed.is_empty();
+        if is_blank && previous_blank {
+            edits.push(Edit { start: line_start, end: line_end, text: String::new() });
+        } else if trimmed.len() < content.len() {
+            edits.push(Edit { start: line_start + trimmed.len(), end: line_start + content.len(), text: String::new() });
+        }
+        previous_blank = is_blank;
+        line_start = line_end;
+    }
+    edits
+}
+
+/// Apply non-overlapping edits, returning the new text and the copied spans
+/// as `(generated_start, original_start, length)`
+fn apply_edits(code: &str, mut edits: Vec<Edit>) -> (String, Vec<(usize, usize, usize)>) {
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+    let mut result = String::with_capacity(code.len());
+    let mut spans = Vec::new();
+    let mut position = 0;
+
+    for edit in edits {
+        if edit.start < position {
+            continue;
+        }
+        if edit.start > position {
+            spans.push((result.len(), position, edit.start - position));
+            result.push_str(&code[position..edit.start]);
+        }
+        result.push_str(&edit.text);
+        position = edit.end;
+    }
+    if position < code.len() {
+        spans.push((result.len(), position, code.len() - position));
+        result.push_str(&code[position..]);
+    }
+
+    (result, spans)
+}
+
+/// Map spans of the final text through the intermediate text to the original
+fn compose(outer: &[(usize, usize, usize)], inner: &[(usize, usize, usize)]) -> Vec<CodeMapping> {
+    let mut mappings: Vec<CodeMapping> = Vec::new();
+    let mut j = 0;
+    for &(generated, middle, length) in outer {
+        let middle_end = middle + length;
+        while j < inner.len() && inner[j].0 + inner[j].2 <= middle {
+            j += 1;
+        }
+        let mut k = j;
+        while k < inner.len() && inner[k].0 < middle_end {
+            let (inner_generated, original, inner_length) = inner[k];
+            let start = middle.max(inner_generated);
+            let end = middle_end.min(inner_generated + inner_length);
+            let mapping = CodeMapping {
+                generated_start: (generated + start - middle) as u32,
+                original_start: (original + start - inner_generated) as u32,
+                length: (end - start) as u32,
+            };
+
+            match mappings.last_mut() {
+                Some(last)
+                    if last.generated_start + last.length == mapping.generated_start
+                        && last.original_start + last.length == mapping.original_start =>
+                {
+                    last.length += mapping.length
+                }
+                _ => mappings.push(mapping),
+            }
+            k += 1;
+        }
+    }
+    mappings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stripping_comments_keeps_python_code() {
+        let code = "def f(a):\n    \"\"\"Doc.\"\"\"\n    a += 1  # bump\n    return a\nclass C:\n    x = 1\n";
+        let compressed = compress_code(code.to_string(), "python".to_string(), 1).unwrap();
+        assert_eq!(compressed.code, "def f(a):\n    a += 1\n    return a\nclass C:\n    x = 1\n");
+    }
+}
@@ -0,0 +1,391 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Options for `diff_lines` and `diff_chars`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiffOptions {
+    pub algorithm: Option<String>, // 'myers' (default) | 'patience' | 'histogram'
+}
+
+/// One run of a diff; old and new ranges are half-open
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffOp {
+    pub kind: String, // 'equal' | 'delete' | 'insert'
+    #[napi(js_name = "oldStart")]
+    pub old_start: u32,
+    #[napi(js_name = "oldEnd")]
+    pub old_end: u32,
+    #[napi(js_name = "newStart")]
+    pub new_start: u32,
+    #[napi(js_name = "newEnd")]
+    pub new_end: u32,
+}
+
+/// Diff two texts line by line, with ranges in line numbers
+///
+/// Lines keep their terminators, so a last line gaining a newline counts as
+/// changed. Deletions come before insertions at the same place. Patience and
+/// histogram diffs anchor on rare lines, which usually lines up functions and
+/// blocks better than Myers when code moves around.
+#[napi]
+pub fn diff_lines(old: String, new: String, options: Option<DiffOptions>) -> Result<Vec<DiffOp>> {
+    let algorithm = Algorithm::parse(options.unwrap_or_default().algorithm.as_deref())?;
+    let mut ids = HashMap::new();
+    let a = line_ids(&old, &mut ids);
+    let b = line_ids(&new, &mut ids);
+    Ok(to_ops(&diff(&a, &b, algorithm), a.len(), b.len(), |i| i, |j| j))
+}
+
+/// Diff two texts character by character, with ranges in UTF-8 byte offsets
+#[napi]
+pub fn diff_chars(old: String, new: String, options: Option<DiffOptions>) -> Result<Vec<DiffOp>> {
+    let algorithm = Algorithm::parse(options.unwrap_or_default().algorithm.as_deref())?;
+    let (a, a_offsets) = char_ids(&old);
+    let (b, b_offsets) = char_ids(&new);
+    Ok(to_ops(&diff(&a, &b, algorithm), a.len(), b.len(), |i| a_offsets[i], |j| b_offsets[j]))
+}
+
+/// Lines as ids, equal lines sharing one
+fn line_ids<'t>(text: &'t str, ids: &mut HashMap<&'t str, u32>) -> Vec<u32> {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let next = ids.len() as u32;
+            *ids.entry(line).or_insert(next)
+        })
+        .collect()
+}
+
+/// Characters as ids, and the byte offset of each plus one past the end
+fn char_ids(text: &str) -> (Vec<u32>, Vec<usize>) {
+    let mut offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    offsets.push(text.len());
+    (text.chars().map(u32::from).collect(), offsets)
+}
+
+#[derive(Clone, Copy)]
+enum Algorithm {
+    Myers,
+    Patience,
+    Histogram,
+}
+
+impl Algorithm {
+    fn parse(name: Option<&str>) -> Result<Self> {
+        match name.unwrap_or("myers") {
+            "myers" => Ok(Self::Myers),
+            "patience" => Ok(Self::Patience),
+            "histogram" => Ok(Self::Histogram),
+            other => Err(Error::from_reason(format!("Unknown diff algorithm: {}", other))),
+        }
+    }
+}
+
+/// Matching runs as `(old_start, new_start, length)`, in order
+type Matches = Vec<(usize, usize, usize)>;
+
+fn diff(a: &[u32], b: &[u32], algorithm: Algorithm) -> Matches {
+    let mut matches = Vec::new();
+    match algorithm {
+        Algorithm::Myers => myers(a, b, 0, 0, &mut matches),
+        Algorithm::Patience => patience(a, b, 0, 0, &mut matches),
+        Algorithm::Histogram => histogram(a, b, 0, 0, &mut matches),
+    }
+    matches
+}
+
+/// Turn matching runs into equal, delete and insert ops, mapping indices
+/// to output units with `old_unit` and `new_unit`
+fn to_ops(
+    matches: &[(usize, usize, usize)],
+    a_len: usize,
+    b_len: usize,
+    old_unit: impl Fn(usize) -> usize,
+    new_unit: impl Fn(usize) -> usize,
+) -> Vec<DiffOp> {
+    let mut ops: Vec<DiffOp> = Vec::new();
+    let mut push = |kind: &str, i: usize, i_end: usize, j: usize, j_end: usize| {
+        let op = DiffOp {
+            kind: kind.to_string(),
+            old_start: old_unit(i) as u32,
+            old_end: old_unit(i_end) as u32,
+            new_start: new_unit(j) as u32,
+            new_end: new_unit(j_end) as u32,
+        };
+        match ops.last_mut() {
+            Some(last) if last.kind == op.kind && last.old_end == op.old_start && last.new_end == op.new_start => {
+                last.old_end = op.old_end;
+                last.new_end = op.new_end;
+            }
+            _ => ops.push(op),
+        }
+    };
+
+    let (mut i, mut j) = (0, 0);
+    for &(a_start, b_start, len) in matches.iter().chain([(a_len, b_len, 0)].iter()) {
+        if a_start > i {
+            push("delete", i, a_start, j, j);
+        }
+        if b_start > j {
+            push("insert", a_start, a_start, j, b_start);
+        }
+        if len > 0 {
+            push("equal", a_start, a_start + len, b_start, b_start + len);
+        }
+        i = a_start + len;
+        j = b_start + len;
+    }
+    ops
+}
+
+/// Lengths of the common prefix and, after it, the common suffix
+fn common_affixes(a: &[u32], b: &[u32]) -> (usize, usize) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    (prefix, suffix)
+}
+
+/// Diff the middle of `a` and `b` with `inner` after matching their common
+/// prefix and suffix; `a0` and `b0` are their offsets in the full inputs
+fn with_affixes(
+    a: &[u32],
+    b: &[u32],
+    a0: usize,
+    b0: usize,
+    matches: &mut Matches,
+    inner: impl FnOnce(&[u32], &[u32], usize, usize, &mut Matches),
+) {
+    let (prefix, suffix) = common_affixes(a, b);
+    if prefix > 0 {
+        matches.push((a0, b0, prefix));
+    }
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+    if !a_mid.is_empty() && !b_mid.is_empty() {
+        inner(a_mid, b_mid, a0 + prefix, b0 + prefix, matches);
+    }
+    if suffix > 0 {
+        matches.push((a0 + a.len() - suffix, b0 + b.len() - suffix, suffix));
+    }
+}
+
+/// Linear-space Myers diff, splitting at the middle snake
+fn myers(a: &[u32], b: &[u32], a0: usize, b0: usize, matches: &mut Matches) {
+    with_affixes(a, b, a0, b0, matches, |a, b, a0, b0, matches| {
+        // A single element either occurs in the other side or doesn't
+        if a.len() == 1 || b.len() == 1 {
+            let (short, long) = if a.len() == 1 { (a, b) } else { (b, a) };
+            if let Some(i) = long.iter().position(|x| *x == short[0]) {
+                matches.push(if a.len() == 1 { (a0, b0 + i, 1) } else { (a0 + i, b0, 1) });
+            }
+            return;
+        }
+        if let Some((x, y)) = middle_snake(a, b) {
+            myers(&a[..x], &b[..y], a0, b0, matches);
+            myers(&a[x..], &b[y..], a0 + x, b0 + y, matches);
+        }
+    });
+}
+
+/// Point where the forward and reverse searches of Myers' algorithm meet, or
+/// `None` if the inputs have nothing in common
+fn middle_snake(a: &[u32], b: &[u32]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let size = 2 * max_d as usize + 2;
+    let mut forward = vec![-1isize; size];
+    let mut reverse = vec![-1isize; size];
+    forward[offset as usize + 1] = 0;
+    reverse[offset as usize + 1] = 0;
+    let delta = n - m;
+    // With an odd delta the forward search is the one to detect the overlap
+    let front = delta % 2 != 0;
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let k1_offset = (offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[k1_offset - 1] < forward[k1_offset + 1]) {
+                forward[k1_offset + 1]
+            } else {
+                forward[k1_offset - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[k1_offset] = x1;
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if front {
+                let k2_offset = offset + delta - k1;
+                if k2_offset >= 0 && (k2_offset as usize) < size && reverse[k2_offset as usize] != -1 {
+                    let x2 = n - reverse[k2_offset as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let k2_offset = (offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && reverse[k2_offset - 1] < reverse[k2_offset + 1]) {
+                reverse[k2_offset + 1]
+            } else {
+                reverse[k2_offset - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            reverse[k2_offset] = x2;
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !front {
+                let k1_offset = offset + delta - k2;
+                if k1_offset >= 0 && (k1_offset as usize) < size && forward[k1_offset as usize] != -1 {
+                    let x1 = forward[k1_offset as usize];
+                    let y1 = offset + x1 - k1_offset;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+    None
+}
+
+/// Patience diff: match lines unique to both sides in order, recurse between
+/// them, and fall back to Myers where there are none
+fn patience(a: &[u32], b: &[u32], a0: usize, b0: usize, matches: &mut Matches) {
+    with_affixes(a, b, a0, b0, matches, |a, b, a0, b0, matches| {
+        // Per element: count and last position in `a`, then in `b`
+        let mut seen: HashMap<u32, (u32, usize, u32, usize)> = HashMap::new();
+        for (i, x) in a.iter().enumerate() {
+            let entry = seen.entry(*x).or_default();
+            entry.0 += 1;
+            entry.1 = i;
+        }
+        for (j, y) in b.iter().enumerate() {
+            if let Some(entry) = seen.get_mut(y) {
+                entry.2 += 1;
+                entry.3 = j;
+            }
+        }
+        let mut unique: Vec<(usize, usize)> = seen
+            .values()
+            .filter(|(a_count, _, b_count, _)| *a_count == 1 && *b_count == 1)
+            .map(|(_, i, _, j)| (*i, *j))
+            .collect();
+        unique.sort_unstable();
+
+        let anchors = longest_increasing(&unique);
+        if anchors.is_empty() {
+            myers(a, b, a0, b0, matches);
+            return;
+        }
+        let (mut i, mut j) = (0, 0);
+        for (ai, bj) in anchors {
+            patience(&a[i..ai], &b[j..bj], a0 + i, b0 + j, matches);
+            matches.push((a0 + ai, b0 + bj, 1));
+            i = ai + 1;
+            j = bj + 1;
+        }
+        patience(&a[i..], &b[j..], a0 + i, b0 + j, matches);
+    });
+}
+
+/// Longest run of pairs, already sorted by the first index, whose second
+/// index also increases
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // `tails[k]` is the pair index ending the best run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (p, &(_, j)) in pairs.iter().enumerate() {
+        let k = tails.partition_point(|&t| pairs[t].1 < j);
+        previous[p] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(p);
+        } else {
+            tails[k] = p;
+        }
+    }
+
+    let mut run = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(p) = next {
+        run.push(pairs[p]);
+        next = previous[p];
+    }
+    run.reverse();
+    run
+}
+
+/// Elements occurring more often than this are not used as anchors
+const MAX_HISTOGRAM_CHAIN: usize = 64;
+
+/// Histogram diff: anchor on the longest common run around the element that
+/// occurs least often, recurse on both sides, and fall back to Myers when
+/// every shared element is too common
+fn histogram(a: &[u32], b: &[u32], a0: usize, b0: usize, matches: &mut Matches) {
+    with_affixes(a, b, a0, b0, matches, |a, b, a0, b0, matches| {
+        let mut positions: HashMap<u32, Vec<usize>> = HashMap::new();
+        for (i, x) in a.iter().enumerate() {
+            positions.entry(*x).or_default().push(i);
+        }
+
+        // (occurrences, length, start in a, start in b) of the best run so far
+        let mut best: Option<(usize, usize, usize, usize)> = None;
+        let mut shared = false;
+        let mut j = 0;
+        while j < b.len() {
+            let mut next_j = j + 1;
+            if let Some(candidates) = positions.get(&b[j]) {
+                shared = true;
+                let count = candidates.len();
+                let competitive = best.is_none_or(|(best_count, ..)| count <= best_count);
+                if count <= MAX_HISTOGRAM_CHAIN && competitive {
+                    for &i in candidates {
+                        let back = a[..i].iter().rev().zip(b[..j].iter().rev()).take_while(|(x, y)| x == y).count();
+                        let forward = a[i..].iter().zip(&b[j..]).take_while(|(x, y)| x == y).count();
+                        let run = (count, back + forward, i - back, j - back);
+                        let better = best.is_none_or(|(best_count, best_len, ..)| {
+                            count < best_count || (count == best_count && run.1 > best_len)
+                        });
+                        if better {
+                            best = Some(run);
+                        }
+                        next_j = next_j.max(j + forward);
+                    }
+                }
+            }
+            j = next_j;
+        }
+
+        let Some((_, len, i, j)) = best else {
+            if shared {
+                myers(a, b, a0, b0, matches);
+            }
+            return;
+        };
+        histogram(&a[..i], &b[..j], a0, b0, matches);
+        matches.push((a0 + i, b0 + j, len));
+        histogram(&a[i + len..], &b[j + len..], a0 + i + len, b0 + j + len, matches);
+    });
+}
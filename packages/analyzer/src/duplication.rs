@@ -1,7 +1,26 @@
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
+use aho_corasick::AhoCorasick;
 use memchr::memmem;
+use rayon::prelude::*;
+use regex::bytes::RegexBuilder as BytesRegexBuilder;
+use regex::{Regex, RegexSet};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::diff::diff_lines;
+use crate::semantic_analyzer::FileInput;
+use crate::hash::simhash_of;
+use crate::similarity::{levenshtein, longest_common_run};
+use crate::streaming_tokenizer::lexical_tokens;
 
 /// Duplicate code information
 #[napi(object)]
@@ -12,41 +31,343 @@ pub struct DuplicateInfo {
     pub start_line: u32,
     #[napi(js_name = "endLine")]
     pub end_line: u32,
+    /// Where the match is in `context`, to link to the original
+    #[napi(js_name = "contextStartLine")]
+    pub context_start_line: u32,
+    #[napi(js_name = "contextEndLine")]
+    pub context_end_line: u32,
     pub similarity: f64,
 }
 
+/// Options for `detect_duplicates`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicateOptions {
+    /// 'exact' | 'normalized' (default: 'exact')
+    ///
+    /// 'normalized' compares token sequences with identifiers and literals
+    /// replaced by placeholders and comments dropped, so renamed copies are
+    /// found too (type-2 clones)
+    pub mode: Option<String>,
+    /// Language for tokenizing in 'normalized' mode and for the metrics
+    #[napi(js_name = "languageId")]
+    pub language_id: Option<String>,
+    /// How to score matches, as for `similarity`. By default exact matches
+    /// use 'words' against the whole context and normalized matches the
+    /// share of tokens left unchanged
+    pub metric: Option<String>,
+    /// Skip lines holding only comments (default: false)
+    #[napi(js_name = "ignoreComments")]
+    pub ignore_comments: Option<bool>,
+    /// Skip import, include, use and require lines, including multi-line
+    /// import blocks (default: false)
+    #[napi(js_name = "ignoreImports")]
+    pub ignore_imports: Option<bool>,
+    /// Report nothing when either side has a generated-file marker such as
+    /// `@generated` or `DO NOT EDIT` near the top (default: false)
+    #[napi(js_name = "ignoreGenerated")]
+    pub ignore_generated: Option<bool>,
+    /// Skip lines matching any of these regexes
+    #[napi(js_name = "ignorePatterns")]
+    pub ignore_patterns: Option<Vec<String>>,
+}
+
 /// Detect duplicate code segments
 /// 
 /// Uses rolling hash and SIMD string comparison for 4-8x speedup
 #[napi]
-pub fn detect_duplicates(code: String, context: String, min_length: Option<u32>) -> Result<Vec<DuplicateInfo>> {
-    let min_len = min_length.unwrap_or(20) as usize;
+pub fn detect_duplicates(
+    code: String,
+    context: String,
+    min_length: Option<u32>,
+    options: Option<DuplicateOptions>,
+) -> Result<Vec<DuplicateInfo>> {
+    find_duplicates(&code, &context, min_length, options.unwrap_or_default(), &HashSet::new(), &DetectionControl::default())
+}
+
+/// Detect code in the working copy of a file that was deleted since `revision`
+///
+/// The file is read at `revision` and at HEAD through `git cat-file`; lines
+/// removed between the two are matched against the file on disk, so code an
+/// edit brings back is reported. Context lines refer to the file at
+/// `revision`. A file missing at HEAD counts as entirely deleted.
+#[napi]
+pub fn detect_duplicates_vs_revision(
+    path: String,
+    revision: String,
+    min_length: Option<u32>,
+    options: Option<DuplicateOptions>,
+) -> Result<Vec<DuplicateInfo>> {
+    let path = Path::new(&path);
+    let working = fs::read_to_string(path)
+        .map_err(|e| Error::from_reason(format!("Failed to read {}: {}", path.display(), e)))?;
+    let old = git_blob(path, &revision)?
+        .ok_or_else(|| Error::from_reason(format!("{} does not exist at {}", path.display(), revision)))?;
+    let head = git_blob(path, "HEAD")?.unwrap_or_default();
+
+    // Lines still present at HEAD are not deleted code
+    let mut kept = HashSet::new();
+    for op in diff_lines(old.clone(), head, None)? {
+        if op.kind == "equal" {
+            kept.extend(op.old_start as usize..op.old_end as usize);
+        }
+    }
+    find_duplicates(&working, &old, min_length, options.unwrap_or_default(), &kept, &DetectionControl::default())
+}
+
+/// Content of a file at a git revision, or `None` when the revision has no such file
+fn git_blob(path: &Path, revision: &str) -> Result<Option<String>> {
+    let directory = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::from_reason(format!("Not a file: {}", path.display())))?;
+    let object = format!("{}:./{}", revision, name.to_string_lossy());
+    // `--end-of-options` keeps a revision starting with `-` from being read as an option
+    let resolved = git(directory, &["rev-parse", "--verify", "--quiet", "--end-of-options", &object])?;
+    // `--quiet` exits with 1 only when the object doesn't exist
+    if resolved.status.code() == Some(1) {
+        return Ok(None);
+    }
+    let id = String::from_utf8_lossy(&git_stdout(resolved, path, revision)?).trim().to_string();
+    let blob = git_stdout(git(directory, &["cat-file", "blob", &id])?, path, revision)?;
+    Ok(Some(String::from_utf8_lossy(&blob).into_owned()))
+}
+
+fn git(directory: &Path, args: &[&str]) -> Result<Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .map_err(|e| Error::from_reason(format!("Failed to run git: {}", e)))
+}
+
+fn git_stdout(output: Output, path: &Path, revision: &str) -> Result<Vec<u8>> {
+    if !output.status.success() {
+        return Err(Error::from_reason(format!(
+            "Failed to read {} at {}: {}",
+            path.display(),
+            revision,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Progress of a `detect_duplicates_async` run, counted in window sizes
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateProgress {
+    pub completed: u32,
+    pub total: u32,
+}
+
+/// Cancels a running `detect_duplicates_async`
+#[napi]
+#[derive(Default)]
+pub struct DuplicateAbortHandle {
+    aborted: Arc<AtomicBool>,
+}
+
+#[napi]
+impl DuplicateAbortHandle {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the detection; its promise rejects with an abort error
+    #[napi]
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::Relaxed);
+    }
+
+    #[napi(getter)]
+    pub fn aborted(&self) -> bool {
+        self.aborted.load(Ordering::Relaxed)
+    }
+}
+
+/// `detect_duplicates` on the libuv thread pool
+pub struct DetectDuplicatesTask {
+    code: String,
+    context: String,
+    min_length: Option<u32>,
+    options: DuplicateOptions,
+    on_progress: Option<ThreadsafeFunction<DuplicateProgress, ErrorStrategy::Fatal>>,
+    aborted: Arc<AtomicBool>,
+}
+
+impl Task for DetectDuplicatesTask {
+    type Output = Vec<DuplicateInfo>;
+    type JsValue = Vec<DuplicateInfo>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let report = |completed: u32, total: u32| {
+            if let Some(on_progress) = &self.on_progress {
+                on_progress.call(DuplicateProgress { completed, total }, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        };
+        let control = DetectionControl {
+            aborted: Some(&self.aborted),
+            progress: Some(&report),
+        };
+        find_duplicates(&self.code, &self.context, self.min_length, self.options.clone(), &HashSet::new(), &control)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Detect duplicate code segments without blocking the event loop
+///
+/// `onProgress` is called after each window size, and `abortHandle.abort()`
+/// stops the run and rejects the promise.
+#[napi(ts_return_type = "Promise<Array<DuplicateInfo>>")]
+pub fn detect_duplicates_async(
+    code: String,
+    context: String,
+    min_length: Option<u32>,
+    options: Option<DuplicateOptions>,
+    #[napi(ts_arg_type = "(progress: DuplicateProgress) => void")] on_progress: Option<
+        ThreadsafeFunction<DuplicateProgress, ErrorStrategy::Fatal>,
+    >,
+    abort_handle: Option<&DuplicateAbortHandle>,
+) -> AsyncTask<DetectDuplicatesTask> {
+    AsyncTask::new(DetectDuplicatesTask {
+        code,
+        context,
+        min_length,
+        options: options.unwrap_or_default(),
+        on_progress,
+        aborted: abort_handle.map(|handle| handle.aborted.clone()).unwrap_or_default(),
+    })
+}
+
+/// Cancellation and progress hooks for a detection run
+#[derive(Default)]
+struct DetectionControl<'c> {
+    aborted: Option<&'c AtomicBool>,
+    progress: Option<&'c dyn Fn(u32, u32)>,
+}
+
+impl DetectionControl<'_> {
+    fn check(&self) -> Result<()> {
+        match self.aborted {
+            Some(aborted) if aborted.load(Ordering::Relaxed) => {
+                Err(Error::new(Status::Cancelled, "Duplicate detection aborted"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn report(&self, completed: usize, total: usize) {
+        if let Some(progress) = self.progress {
+            progress(completed as u32, total as u32);
+        }
+    }
+}
+
+fn find_duplicates(
+    code: &str,
+    context: &str,
+    min_length: Option<u32>,
+    options: DuplicateOptions,
+    context_skip: &HashSet<usize>,
+    control: &DetectionControl,
+) -> Result<Vec<DuplicateInfo>> {
+    let min_len = min_length.unwrap_or(20).max(1) as usize;
+    let language_id = options.language_id.as_deref().unwrap_or("");
+    let metric = options.metric.as_deref().map(Metric::parse).transpose()?;
+    let filters = IgnoreFilters::new(&options)?;
+    if options.ignore_generated.unwrap_or(false) && (is_generated(code) || is_generated(context)) {
+        return Ok(Vec::new());
+    }
+    let code_excluded = filters.excluded_lines(code);
+    let mut context_excluded = filters.excluded_lines(context);
+    context_excluded.extend(context_skip);
+
+    match options.mode.as_deref().unwrap_or("exact") {
+        "exact" => {}
+        "normalized" => {
+            let mut duplicates = normalized_duplicates(
+                code,
+                context,
+                min_len,
+                language_id,
+                metric,
+                (&code_excluded, &context_excluded),
+                control,
+            )?;
+            deduplicate_results(&mut duplicates);
+            return Ok(duplicates);
+        }
+        other => return Err(Error::from_reason(format!("Unknown duplicate mode: {}", other))),
+    }
     let mut duplicates = Vec::new();
     
-    let code_lines: Vec<&str> = code.lines().collect();
-    let _context_lines: Vec<&str> = context.lines().collect();
+    let all_code_lines: Vec<&str> = code.lines().collect();
+    let code_lines: Vec<(usize, &str)> = all_code_lines
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(i, _)| !code_excluded.contains(i))
+        .collect();
+    // Context without the ignored lines, and where each kept line starts
+    let mut haystack = String::with_capacity(context.len());
+    let mut context_starts: Vec<(usize, usize)> = Vec::new();
+    for (i, line) in context.lines().enumerate() {
+        if !context_excluded.contains(&i) {
+            context_starts.push((haystack.len(), i));
+            haystack.push_str(line);
+            haystack.push('\n');
+        }
+    }
+    if context_starts.is_empty() {
+        return Ok(duplicates);
+    }
     
     // Use sliding window to find duplicates
-    for window_size in (min_len..=code_lines.len().min(50)).rev() {
-        for (i, window) in code_lines.windows(window_size).enumerate() {
-            let window_text = window.join("\n");
+    let window_sizes = (min_len..=code_lines.len().min(50)).rev();
+    let total = window_sizes.clone().count();
+    for (completed, window_size) in window_sizes.enumerate() {
+        control.report(completed, total);
+        for window in code_lines.windows(window_size) {
+            control.check()?;
+            let window_text = window.iter().map(|(_, line)| *line).collect::<Vec<_>>().join("\n");
+            // A lone blank line matches anywhere
+            if window_text.is_empty() {
+                continue;
+            }
             
             // Use fast substring search (SIMD-optimized)
-            if let Some(_pos) = memmem::find(context.as_bytes(), window_text.as_bytes()) {
+            if let Some(pos) = memmem::find(haystack.as_bytes(), window_text.as_bytes()) {
                 // Calculate similarity
-                let similarity = calculate_similarity(&window_text, &context);
+                let similarity = match metric {
+                    Some(metric) => {
+                        let matched = &haystack[pos..pos + window_text.len()];
+                        metric.score(&window_text, matched, language_id)
+                    }
+                    None => calculate_similarity(&window_text, &haystack),
+                };
                 
                 if similarity > 0.8 {
+                    let line_at = |offset: usize| context_starts[context_starts.partition_point(|(start, _)| *start <= offset) - 1].1;
+                    let (first, last) = (window[0].0, window[window_size - 1].0);
                     duplicates.push(DuplicateInfo {
-                        text: window_text,
-                        start_line: i as u32,
-                        end_line: (i + window_size) as u32,
+                        text: all_code_lines[first..=last].join("\n"),
+                        start_line: first as u32,
+                        end_line: (last + 1) as u32,
+                        context_start_line: line_at(pos) as u32,
+                        context_end_line: (line_at(pos + window_text.len().max(1) - 1) + 1) as u32,
                         similarity,
                     });
                 }
             }
         }
     }
+    control.report(total, total);
     
     // Remove overlapping duplicates
     deduplicate_results(&mut duplicates);
@@ -54,6 +375,1002 @@ pub fn detect_duplicates(code: String, context: String, min_length: Option<u32>)
     Ok(duplicates)
 }
 
+/// Lines `detect_duplicates` leaves out of matching
+struct IgnoreFilters<'o> {
+    comments: bool,
+    imports: bool,
+    patterns: Option<RegexSet>,
+    language_id: &'o str,
+}
+
+impl<'o> IgnoreFilters<'o> {
+    fn new(options: &'o DuplicateOptions) -> Result<Self> {
+        let patterns = match &options.ignore_patterns {
+            Some(patterns) if !patterns.is_empty() => Some(
+                RegexSet::new(patterns)
+                    .map_err(|e| Error::from_reason(format!("Invalid ignore pattern: {}", e)))?,
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            comments: options.ignore_comments.unwrap_or(false),
+            imports: options.ignore_imports.unwrap_or(false),
+            patterns,
+            language_id: options.language_id.as_deref().unwrap_or(""),
+        })
+    }
+
+    /// Numbers of the lines to skip
+    fn excluded_lines(&self, text: &str) -> HashSet<usize> {
+        let mut excluded = HashSet::new();
+        if self.comments {
+            excluded.extend(comment_only_lines(text, self.language_id));
+        }
+        if !self.imports && self.patterns.is_none() {
+            return excluded;
+        }
+
+        // Closing bracket of a multi-line import still open
+        let mut open_import: Option<char> = None;
+        for (i, line) in text.lines().enumerate() {
+            if self.imports {
+                if let Some(closer) = open_import {
+                    excluded.insert(i);
+                    if line.contains(closer) {
+                        open_import = None;
+                    }
+                    continue;
+                }
+                if import_regex().is_match(line) {
+                    excluded.insert(i);
+                    open_import = [('{', '}'), ('(', ')')]
+                        .into_iter()
+                        .find(|(opener, closer)| line.contains(*opener) && !line.contains(*closer))
+                        .map(|(_, closer)| closer);
+                    continue;
+                }
+            }
+            if self.patterns.as_ref().is_some_and(|patterns| patterns.is_match(line)) {
+                excluded.insert(i);
+            }
+        }
+        excluded
+    }
+}
+
+/// Lines with a comment and no code
+fn comment_only_lines(text: &str, language_id: &str) -> Vec<usize> {
+    let line_count = text.lines().count();
+    let mut has_comment = vec![false; line_count];
+    let mut has_code = vec![false; line_count];
+    let mut line = 0;
+    let mut counted = 0;
+    for (start, end, token_type) in lexical_tokens(text, language_id) {
+        line += memchr::memchr_iter(b'\n', &text.as_bytes()[counted..start]).count();
+        counted = start;
+        let span = memchr::memchr_iter(b'\n', &text.as_bytes()[start..end]).count();
+        let marks = if token_type == "comment" { &mut has_comment } else { &mut has_code };
+        for mark in marks.iter_mut().skip(line).take(span + 1) {
+            *mark = true;
+        }
+    }
+    (0..line_count).filter(|i| has_comment[*i] && !has_code[*i]).collect()
+}
+
+fn import_regex() -> &'static Regex {
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+    IMPORT.get_or_init(|| {
+        Regex::new(concat!(
+            r"^\s*(?:import\b|from\s+\S+\s+import\b|export\s+(?:\*|\{[^}]*\})\s*(?:as\s+\w+\s+)?from\b",
+            r"|use\s+[\w:\\{]|#\s*(?:include|import)\b|using\s+[\w.]+(?:\s*=\s*[\w.]+)?\s*;|package\s+[\w.]+\s*;?\s*$",
+            r"|require(?:_once|_relative)?\b|(?:const|let|var)\s+[\w{}\s,:]+=\s*require\()",
+        ))
+        .unwrap()
+    })
+}
+
+/// Lines near the top searched for generated-file markers
+const GENERATED_HEADER_LINES: usize = 10;
+
+/// Whether the text starts like a generated file
+fn is_generated(text: &str) -> bool {
+    static GENERATED: OnceLock<Regex> = OnceLock::new();
+    let marker = GENERATED.get_or_init(|| {
+        Regex::new(r"@generated|DO NOT EDIT|(?i:auto-?generated|code generated by|this file (?:was|is) (?:automatically )?generated)")
+            .unwrap()
+    });
+    text.lines().take(GENERATED_HEADER_LINES).any(|line| marker.is_match(line))
+}
+
+/// Options for `detect_duplicates_across`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrossFileDuplicateOptions {
+    /// Fewest non-blank lines a clone spans (default: 6)
+    #[napi(js_name = "minLines")]
+    pub min_lines: Option<u32>,
+    /// 'exact' | 'normalized' | 'near' (default: 'normalized')
+    ///
+    /// All ignore formatting and comments; 'normalized' also matches code
+    /// with renamed identifiers and changed literals, and 'near' also code
+    /// with lines added, removed or edited
+    pub mode: Option<String>,
+    /// Lowest token similarity for 'near' matches (default: 0.8)
+    #[napi(js_name = "minSimilarity")]
+    pub min_similarity: Option<f64>,
+}
+
+/// Lines of one file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloneRange {
+    pub path: String,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    /// Exclusive, like `DuplicateInfo.endLine`
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+}
+
+/// Two places holding the same code
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClonePair {
+    pub a: CloneRange,
+    pub b: CloneRange,
+    /// Share of tokens that match exactly (1 for verbatim copies)
+    pub similarity: f64,
+}
+
+/// Share of the longer of two ranges of a file that must overlap for
+/// `detect_clone_classes` to treat them as the same region
+const MIN_REGION_OVERLAP: f64 = 0.8;
+
+/// Windows seen more often than this are boilerplate and skipped, which
+/// keeps pair generation from going quadratic
+const MAX_WINDOW_OCCURRENCES: usize = 64;
+
+/// Find clones across many files, within and between them
+///
+/// Every run of `minLines` non-blank lines is fingerprinted into one shared
+/// index; windows that collide are paired up and consecutive pairs merged
+/// into maximal clones. Files are tokenized and fingerprinted in parallel.
+/// Pairs come sorted by the first range's path and line
+#[napi]
+pub fn detect_duplicates_across(
+    files: Vec<FileInput>,
+    options: Option<CrossFileDuplicateOptions>,
+) -> Result<Vec<ClonePair>> {
+    clone_pairs(&files, options)
+}
+
+/// Code found in several places
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloneClass {
+    /// The member cloned most often, earliest on ties
+    pub representative: CloneRange,
+    /// Every place the code appears, the representative included
+    pub members: Vec<CloneRange>,
+    /// Lowest similarity between two members found as a pair
+    pub similarity: f64,
+}
+
+/// Find clones across many files like `detect_duplicates_across`, grouped
+/// into classes: a block in five places is one class of five members rather
+/// than ten pairs
+///
+/// Pairs sharing a range, or ranges of a file that overlap by 80% or more,
+/// join one class, and overlapping members are merged. Classes come largest
+/// first
+#[napi]
+pub fn detect_clone_classes(
+    files: Vec<FileInput>,
+    options: Option<CrossFileDuplicateOptions>,
+) -> Result<Vec<CloneClass>> {
+    clone_classes(&files, options)
+}
+
+/// Duplication in one file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDuplication {
+    pub path: String,
+    /// Code lines (not blank or comment-only) inside some clone
+    #[napi(js_name = "duplicatedLines")]
+    pub duplicated_lines: u32,
+    /// Code lines in the file
+    #[napi(js_name = "totalLines")]
+    pub total_lines: u32,
+    /// `duplicatedLines` as a percentage of `totalLines`
+    #[napi(js_name = "duplicatedPercentage")]
+    pub duplicated_percentage: f64,
+}
+
+/// Duplication summary for a set of files
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicationReport {
+    #[napi(js_name = "duplicatedLines")]
+    pub duplicated_lines: u32,
+    #[napi(js_name = "totalLines")]
+    pub total_lines: u32,
+    /// The number to track: duplicated code lines as a percentage of all
+    #[napi(js_name = "duplicatedPercentage")]
+    pub duplicated_percentage: f64,
+    /// Every file, in input order
+    pub files: Vec<FileDuplication>,
+    /// Files with the most duplicated lines, at most 10
+    #[napi(js_name = "topOffenders")]
+    pub top_offenders: Vec<FileDuplication>,
+    /// Clone classes with the most members, at most 10
+    #[napi(js_name = "largestClasses")]
+    pub largest_classes: Vec<CloneClass>,
+}
+
+/// Entries in the top lists of `duplication_report`
+const REPORT_TOP: usize = 10;
+
+/// Summarize duplication across files: duplicated code lines per file and
+/// overall, the worst files and the most repeated code
+///
+/// Clones are found as by `detect_clone_classes` with the same options. Only
+/// code lines count, so blank lines and comments don't dilute the numbers
+#[napi]
+pub fn duplication_report(
+    files: Vec<FileInput>,
+    options: Option<CrossFileDuplicateOptions>,
+) -> Result<DuplicationReport> {
+    let classes = clone_classes(&files, options)?;
+
+    let mut covered: HashMap<&str, Vec<(u32, u32)>> = HashMap::new();
+    for member in classes.iter().flat_map(|class| &class.members) {
+        covered.entry(&member.path).or_default().push((member.start_line, member.end_line));
+    }
+    let percentage = |part: u32, total: u32| if total == 0 { 0.0 } else { part as f64 * 100.0 / total as f64 };
+
+    let stats: Vec<FileDuplication> = files
+        .par_iter()
+        .map(|file| {
+            let lines = normalized_lines(&file.code, &file.language_id, false);
+            let ranges = covered.get(file.path.as_str()).map_or(&[][..], |ranges| ranges);
+            let duplicated = lines
+                .iter()
+                .filter(|line| ranges.iter().any(|&(start, end)| (start..end).contains(&(line.line as u32))))
+                .count() as u32;
+            FileDuplication {
+                path: file.path.clone(),
+                duplicated_lines: duplicated,
+                total_lines: lines.len() as u32,
+                duplicated_percentage: percentage(duplicated, lines.len() as u32),
+            }
+        })
+        .collect();
+
+    let duplicated_lines = stats.iter().map(|file| file.duplicated_lines).sum();
+    let total_lines = stats.iter().map(|file| file.total_lines).sum();
+    let mut top_offenders: Vec<FileDuplication> =
+        stats.iter().filter(|file| file.duplicated_lines > 0).cloned().collect();
+    top_offenders.sort_by(|a, b| {
+        b.duplicated_lines.cmp(&a.duplicated_lines).then(b.duplicated_percentage.total_cmp(&a.duplicated_percentage))
+    });
+    top_offenders.truncate(REPORT_TOP);
+
+    Ok(DuplicationReport {
+        duplicated_lines,
+        total_lines,
+        duplicated_percentage: percentage(duplicated_lines, total_lines),
+        files: stats,
+        top_offenders,
+        largest_classes: classes.into_iter().take(REPORT_TOP).collect(),
+    })
+}
+
+fn clone_classes(files: &[FileInput], options: Option<CrossFileDuplicateOptions>) -> Result<Vec<CloneClass>> {
+    let pairs = clone_pairs(files, options)?;
+
+    let mut ranges: Vec<&CloneRange> = pairs.iter().flat_map(|pair| [&pair.a, &pair.b]).collect();
+    ranges.sort_by(|x, y| (&x.path, x.start_line, x.end_line).cmp(&(&y.path, y.start_line, y.end_line)));
+    ranges.dedup_by(|x, y| (&x.path, x.start_line, x.end_line) == (&y.path, y.start_line, y.end_line));
+    let node = |range: &CloneRange| {
+        ranges
+            .binary_search_by(|probe| {
+                (&probe.path, probe.start_line, probe.end_line).cmp(&(&range.path, range.start_line, range.end_line))
+            })
+            .unwrap()
+    };
+
+    let mut parents: Vec<usize> = (0..ranges.len()).collect();
+    for pair in &pairs {
+        union(&mut parents, node(&pair.a), node(&pair.b));
+    }
+    // Ranges of a file that mostly coincide are one region whose boundaries
+    // came out differently per pair; sorted by path, so each file is a run
+    for i in 0..ranges.len() {
+        for j in (0..i).rev().take_while(|j| ranges[*j].path == ranges[i].path) {
+            let overlap = ranges[i].end_line.min(ranges[j].end_line).saturating_sub(ranges[i].start_line);
+            let longer = (ranges[i].end_line - ranges[i].start_line).max(ranges[j].end_line - ranges[j].start_line);
+            if overlap as f64 >= MIN_REGION_OVERLAP * longer as f64 {
+                union(&mut parents, i, j);
+            }
+        }
+    }
+
+    let mut pair_counts = vec![0u32; ranges.len()];
+    let mut similarities: HashMap<usize, f64> = HashMap::new();
+    for pair in &pairs {
+        let (a, b) = (node(&pair.a), node(&pair.b));
+        pair_counts[a] += 1;
+        pair_counts[b] += 1;
+        let similarity = similarities.entry(find(&mut parents, a)).or_insert(1.0);
+        *similarity = similarity.min(pair.similarity);
+    }
+
+    let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..ranges.len() {
+        components.entry(find(&mut parents, i)).or_default().push(i);
+    }
+
+    let mut classes: Vec<CloneClass> = components
+        .into_iter()
+        .map(|(root, nodes)| {
+            // `nodes` is in range order; the earliest most-paired one represents
+            let representative = nodes.iter().copied().rev().max_by_key(|i| pair_counts[*i]).unwrap();
+            let mut members: Vec<CloneRange> = Vec::new();
+            for i in nodes {
+                match members.last_mut() {
+                    Some(last) if last.path == ranges[i].path && ranges[i].start_line < last.end_line => {
+                        last.end_line = last.end_line.max(ranges[i].end_line);
+                    }
+                    _ => members.push(ranges[i].clone()),
+                }
+            }
+            CloneClass {
+                representative: ranges[representative].clone(),
+                members,
+                similarity: similarities[&root],
+            }
+        })
+        .collect();
+    classes.sort_by_cached_key(|class| {
+        let first = &class.members[0];
+        (std::cmp::Reverse(class.members.len()), first.path.clone(), first.start_line)
+    });
+    Ok(classes)
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[a.max(b)] = a.min(b);
+}
+
+fn clone_pairs(files: &[FileInput], options: Option<CrossFileDuplicateOptions>) -> Result<Vec<ClonePair>> {
+    let options = options.unwrap_or_default();
+    let min_lines = options.min_lines.unwrap_or(6).max(1) as usize;
+    let (normalize, near) = match options.mode.as_deref().unwrap_or("normalized") {
+        "exact" => (false, false),
+        "normalized" => (true, false),
+        "near" => (true, true),
+        other => return Err(Error::from_reason(format!("Unknown duplicate mode: {}", other))),
+    };
+
+    let lines: Vec<Vec<NormalizedLine>> = files
+        .par_iter()
+        .map(|file| normalized_lines(&file.code, &file.language_id, normalize))
+        .collect();
+    let window_texts: Vec<Vec<String>> = lines
+        .par_iter()
+        .map(|lines| {
+            lines
+                .windows(min_lines)
+                .map(|window| window.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"))
+                .collect()
+        })
+        .collect();
+
+    // Matching windows as (file a, file b, window in a, window in b), a first,
+    // with their similarity when it isn't found from the merged clone
+    let (mut seeds, scores) = if near {
+        let min_similarity = options.min_similarity.unwrap_or(0.8);
+        near_seeds(&window_texts, min_lines, min_similarity)
+    } else {
+        (exact_seeds(&window_texts, min_lines), HashMap::new())
+    };
+
+    // Seeds on the same diagonal with consecutive windows form one clone
+    seeds.sort_unstable_by_key(|&(file_a, file_b, window_a, window_b)| {
+        (file_a, file_b, window_a as isize - window_b as isize, window_a)
+    });
+    let mut pairs = Vec::new();
+    let mut i = 0;
+    while i < seeds.len() {
+        let (file_a, file_b, start_a, start_b) = seeds[i];
+        let mut run = 1;
+        while seeds.get(i + run) == Some(&(file_a, file_b, start_a + run, start_b + run)) {
+            run += 1;
+        }
+        i += run;
+
+        let length = run + min_lines - 1;
+        let window_a = &lines[file_a][start_a..start_a + length];
+        let window_b = &lines[file_b][start_b..start_b + length];
+        let similarity = if near {
+            let run_scores = (0..run).map(|k| scores[&(file_a, file_b, start_a + k, start_b + k)]);
+            run_scores.sum::<f64>() / run as f64
+        } else {
+            let total = window_a.iter().map(|line| line.tokens.len()).sum::<usize>();
+            let equal = window_a
+                .iter()
+                .flat_map(|line| &line.tokens)
+                .zip(window_b.iter().flat_map(|line| &line.tokens))
+                .filter(|(a, b)| a == b)
+                .count();
+            equal as f64 / total.max(1) as f64
+        };
+        let range = |file: usize, window: &[NormalizedLine]| CloneRange {
+            path: files[file].path.clone(),
+            start_line: window[0].line as u32,
+            end_line: (window[window.len() - 1].line + 1) as u32,
+        };
+        pairs.push(ClonePair {
+            a: range(file_a, window_a),
+            b: range(file_b, window_b),
+            similarity,
+        });
+    }
+
+    if near {
+        pairs = without_overlaps(pairs);
+    }
+    pairs.sort_by_cached_key(|pair| (pair.a.path.clone(), pair.a.start_line, pair.b.path.clone(), pair.b.start_line));
+    Ok(pairs)
+}
+
+/// Near matches of one region come out on several diagonals; keep the
+/// longest, then most similar, of pairs that overlap on both sides
+fn without_overlaps(mut pairs: Vec<ClonePair>) -> Vec<ClonePair> {
+    let lines = |pair: &ClonePair| pair.a.end_line - pair.a.start_line + pair.b.end_line - pair.b.start_line;
+    pairs.sort_by(|x, y| lines(y).cmp(&lines(x)).then(y.similarity.total_cmp(&x.similarity)));
+    let overlaps = |x: &CloneRange, y: &CloneRange| {
+        x.path == y.path && x.start_line < y.end_line && y.start_line < x.end_line
+    };
+    let mut kept: Vec<ClonePair> = Vec::new();
+    for pair in pairs {
+        if !kept.iter().any(|k| overlaps(&k.a, &pair.a) && overlaps(&k.b, &pair.b)) {
+            kept.push(pair);
+        }
+    }
+    kept
+}
+
+type Seed = (usize, usize, usize, usize);
+
+/// Pairs of windows with identical text
+fn exact_seeds(window_texts: &[Vec<String>], min_lines: usize) -> Vec<Seed> {
+    let mut index: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for (file, texts) in window_texts.iter().enumerate() {
+        for (window, text) in texts.iter().enumerate() {
+            index.entry(text).or_default().push((file, window));
+        }
+    }
+
+    let mut seeds = Vec::new();
+    for occurrences in index.values() {
+        if occurrences.len() > MAX_WINDOW_OCCURRENCES {
+            continue;
+        }
+        for (i, &(file_a, window_a)) in occurrences.iter().enumerate() {
+            for &(file_b, window_b) in &occurrences[i + 1..] {
+                // Overlapping windows of one file aren't clones of each other
+                if file_a == file_b && window_b < window_a + min_lines {
+                    continue;
+                }
+                seeds.push((file_a, file_b, window_a, window_b));
+            }
+        }
+    }
+    seeds
+}
+
+/// Bits per SimHash band; windows sharing a band are compared
+const SIMHASH_BAND_BITS: u32 = 16;
+
+/// Pairs of windows with similar text and their similarity
+///
+/// Comparing every pair of windows is quadratic, so windows are bucketed by
+/// each 16-bit band of their SimHash and only windows sharing a bucket are
+/// compared, by cosine similarity of token trigrams. Windows whose SimHashes
+/// differ in 3 bits or fewer always share a band
+fn near_seeds(
+    window_texts: &[Vec<String>],
+    min_lines: usize,
+    min_similarity: f64,
+) -> (Vec<Seed>, HashMap<Seed, f64>) {
+    let simhashes: Vec<Vec<u64>> = window_texts
+        .par_iter()
+        .map(|texts| texts.iter().map(|text| simhash_of(text)).collect())
+        .collect();
+
+    let mut buckets: HashMap<(u32, u64), Vec<(usize, usize)>> = HashMap::new();
+    for (file, hashes) in simhashes.iter().enumerate() {
+        for (window, hash) in hashes.iter().enumerate() {
+            for band in 0..64 / SIMHASH_BAND_BITS {
+                let key = hash >> (band * SIMHASH_BAND_BITS) & ((1 << SIMHASH_BAND_BITS) - 1);
+                buckets.entry((band, key)).or_default().push((file, window));
+            }
+        }
+    }
+
+    let mut candidates = HashSet::new();
+    for occurrences in buckets.values() {
+        if occurrences.len() > MAX_WINDOW_OCCURRENCES {
+            continue;
+        }
+        for (i, &(file_a, window_a)) in occurrences.iter().enumerate() {
+            for &(file_b, window_b) in &occurrences[i + 1..] {
+                if file_a == file_b && window_b < window_a + min_lines {
+                    continue;
+                }
+                candidates.insert((file_a, file_b, window_a, window_b));
+            }
+        }
+    }
+
+    let scores: HashMap<Seed, f64> = candidates
+        .into_par_iter()
+        .filter_map(|seed @ (file_a, file_b, window_a, window_b)| {
+            let a = &window_texts[file_a][window_a];
+            let b = &window_texts[file_b][window_b];
+            let similarity = Metric::Cosine.score(a, b, "");
+            (similarity >= min_similarity).then_some((seed, similarity))
+        })
+        .collect();
+    (scores.keys().copied().collect(), scores)
+}
+
+/// A non-blank line after normalizing: its line number, its tokens with
+/// identifiers and literals replaced, and the original tokens
+struct NormalizedLine<'a> {
+    line: usize,
+    text: String,
+    tokens: Vec<&'a str>,
+}
+
+/// Windows of `code` lines whose normalized tokens appear in `context`
+///
+/// Similarity is the share of original tokens that match exactly, so a
+/// verbatim copy scores 1 and a renamed one less
+fn normalized_duplicates(
+    code: &str,
+    context: &str,
+    min_len: usize,
+    language_id: &str,
+    metric: Option<Metric>,
+    (code_excluded, context_excluded): (&HashSet<usize>, &HashSet<usize>),
+    control: &DetectionControl,
+) -> Result<Vec<DuplicateInfo>> {
+    let code_lines: Vec<&str> = code.lines().collect();
+    let context_lines: Vec<&str> = context.lines().collect();
+    let mut code_normalized = normalized_lines(code, language_id, true);
+    let mut context_normalized = normalized_lines(context, language_id, true);
+    code_normalized.retain(|line| !code_excluded.contains(&line.line));
+    context_normalized.retain(|line| !context_excluded.contains(&line.line));
+
+    // Whole lines only: every line is wrapped in newlines
+    let mut haystack = String::from("\n");
+    let mut line_starts = Vec::with_capacity(context_normalized.len());
+    for line in &context_normalized {
+        line_starts.push(haystack.len());
+        haystack.push_str(&line.text);
+        haystack.push('\n');
+    }
+
+    let mut duplicates = Vec::new();
+    // Windows already inside a larger match, which would only fragment it
+    let mut covered: Vec<(usize, usize)> = Vec::new();
+    let window_sizes = (min_len..=code_normalized.len().min(50)).rev();
+    let total = window_sizes.clone().count();
+    for (completed, window_size) in window_sizes.enumerate() {
+        control.report(completed, total);
+        for (i, window) in code_normalized.windows(window_size).enumerate() {
+            control.check()?;
+            if covered.iter().any(|&(start, end)| start <= i && i + window_size <= end) {
+                continue;
+            }
+            let mut needle = String::from("\n");
+            for line in window {
+                needle.push_str(&line.text);
+                needle.push('\n');
+            }
+            let Some(pos) = memmem::find(haystack.as_bytes(), needle.as_bytes()) else {
+                continue;
+            };
+            let Ok(context_start) = line_starts.binary_search(&(pos + 1)) else {
+                continue;
+            };
+
+            let matched = &context_normalized[context_start..context_start + window_size];
+            covered.push((i, i + window_size));
+            let first = window[0].line;
+            let last = window[window_size - 1].line;
+            let text = code_lines[first..=last].join("\n");
+
+            let similarity = match metric {
+                Some(metric) => {
+                    let matched_text = context_lines[matched[0].line..=matched[window_size - 1].line].join("\n");
+                    metric.score(&text, &matched_text, language_id)
+                }
+                None => {
+                    let code_tokens = window.iter().flat_map(|line| &line.tokens);
+                    let context_tokens = matched.iter().flat_map(|line| &line.tokens);
+                    let total = window.iter().map(|line| line.tokens.len()).sum::<usize>();
+                    let equal = code_tokens.zip(context_tokens).filter(|(a, b)| a == b).count();
+                    equal as f64 / total.max(1) as f64
+                }
+            };
+            duplicates.push(DuplicateInfo {
+                text,
+                start_line: first as u32,
+                end_line: (last + 1) as u32,
+                context_start_line: matched[0].line as u32,
+                context_end_line: (matched[window_size - 1].line + 1) as u32,
+                similarity,
+            });
+        }
+    }
+    control.report(total, total);
+    Ok(duplicates)
+}
+
+/// Tokens of each non-blank line, without comments; identifiers and literals
+/// become placeholders when `normalize` is set
+fn normalized_lines<'a>(text: &'a str, language_id: &str, normalize: bool) -> Vec<NormalizedLine<'a>> {
+    let mut lines: Vec<NormalizedLine> = Vec::new();
+    let mut line = 0;
+    let mut counted = 0;
+    for (start, end, token_type) in lexical_tokens(text, language_id) {
+        let placeholder = match token_type {
+            "comment" => continue,
+            "identifier" if normalize => "$id",
+            "string" | "number" if normalize => "$lit",
+            _ => &text[start..end],
+        };
+        line += memchr::memchr_iter(b'\n', &text.as_bytes()[counted..start]).count();
+        counted = start;
+
+        match lines.last_mut() {
+            Some(last) if last.line == line => {
+                last.text.push(' ');
+                last.text.push_str(placeholder);
+                last.tokens.push(&text[start..end]);
+            }
+            _ => lines.push(NormalizedLine {
+                line,
+                text: placeholder.to_string(),
+                tokens: vec![&text[start..end]],
+            }),
+        }
+    }
+    lines
+}
+
+/// The longest run of lines shared by two texts
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommonBlock {
+    #[napi(js_name = "aStartLine")]
+    pub a_start_line: u32,
+    /// Exclusive
+    #[napi(js_name = "aEndLine")]
+    pub a_end_line: u32,
+    #[napi(js_name = "bStartLine")]
+    pub b_start_line: u32,
+    #[napi(js_name = "bEndLine")]
+    pub b_end_line: u32,
+    /// UTF-8 byte range of the lines in `a`, without the final line break
+    #[napi(js_name = "aStartByte")]
+    pub a_start_byte: u32,
+    #[napi(js_name = "aEndByte")]
+    pub a_end_byte: u32,
+    #[napi(js_name = "bStartByte")]
+    pub b_start_byte: u32,
+    #[napi(js_name = "bEndByte")]
+    pub b_end_byte: u32,
+}
+
+/// Find the longest block of whole lines shared by two texts
+///
+/// Lines compare with leading and trailing whitespace ignored, so a block
+/// re-indented in a completion still matches the original. Runs in linear
+/// time with a suffix automaton over line ids, which keeps megabyte inputs
+/// fast. Blank lines never match, so blocks do not span them. Returns
+/// `null` when no non-blank line is shared
+#[napi]
+pub fn longest_common_block(a: String, b: String) -> Option<CommonBlock> {
+    let a_lines = line_spans(&a);
+    let b_lines = line_spans(&b);
+    let mut ids = HashMap::new();
+    let mut next_id = 0;
+    let a_ids = trimmed_line_ids(&a, &a_lines, &mut ids, &mut next_id);
+    let b_ids = trimmed_line_ids(&b, &b_lines, &mut ids, &mut next_id);
+
+    let (a_start, b_start, length) = longest_common_run(&a_ids, &b_ids)?;
+
+    Some(CommonBlock {
+        a_start_line: a_start as u32,
+        a_end_line: (a_start + length) as u32,
+        b_start_line: b_start as u32,
+        b_end_line: (b_start + length) as u32,
+        a_start_byte: a_lines[a_start].0 as u32,
+        a_end_byte: a_lines[a_start + length - 1].1 as u32,
+        b_start_byte: b_lines[b_start].0 as u32,
+        b_end_byte: b_lines[b_start + length - 1].1 as u32,
+    })
+}
+
+/// Ids for lines, equal for lines that are equal once trimmed; every blank
+/// line gets an id of its own
+fn trimmed_line_ids<'t>(
+    text: &'t str,
+    spans: &[(usize, usize)],
+    ids: &mut HashMap<&'t str, u32>,
+    next_id: &mut u32,
+) -> Vec<u32> {
+    let mut take_id = || {
+        *next_id += 1;
+        *next_id - 1
+    };
+    spans
+        .iter()
+        .map(|&(start, end)| match text[start..end].trim() {
+            "" => take_id(),
+            line => *ids.entry(line).or_insert_with(&mut take_id),
+        })
+        .collect()
+}
+
+/// Byte range of each line, without its line break
+fn line_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        spans.push((start, start + line.trim_end_matches(['\n', '\r']).len()));
+        start += line.len();
+    }
+    spans
+}
+
+/// Options for `winnow_fingerprints`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WinnowOptions {
+    /// Tokens per k-gram; shorter matches are never found (default: 5)
+    pub k: Option<u32>,
+    /// k-grams per window; matches of at least `windowSize + k - 1` tokens
+    /// are always found (default: 4)
+    #[napi(js_name = "windowSize")]
+    pub window_size: Option<u32>,
+    /// Language for tokenizing
+    #[napi(js_name = "languageId")]
+    pub language_id: Option<String>,
+}
+
+/// A selected k-gram hash and where its tokens are
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WinnowFingerprint {
+    pub hash: String,
+    #[napi(js_name = "startByte")]
+    pub start_byte: u32,
+    #[napi(js_name = "endByte")]
+    pub end_byte: u32,
+}
+
+/// How much two fingerprint sets have in common
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintOverlap {
+    /// Jaccard similarity of the distinct hashes
+    pub similarity: f64,
+    /// Share of the first set's hashes found in the second
+    #[napi(js_name = "containmentA")]
+    pub containment_a: f64,
+    /// Share of the second set's hashes found in the first
+    #[napi(js_name = "containmentB")]
+    pub containment_b: f64,
+    /// Number of distinct shared hashes
+    pub shared: u32,
+}
+
+/// Select fingerprints of code by winnowing, as MOSS does
+///
+/// Tokens are normalized (identifiers and literals become placeholders,
+/// comments are dropped) and hashed in overlapping k-grams; each window
+/// keeps its smallest hash. Copies survive renaming, reformatting and
+/// reordering of larger blocks, and the fingerprint count stays around
+/// `2 / (windowSize + 1)` of the k-grams
+#[napi]
+pub fn winnow_fingerprints(code: String, options: Option<WinnowOptions>) -> Result<Vec<WinnowFingerprint>> {
+    let options = options.unwrap_or_default();
+    let k = options.k.unwrap_or(5) as usize;
+    let window_size = options.window_size.unwrap_or(4) as usize;
+    if k == 0 || window_size == 0 {
+        return Err(Error::from_reason("k and windowSize must be at least 1"));
+    }
+
+    Ok(winnow(&code, options.language_id.as_deref().unwrap_or(""), k, window_size)
+        .into_iter()
+        .map(|(hash, start_byte, end_byte)| WinnowFingerprint {
+            hash: format!("{:x}", hash),
+            start_byte: start_byte as u32,
+            end_byte: end_byte as u32,
+        })
+        .collect())
+}
+
+/// Winnowed fingerprints as (hash, start byte, end byte); `k` and
+/// `window_size` must be at least 1
+pub(crate) fn winnow(code: &str, language_id: &str, k: usize, window_size: usize) -> Vec<(u64, usize, usize)> {
+    let tokens: Vec<(usize, usize, &str)> = lexical_tokens(code, language_id)
+        .into_iter()
+        .filter_map(|(start, end, token_type)| match token_type {
+            "comment" => None,
+            "identifier" => Some((start, end, "$id")),
+            "string" | "number" => Some((start, end, "$lit")),
+            _ => Some((start, end, &code[start..end])),
+        })
+        .collect();
+    let hashes: Vec<u64> = tokens
+        .windows(k)
+        .map(|gram| {
+            let mut hasher = Xxh3::new();
+            for (_, _, text) in gram {
+                hasher.update(text.as_bytes());
+                hasher.update(b"\0");
+            }
+            hasher.digest()
+        })
+        .collect();
+
+    // Rightmost minimum of each window, recorded once while it stays selected
+    let mut fingerprints = Vec::new();
+    let mut selected: Option<usize> = None;
+    // Fewer k-grams than a window still make one (short) window
+    let windows = if hashes.len() < window_size { hashes.len().min(1) } else { hashes.len() - window_size + 1 };
+    for start in 0..windows {
+        let window = &hashes[start..(start + window_size).min(hashes.len())];
+        let (offset, _) = window
+            .iter()
+            .enumerate()
+            .rev()
+            .min_by_key(|(_, hash)| **hash)
+            .unwrap();
+        let position = start + offset;
+        if selected != Some(position) {
+            selected = Some(position);
+            fingerprints.push((hashes[position], tokens[position].0, tokens[position + k - 1].1));
+        }
+    }
+    fingerprints
+}
+
+/// Compare two sets of fingerprints from `winnow_fingerprints`
+#[napi]
+pub fn compare_fingerprints(a: Vec<WinnowFingerprint>, b: Vec<WinnowFingerprint>) -> FingerprintOverlap {
+    let a: HashSet<&str> = a.iter().map(|fingerprint| fingerprint.hash.as_str()).collect();
+    let b: HashSet<&str> = b.iter().map(|fingerprint| fingerprint.hash.as_str()).collect();
+    let shared = a.intersection(&b).count();
+    let ratio = |count: usize, total: usize| if total == 0 { 0.0 } else { count as f64 / total as f64 };
+    FingerprintOverlap {
+        similarity: ratio(shared, a.len() + b.len() - shared),
+        containment_a: ratio(shared, a.len()),
+        containment_b: ratio(shared, b.len()),
+        shared: shared as u32,
+    }
+}
+
+/// Similarity of two texts from 0 to 1
+///
+/// Metrics are 'words' | 'jaccard' | 'levenshtein' | 'cosine':
+/// - 'words': share of words that also appear in the other text; ignores
+///   order, so reordered code scores as identical
+/// - 'jaccard': Jaccard similarity of the sets of tokens
+/// - 'levenshtein': one minus the character edit distance over the longer
+///   length
+/// - 'cosine': cosine similarity of token trigram counts, which keeps some
+///   of the order
+///
+/// Comments and whitespace don't count towards the token-based metrics
+#[napi]
+pub fn similarity(a: String, b: String, metric: String, language_id: Option<String>) -> Result<f64> {
+    Ok(Metric::parse(&metric)?.score(&a, &b, language_id.as_deref().unwrap_or("")))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Metric {
+    Words,
+    Jaccard,
+    Levenshtein,
+    Cosine,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "words" => Ok(Self::Words),
+            "jaccard" => Ok(Self::Jaccard),
+            "levenshtein" => Ok(Self::Levenshtein),
+            "cosine" => Ok(Self::Cosine),
+            other => Err(Error::from_reason(format!("Unknown similarity metric: {}", other))),
+        }
+    }
+
+    fn score(self, a: &str, b: &str, language_id: &str) -> f64 {
+        match self {
+            Self::Words => calculate_similarity(a, b),
+            Self::Jaccard => {
+                let a: HashSet<&str> = code_tokens(a, language_id).into_iter().collect();
+                let b: HashSet<&str> = code_tokens(b, language_id).into_iter().collect();
+                let union = a.union(&b).count();
+                if union == 0 {
+                    return 1.0;
+                }
+                a.intersection(&b).count() as f64 / union as f64
+            }
+            Self::Levenshtein => {
+                let a: Vec<char> = a.chars().collect();
+                let b: Vec<char> = b.chars().collect();
+                let longer = a.len().max(b.len());
+                if longer == 0 {
+                    return 1.0;
+                }
+                let distance = levenshtein(&a, &b, None).unwrap_or(longer);
+                1.0 - distance as f64 / longer as f64
+            }
+            Self::Cosine => {
+                let (a, b) = (code_tokens(a, language_id), code_tokens(b, language_id));
+                let (a, b) = (trigram_counts(&a), trigram_counts(&b));
+                if a.is_empty() && b.is_empty() {
+                    return 1.0;
+                }
+                let dot: f64 = a.iter().map(|(gram, count)| count * b.get(gram).copied().unwrap_or(0.0)).sum();
+                let norm = |counts: &HashMap<&[&str], f64>| counts.values().map(|c| c * c).sum::<f64>().sqrt();
+                let norms = norm(&a) * norm(&b);
+                if norms == 0.0 { 0.0 } else { dot / norms }
+            }
+        }
+    }
+}
+
+/// Token texts without comments
+fn code_tokens<'a>(text: &'a str, language_id: &str) -> Vec<&'a str> {
+    lexical_tokens(text, language_id)
+        .into_iter()
+        .filter(|(_, _, token_type)| *token_type != "comment")
+        .map(|(start, end, _)| &text[start..end])
+        .collect()
+}
+
+/// Counts of token trigrams, or of the whole sequence when it is shorter
+fn trigram_counts<'t, 'a>(tokens: &'t [&'a str]) -> HashMap<&'t [&'a str], f64> {
+    let mut counts = HashMap::new();
+    if tokens.is_empty() {
+        return counts;
+    }
+    for gram in tokens.windows(3.min(tokens.len())) {
+        *counts.entry(gram).or_insert(0.0) += 1.0;
+    }
+    counts
+}
+
 /// Calculate similarity between two strings using Levenshtein-like metric
 fn calculate_similarity(s1: &str, s2: &str) -> f64 {
     let s1_words: Vec<&str> = s1.split_whitespace().collect();
@@ -102,17 +1419,294 @@ fn ranges_overlap(start1: u32, end1: u32, start2: u32, end2: u32) -> bool {
 }
 
 /// Fast substring search using SIMD
+///
+/// Either side may be a Buffer or Uint8Array, which need not be UTF-8
 #[napi]
-pub fn find_substring(haystack: String, needle: String) -> Option<u32> {
-    memmem::find(haystack.as_bytes(), needle.as_bytes())
+pub fn find_substring(haystack: Either<String, Buffer>, needle: Either<String, Buffer>) -> Option<u32> {
+    memmem::find(either_bytes(&haystack), either_bytes(&needle))
         .map(|pos| pos as u32)
 }
 
+/// Options for `find_all_occurrences`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OccurrenceOptions {
+    /// Ignore case, Unicode-aware (default: false)
+    #[napi(js_name = "caseInsensitive")]
+    pub case_insensitive: Option<bool>,
+    /// Only matches not preceded or followed by a word character
+    /// (default: false)
+    #[napi(js_name = "wholeWord")]
+    pub whole_word: Option<bool>,
+    /// Treat the needle as an RE2-style regex (default: false)
+    pub regex: Option<bool>,
+}
+
 /// Find all occurrences of a pattern
+///
+/// Returns the byte offset of each match; regex matches never overlap.
+/// Either side may be a Buffer or Uint8Array, which need not be UTF-8
 #[napi]
-pub fn find_all_occurrences(haystack: String, needle: String) -> Vec<u32> {
-    let finder = memmem::Finder::new(&needle);
-    finder.find_iter(haystack.as_bytes())
-        .map(|pos| pos as u32)
+pub fn find_all_occurrences(
+    haystack: Either<String, Buffer>,
+    needle: Either<String, Buffer>,
+    options: Option<OccurrenceOptions>,
+) -> Result<Vec<u32>> {
+    occurrences(either_bytes(&haystack), either_bytes(&needle), options.unwrap_or_default())
+}
+
+fn occurrences(haystack: &[u8], needle: &[u8], options: OccurrenceOptions) -> Result<Vec<u32>> {
+    let case_insensitive = options.case_insensitive.unwrap_or(false);
+    let whole_word = options.whole_word.unwrap_or(false);
+    let is_regex = options.regex.unwrap_or(false);
+    if !case_insensitive && !whole_word && !is_regex {
+        let finder = memmem::Finder::new(needle);
+        return Ok(finder.find_iter(haystack)
+            .map(|pos| pos as u32)
+            .collect());
+    }
+
+    let pattern = if is_regex {
+        std::str::from_utf8(needle)
+            .map_err(|_| Error::from_reason("Regex pattern must be valid UTF-8"))?
+            .to_string()
+    } else {
+        literal_pattern(needle)
+    };
+    let regex = BytesRegexBuilder::new(&pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| Error::from_reason(format!("Invalid pattern: {}", e)))?;
+    if !whole_word {
+        return Ok(regex.find_iter(haystack).map(|m| m.start() as u32).collect());
+    }
+
+    // Word boundaries are checked as each match is found, since the regex
+    // crate has no lookaround and `\b` needs a word character inside the match
+    let mut matches = Vec::new();
+    let mut at = 0;
+    while let Some(found) = regex.find_at(haystack, at) {
+        if is_whole_word(haystack, found.start(), found.end()) {
+            matches.push(found.start() as u32);
+            at = found.end() + usize::from(found.is_empty());
+        } else {
+            // A match starting inside this one can still be a whole word
+            at = found.start() + 1;
+        }
+        if at > haystack.len() {
+            break;
+        }
+    }
+    Ok(matches)
+}
+
+/// Options for `search_file`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileSearchOptions {
+    /// Bytes read at a time (default: 1 MiB)
+    #[napi(js_name = "chunkSize")]
+    pub chunk_size: Option<u32>,
+    /// Stop after this many matches
+    #[napi(js_name = "maxMatches")]
+    pub max_matches: Option<u32>,
+}
+
+/// An occurrence found by `search_file`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMatch {
+    /// Byte offset in the file
+    pub offset: i64,
+    /// Zero-based line of the match
+    pub line: u32,
+}
+
+/// Find all occurrences of a pattern in a file without loading it whole
+///
+/// The file is read in chunks, keeping enough of each chunk to find matches
+/// that straddle the next one, so memory stays at about one chunk however
+/// large the file. Matches never overlap, as in `find_all_occurrences`
+#[napi]
+pub fn search_file(path: String, needle: Either<String, Buffer>, options: Option<FileSearchOptions>) -> Result<Vec<FileMatch>> {
+    let options = options.unwrap_or_default();
+    let needle = either_bytes(&needle);
+    if needle.is_empty() {
+        return Err(Error::from_reason("Needle must not be empty"));
+    }
+    let chunk_size = (options.chunk_size.unwrap_or(1 << 20) as usize).max(needle.len());
+    let max_matches = options.max_matches.map_or(usize::MAX, |max| max as usize);
+    let mut file = fs::File::open(&path).map_err(|e| Error::from_reason(format!("Failed to open {}: {}", path, e)))?;
+
+    let finder = memmem::Finder::new(needle);
+    let mut matches = Vec::new();
+    let mut buffer: Vec<u8> = Vec::with_capacity(chunk_size + needle.len());
+    // File offset and line of buffer[0]
+    let mut base = 0usize;
+    let mut base_line = 0usize;
+    // End of the last match, so a match found again across a chunk boundary
+    // is not counted twice
+    let mut match_end = 0usize;
+    loop {
+        let carried = buffer.len();
+        buffer.resize(carried + chunk_size, 0);
+        let read = read_full(&mut file, &mut buffer[carried..])
+            .map_err(|e| Error::from_reason(format!("Failed to read {}: {}", path, e)))?;
+        buffer.truncate(carried + read);
+
+        let mut line = base_line;
+        let mut counted = 0;
+        for pos in finder.find_iter(&buffer) {
+            if base + pos < match_end {
+                continue;
+            }
+            line += memchr::memchr_iter(b'\n', &buffer[counted..pos]).count();
+            counted = pos;
+            matches.push(FileMatch { offset: (base + pos) as i64, line: line as u32 });
+            match_end = base + pos + needle.len();
+            if matches.len() >= max_matches {
+                return Ok(matches);
+            }
+        }
+        if read == 0 {
+            return Ok(matches);
+        }
+
+        // Keep the tail a match could still start in
+        let consumed = buffer.len().saturating_sub(needle.len() - 1);
+        base_line += memchr::memchr_iter(b'\n', &buffer[..consumed]).count();
+        base += consumed;
+        buffer.drain(..consumed);
+    }
+}
+
+/// Read until `buffer` is full or the file ends
+fn read_full(file: &mut fs::File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn either_bytes(value: &Either<String, Buffer>) -> &[u8] {
+    match value {
+        Either::A(text) => text.as_bytes(),
+        Either::B(buffer) => buffer,
+    }
+}
+
+/// Regex matching `bytes` literally, including invalid UTF-8
+fn literal_pattern(bytes: &[u8]) -> String {
+    bytes
+        .utf8_chunks()
+        .map(|chunk| {
+            let invalid: String = chunk.invalid().iter().map(|byte| format!("(?-u:\\x{:02x})", byte)).collect();
+            regex::escape(chunk.valid()) + &invalid
+        })
         .collect()
 }
+
+/// Whether `text[start..end]` has no word character right before or after it
+fn is_whole_word(text: &[u8], start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    // Characters are at most 4 bytes; anything that doesn't decode is no word
+    let before = (1..=start.min(4))
+        .find_map(|len| std::str::from_utf8(&text[start - len..start]).ok())
+        .and_then(|s| s.chars().next_back());
+    let after = (1..=(text.len() - end).min(4))
+        .find_map(|len| std::str::from_utf8(&text[end..end + len]).ok())
+        .and_then(|s| s.chars().next());
+    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+}
+
+/// A needle found by `find_any`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternMatch {
+    /// Index of the needle in the list searched for
+    #[napi(js_name = "patternIndex")]
+    pub pattern_index: u32,
+    /// Byte offset of the match
+    pub offset: u32,
+}
+
+/// Find every occurrence of any of the needles in one pass (Aho-Corasick)
+///
+/// Overlapping matches are all reported, ordered by where they end
+#[napi]
+pub fn find_any(haystack: String, needles: Vec<String>) -> Result<Vec<PatternMatch>> {
+    Ok(MultiPatternSearcher::new(needles)?.find(haystack))
+}
+
+/// Needles compiled once for `find_any` searches over many texts, such as
+/// the chunks of a stream
+#[napi]
+pub struct MultiPatternSearcher {
+    automaton: AhoCorasick,
+}
+
+#[napi]
+impl MultiPatternSearcher {
+    #[napi(constructor)]
+    pub fn new(needles: Vec<String>) -> Result<Self> {
+        let automaton = AhoCorasick::new(&needles)
+            .map_err(|e| Error::from_reason(format!("Failed to build matcher: {}", e)))?;
+        Ok(Self { automaton })
+    }
+
+    /// Every occurrence of any needle, as from `find_any`
+    #[napi]
+    pub fn find(&self, haystack: String) -> Vec<PatternMatch> {
+        self.automaton
+            .find_overlapping_iter(&haystack)
+            .map(|m| PatternMatch {
+                pattern_index: m.pattern().as_u32(),
+                offset: m.start() as u32,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whole_words(haystack: &str, needle: &str) -> Vec<u32> {
+        let options = OccurrenceOptions { whole_word: Some(true), ..Default::default() };
+        occurrences(haystack.as_bytes(), needle.as_bytes(), options).unwrap()
+    }
+
+    #[test]
+    fn metrics_handle_empty_context_and_blank_windows() {
+        for metric in ["jaccard", "levenshtein", "cosine"] {
+            let options = || DuplicateOptions { metric: Some(metric.to_string()), ..Default::default() };
+            assert!(detect_duplicates("a\n\nb".to_string(), String::new(), Some(1), Some(options())).unwrap().is_empty());
+            let found = detect_duplicates("a\n\nb".to_string(), "x\n\nb\n".to_string(), Some(1), Some(options())).unwrap();
+            assert!(found.iter().all(|duplicate| !duplicate.text.is_empty()));
+        }
+    }
+
+    #[test]
+    fn whole_word_matches_are_not_hidden_by_rejected_ones() {
+        assert_eq!(whole_words("ba-a-a", "a-a"), [3]);
+        assert_eq!(whole_words("foo foobar _foo (foo)", "foo"), [0, 17]);
+        assert_eq!(whole_words("x.y .y", ".y"), [4]);
+        // Bytes that aren't UTF-8 are no word characters
+        let options = OccurrenceOptions { whole_word: Some(true), ..Default::default() };
+        assert_eq!(occurrences(b"\xfffoo\xfe xfoo", b"foo", options).unwrap(), [1]);
+    }
+
+    #[test]
+    fn blank_lines_never_form_a_common_block() {
+        let block = longest_common_block("\n\n\nfoo".to_string(), "foo\n\n\n".to_string()).unwrap();
+        assert_eq!((block.a_start_line, block.a_end_line), (3, 4));
+        assert_eq!((block.b_start_line, block.b_end_line), (0, 1));
+        assert!(longest_common_block("a\n\n".to_string(), "\n\nb".to_string()).is_none());
+    }
+}
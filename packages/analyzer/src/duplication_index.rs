@@ -0,0 +1,260 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::duplication::winnow;
+use crate::file_io::atomic_write;
+use crate::semantic_analyzer::FileInput;
+
+/// Token k-gram and window sizes, as the `winnow_fingerprints` defaults
+const K: usize = 5;
+const WINDOW_SIZE: usize = 4;
+
+/// Bumped whenever the stored layout or fingerprinting changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Options for `DuplicationIndex.queryDuplicatesOf`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexQueryOptions {
+    #[napi(js_name = "languageId")]
+    pub language_id: Option<String>,
+    /// Fewest distinct shared fingerprints for a file to match (default: 3)
+    #[napi(js_name = "minShared")]
+    pub min_shared: Option<u32>,
+    /// Skip this file, usually the one the query came from
+    #[napi(js_name = "excludePath")]
+    pub exclude_path: Option<String>,
+    /// Maximum number of files returned (default: 20)
+    pub limit: Option<u32>,
+}
+
+/// An indexed file sharing code with a query
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDuplicate {
+    pub path: String,
+    /// Distinct fingerprints found in both
+    pub shared: u32,
+    /// Share of the query's fingerprints found in the file
+    pub containment: f64,
+    /// Lines from the first to the last shared fingerprint in the file
+    /// (0-based, end exclusive)
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    /// The same span in the query
+    #[napi(js_name = "queryStartLine")]
+    pub query_start_line: u32,
+    #[napi(js_name = "queryEndLine")]
+    pub query_end_line: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    content_hash: u64,
+    /// (hash, start line, end line) of each winnowed fingerprint
+    fingerprints: Vec<(u64, u32, u32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredIndex {
+    version: u32,
+    files: HashMap<String, IndexedFile>,
+}
+
+/// Winnowing fingerprints of a workspace, kept up to date file by file
+///
+/// Files are fingerprinted once and re-fingerprinted only when their
+/// content changes; queries look fingerprints up in an inverted index
+/// instead of comparing against every file. With a storage path the
+/// fingerprints are loaded on construction and written by `save`
+#[napi]
+pub struct DuplicationIndex {
+    storage_path: Option<PathBuf>,
+    files: HashMap<Arc<str>, IndexedFile>,
+    /// Files by fingerprint hash
+    postings: HashMap<u64, HashSet<Arc<str>>>,
+}
+
+#[napi]
+impl DuplicationIndex {
+    /// Open an index, loading `storagePath` when it exists
+    #[napi(constructor)]
+    pub fn new(storage_path: Option<String>) -> Result<Self> {
+        let mut index = Self {
+            storage_path: storage_path.map(PathBuf::from),
+            files: HashMap::new(),
+            postings: HashMap::new(),
+        };
+        let Some(path) = index.storage_path.as_ref().filter(|path| path.exists()) else {
+            return Ok(index);
+        };
+        let data = fs::read(path)
+            .map_err(|e| Error::from_reason(format!("Failed to read index {}: {}", path.display(), e)))?;
+        let stored: StoredIndex = serde_json::from_slice(&data)
+            .map_err(|e| Error::from_reason(format!("Invalid index {}: {}", path.display(), e)))?;
+        // An index from another format version is rebuilt from scratch
+        if stored.version == FORMAT_VERSION {
+            for (path, file) in stored.files {
+                index.insert(Arc::from(path), file);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Index a file, returning false when its content is unchanged
+    #[napi]
+    pub fn update_file(&mut self, path: String, code: String, language_id: Option<String>) -> bool {
+        let content_hash = xxh3_64(code.as_bytes());
+        if self.files.get(path.as_str()).is_some_and(|file| file.content_hash == content_hash) {
+            return false;
+        }
+        let file = IndexedFile {
+            content_hash,
+            fingerprints: line_fingerprints(&code, language_id.as_deref().unwrap_or("")),
+        };
+        self.remove_file(path.clone());
+        self.insert(Arc::from(path), file);
+        true
+    }
+
+    /// Index many files in parallel, returning how many changed
+    #[napi]
+    pub fn update_files(&mut self, files: Vec<FileInput>) -> u32 {
+        let changed: Vec<(String, IndexedFile)> = files
+            .into_par_iter()
+            .filter_map(|input| {
+                let content_hash = xxh3_64(input.code.as_bytes());
+                if self.files.get(input.path.as_str()).is_some_and(|file| file.content_hash == content_hash) {
+                    return None;
+                }
+                let fingerprints = line_fingerprints(&input.code, &input.language_id);
+                Some((input.path, IndexedFile { content_hash, fingerprints }))
+            })
+            .collect();
+        let count = changed.len() as u32;
+        for (path, file) in changed {
+            self.remove_file(path.clone());
+            self.insert(Arc::from(path), file);
+        }
+        count
+    }
+
+    /// Drop a file, returning whether it was indexed
+    #[napi]
+    pub fn remove_file(&mut self, path: String) -> bool {
+        let Some((path, file)) = self.files.remove_entry(path.as_str()) else {
+            return false;
+        };
+        for (hash, _, _) in &file.fingerprints {
+            if let Some(paths) = self.postings.get_mut(hash) {
+                paths.remove(&path);
+                if paths.is_empty() {
+                    self.postings.remove(hash);
+                }
+            }
+        }
+        true
+    }
+
+    /// Indexed files sharing code with `code`, most shared first
+    #[napi]
+    pub fn query_duplicates_of(&self, code: String, options: Option<IndexQueryOptions>) -> Vec<IndexedDuplicate> {
+        let options = options.unwrap_or_default();
+        let min_shared = options.min_shared.unwrap_or(3).max(1) as usize;
+        let query = line_fingerprints(&code, options.language_id.as_deref().unwrap_or(""));
+        let query_hashes: HashSet<u64> = query.iter().map(|(hash, _, _)| *hash).collect();
+
+        let mut shared: HashMap<&Arc<str>, HashSet<u64>> = HashMap::new();
+        for hash in &query_hashes {
+            for path in self.postings.get(hash).into_iter().flatten() {
+                if options.exclude_path.as_deref() != Some(&**path) {
+                    shared.entry(path).or_default().insert(*hash);
+                }
+            }
+        }
+
+        let mut duplicates: Vec<IndexedDuplicate> = shared
+            .into_iter()
+            .filter(|(_, hashes)| hashes.len() >= min_shared)
+            .map(|(path, hashes)| {
+                let (start_line, end_line) = span(&self.files[path].fingerprints, &hashes);
+                let (query_start_line, query_end_line) = span(&query, &hashes);
+                IndexedDuplicate {
+                    path: path.to_string(),
+                    shared: hashes.len() as u32,
+                    containment: hashes.len() as f64 / query_hashes.len() as f64,
+                    start_line,
+                    end_line,
+                    query_start_line,
+                    query_end_line,
+                }
+            })
+            .collect();
+        duplicates.sort_by(|a, b| b.shared.cmp(&a.shared).then_with(|| a.path.cmp(&b.path)));
+        duplicates.truncate(options.limit.unwrap_or(20) as usize);
+        duplicates
+    }
+
+    /// Write the fingerprints to the storage path
+    #[napi]
+    pub fn save(&self) -> Result<()> {
+        let path = self
+            .storage_path
+            .as_ref()
+            .ok_or_else(|| Error::from_reason("Index has no storage path"))?;
+        let stored = StoredIndex {
+            version: FORMAT_VERSION,
+            files: self
+                .files
+                .iter()
+                .map(|(path, file)| (path.to_string(), file.clone()))
+                .collect(),
+        };
+        let data = serde_json::to_vec(&stored)
+            .map_err(|e| Error::from_reason(format!("Serialization error: {}", e)))?;
+        atomic_write(path, &data)
+            .map_err(|e| Error::from_reason(format!("Failed to write index {}: {}", path.display(), e)))
+    }
+
+    /// Number of indexed files
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.files.len() as u32
+    }
+
+    fn insert(&mut self, path: Arc<str>, file: IndexedFile) {
+        for (hash, _, _) in &file.fingerprints {
+            self.postings.entry(*hash).or_default().insert(path.clone());
+        }
+        self.files.insert(path, file);
+    }
+}
+
+/// Winnowed fingerprints with their byte ranges turned into lines
+fn line_fingerprints(code: &str, language_id: &str) -> Vec<(u64, u32, u32)> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(memchr::memchr_iter(b'\n', code.as_bytes()).map(|i| i + 1))
+        .collect();
+    let line_of = |offset: usize| (line_starts.partition_point(|start| *start <= offset) - 1) as u32;
+    winnow(code, language_id, K, WINDOW_SIZE)
+        .into_iter()
+        .map(|(hash, start, end)| (hash, line_of(start), line_of(end.max(start + 1) - 1) + 1))
+        .collect()
+}
+
+/// Lines covered by the fingerprints with one of `hashes`
+fn span(fingerprints: &[(u64, u32, u32)], hashes: &HashSet<u64>) -> (u32, u32) {
+    fingerprints
+        .iter()
+        .filter(|(hash, _, _)| hashes.contains(hash))
+        .fold((u32::MAX, 0), |(start, end), (_, first, last)| (start.min(*first), end.max(*last)))
+}
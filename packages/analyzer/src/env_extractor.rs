@@ -0,0 +1,179 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::semantic_analyzer::LineIndex;
+
+/// Environment variable usage
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvVarUsage {
+    pub name: String,
+    pub access: String, // 'read' | 'write'
+    /// A fallback is supplied (`os.getenv("X", "d")`, `process.env.X ?? "d"`, `.unwrap_or(..)`)
+    #[napi(js_name = "hasDefault")]
+    pub has_default: bool,
+    #[napi(js_name = "lineNumber")]
+    pub line_number: u32,
+}
+
+/// Extract environment variable reads and writes
+///
+/// Covers `process.env.X` (including destructuring), `import.meta.env.X`,
+/// `os.environ["X"]`, `os.getenv`, `env::var`, `env!`, `os.Getenv`,
+/// `System.getenv`, `Environment.GetEnvironmentVariable`, `ENV["X"]`,
+/// `getenv` and `$_ENV`.
+#[napi]
+pub fn extract_env_vars(code: String, language_id: String) -> Result<Vec<EnvVarUsage>> {
+    let mut usages: Vec<(usize, EnvVarUsage)> = Vec::new();
+    let Some(patterns) = env_patterns().get(language_group(&language_id)) else {
+        return Ok(Vec::new());
+    };
+    let line_index = LineIndex::new(&code);
+
+    for pattern in patterns {
+        for caps in pattern.regex.captures_iter(&code) {
+            let whole = caps.get(0).unwrap();
+            let Some(name) = caps.name("name") else { continue };
+            let tail = &code[whole.end()..];
+            let access = if pattern.write || is_assignment(tail) { "write" } else { "read" };
+
+            if pattern.destructuring {
+                // `const { A, B: b, C = "d" } = process.env`
+                for binding in name.as_str().split(',') {
+                    let binding = binding.trim();
+                    let var = binding.split([':', '=']).next().unwrap_or_default().trim();
+                    if var.is_empty() || var.starts_with("...") {
+                        continue;
+                    }
+                    usages.push((
+                        name.start(),
+                        EnvVarUsage {
+                            name: var.to_string(),
+                            access: access.to_string(),
+                            has_default: binding.contains('='),
+                            line_number: line_index.get_line(name.start()),
+                        },
+                    ));
+                }
+                continue;
+            }
+
+            usages.push((
+                whole.start(),
+                EnvVarUsage {
+                    name: name.as_str().to_string(),
+                    access: access.to_string(),
+                    has_default: access == "read" && ((pattern.default_argument && next_is_argument(tail)) || has_fallback(tail)),
+                    line_number: line_index.get_line(whole.start()),
+                },
+            ));
+        }
+    }
+
+    usages.sort_by_key(|(offset, _)| *offset);
+    Ok(usages.into_iter().map(|(_, usage)| usage).collect())
+}
+
+struct EnvPattern {
+    regex: Regex,
+    write: bool,
+    /// A second call argument is a default value
+    default_argument: bool,
+    /// `name` captures a destructuring list
+    destructuring: bool,
+}
+
+fn language_group(language_id: &str) -> &str {
+    match language_id {
+        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" | "vue" | "svelte" => "js",
+        "c" | "cpp" => "cpp",
+        other => other,
+    }
+}
+
+static ENV_PATTERNS: OnceLock<HashMap<&'static str, Vec<EnvPattern>>> = OnceLock::new();
+
+fn env_patterns() -> &'static HashMap<&'static str, Vec<EnvPattern>> {
+    ENV_PATTERNS.get_or_init(|| {
+        let pattern = |regex: &str, write: bool, default_argument: bool| EnvPattern {
+            regex: Regex::new(regex).unwrap(),
+            write,
+            default_argument,
+            destructuring: false,
+        };
+
+        let mut patterns = HashMap::new();
+        patterns.insert("js", vec![
+            pattern(r"\b(?:process|Bun)\.env\.(?P<name>[A-Za-z_$][\w$]*)", false, false),
+            pattern(r#"\b(?:process|Bun)\.env\[\s*['"`](?P<name>[^'"`]+)['"`]\s*\]"#, false, false),
+            pattern(r"\bimport\.meta\.env\.(?P<name>[A-Za-z_$][\w$]*)", false, false),
+            pattern(r#"\bDeno\.env\.get\(\s*['"`](?P<name>[^'"`]+)['"`]"#, false, false),
+            pattern(r#"\bDeno\.env\.set\(\s*['"`](?P<name>[^'"`]+)['"`]"#, true, false),
+            EnvPattern {
+                regex: Regex::new(r"\b(?:const|let|var)\s*\{(?P<name>[^}]*)\}\s*=\s*(?:process|Bun)\.env\b").unwrap(),
+                write: false,
+                default_argument: false,
+                destructuring: true,
+            },
+        ]);
+        patterns.insert("python", vec![
+            pattern(r#"\b(?:os\.)?environ\[\s*['"](?P<name>[^'"]+)['"]\s*\]"#, false, false),
+            pattern(r#"\b(?:os\.)?environ\.(?:get|setdefault)\(\s*['"](?P<name>[^'"]+)['"]"#, false, true),
+            pattern(r#"\bos\.getenv\(\s*['"](?P<name>[^'"]+)['"]"#, false, true),
+            pattern(r#"\bos\.putenv\(\s*['"](?P<name>[^'"]+)['"]"#, true, false),
+        ]);
+        patterns.insert("rust", vec![
+            pattern(r#"\benv::var(?:_os)?\(\s*"(?P<name>[^"]+)""#, false, false),
+            pattern(r#"\b(?:option_)?env!\(\s*"(?P<name>[^"]+)""#, false, false),
+            pattern(r#"\benv::(?:set_var|remove_var)\(\s*"(?P<name>[^"]+)""#, true, false),
+        ]);
+        patterns.insert("go", vec![
+            pattern(r#"\bos\.(?:Getenv|LookupEnv)\(\s*"(?P<name>[^"]+)""#, false, false),
+            pattern(r#"\bos\.(?:Setenv|Unsetenv)\(\s*"(?P<name>[^"]+)""#, true, false),
+        ]);
+        patterns.insert("java", vec![
+            pattern(r#"\bSystem\.getenv\(\s*"(?P<name>[^"]+)""#, false, false),
+        ]);
+        patterns.insert("csharp", vec![
+            pattern(r#"\bEnvironment\.GetEnvironmentVariable\(\s*"(?P<name>[^"]+)""#, false, false),
+            pattern(r#"\bEnvironment\.SetEnvironmentVariable\(\s*"(?P<name>[^"]+)""#, true, false),
+        ]);
+        patterns.insert("ruby", vec![
+            pattern(r#"\bENV\[\s*['"](?P<name>[^'"]+)['"]\s*\]"#, false, false),
+            pattern(r#"\bENV\.fetch\(\s*['"](?P<name>[^'"]+)['"]"#, false, true),
+        ]);
+        patterns.insert("php", vec![
+            pattern(r#"\bgetenv\(\s*['"](?P<name>[^'"]+)['"]"#, false, false),
+            pattern(r#"\$_ENV\[\s*['"](?P<name>[^'"]+)['"]\s*\]"#, false, false),
+            pattern(r#"\benv\(\s*['"](?P<name>[^'"]+)['"]"#, false, true),
+            pattern(r#"\bputenv\(\s*['"](?P<name>[^'"=]+)="#, true, false),
+        ]);
+        patterns.insert("cpp", vec![
+            pattern(r#"\b(?:std::)?getenv\(\s*"(?P<name>[^"]+)""#, false, false),
+            pattern(r#"\b(?:setenv|unsetenv)\(\s*"(?P<name>[^"]+)""#, true, false),
+        ]);
+        patterns
+    })
+}
+
+/// `= value` right after the access, but not a comparison
+fn is_assignment(tail: &str) -> bool {
+    let tail = tail.trim_start();
+    tail.starts_with('=') && !tail.starts_with("==") && !tail.starts_with("=>")
+}
+
+/// Another argument follows the variable name in the call
+fn next_is_argument(tail: &str) -> bool {
+    tail.trim_start().starts_with(',')
+}
+
+/// A fallback operator or method follows the access
+fn has_fallback(tail: &str) -> bool {
+    let tail = tail.trim_start();
+    let tail = tail.strip_prefix(')').unwrap_or(tail).trim_start();
+    ["??", "||", "or ", ".unwrap_or", ".or("].iter().any(|op| tail.starts_with(op))
+}
@@ -0,0 +1,26 @@
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Map a file read-only
+///
+/// The map must only be read. Like any mmap of a shared file, reading it can
+/// fault if another process truncates the file meanwhile; callers accept that
+/// for the speed of not copying large files.
+pub(crate) fn map_file(file: &File) -> io::Result<Mmap> {
+    // Safety: see above; the map is never written through
+    unsafe { Mmap::map(file) }
+}
+
+/// Replace a file's contents so a crash leaves either the old or the new ones
+///
+/// The bytes go to a temporary file beside `path`, which is flushed to disk
+/// and renamed over it.
+pub(crate) fn atomic_write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let temp = path.with_extension("tmp");
+    let mut file = File::create(&temp)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&temp, path)
+}
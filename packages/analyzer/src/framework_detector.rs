@@ -0,0 +1,329 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::semantic_analyzer::{extract_decorators, extract_imports, FileInput};
+
+/// Detected framework
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkInfo {
+    pub name: String,
+    /// Between 0 and 1; independent signals reinforce each other
+    pub confidence: f64,
+    /// Signals that matched, such as `import react` or `@Controller`
+    pub evidence: Vec<String>,
+    /// Number of files with at least one signal
+    #[napi(js_name = "fileCount")]
+    pub file_count: u32,
+}
+
+struct FrameworkRule {
+    name: &'static str,
+    languages: &'static [&'static str],
+    /// Import prefixes, also matched against manifest dependencies
+    packages: &'static [&'static str],
+    decorators: &'static [&'static str],
+    markers: &'static [&'static str],
+}
+
+const JS: &[&str] = &["typescript", "typescriptreact", "javascript", "javascriptreact", "vue", "svelte"];
+
+const RULES: &[FrameworkRule] = &[
+    FrameworkRule {
+        name: "React",
+        languages: JS,
+        packages: &["react", "react-dom"],
+        decorators: &[],
+        markers: &["useState(", "useEffect(", "React.Component", "className="],
+    },
+    FrameworkRule {
+        name: "Next.js",
+        languages: JS,
+        packages: &["next"],
+        decorators: &[],
+        markers: &["getServerSideProps", "getStaticProps", "'use client'", "\"use client\""],
+    },
+    FrameworkRule {
+        name: "Vue",
+        languages: JS,
+        packages: &["vue", "@vue"],
+        decorators: &[],
+        markers: &["defineComponent(", "<template>", "defineProps("],
+    },
+    FrameworkRule {
+        name: "Svelte",
+        languages: JS,
+        packages: &["svelte", "@sveltejs"],
+        decorators: &[],
+        markers: &["$:"],
+    },
+    FrameworkRule {
+        name: "Angular",
+        languages: JS,
+        packages: &["@angular"],
+        decorators: &["Component", "NgModule", "Directive", "Pipe"],
+        markers: &["ngOnInit"],
+    },
+    FrameworkRule {
+        name: "NestJS",
+        languages: JS,
+        packages: &["@nestjs"],
+        decorators: &["Controller", "Module", "Injectable", "Get", "Post"],
+        markers: &["NestFactory"],
+    },
+    FrameworkRule {
+        name: "Express",
+        languages: JS,
+        packages: &["express"],
+        decorators: &[],
+        markers: &["express()", "express.Router(", "(req, res"],
+    },
+    FrameworkRule {
+        name: "Django",
+        languages: &["python"],
+        packages: &["django", "rest_framework"],
+        decorators: &["login_required", "api_view"],
+        markers: &["models.Model", "urlpatterns"],
+    },
+    FrameworkRule {
+        name: "Flask",
+        languages: &["python"],
+        packages: &["flask"],
+        decorators: &["app.route", "blueprint.route"],
+        markers: &["Flask(__name__)"],
+    },
+    FrameworkRule {
+        name: "FastAPI",
+        languages: &["python"],
+        packages: &["fastapi"],
+        decorators: &["app.get", "app.post", "router.get", "router.post"],
+        markers: &["FastAPI(", "APIRouter("],
+    },
+    FrameworkRule {
+        name: "Rails",
+        languages: &["ruby"],
+        packages: &["rails"],
+        decorators: &[],
+        markers: &["ApplicationController", "ApplicationRecord", "ActiveRecord::", "Rails."],
+    },
+    FrameworkRule {
+        name: "Spring",
+        languages: &["java"],
+        packages: &["org.springframework"],
+        decorators: &["SpringBootApplication", "RestController", "Autowired", "Service", "Repository"],
+        markers: &[],
+    },
+    FrameworkRule {
+        name: "Laravel",
+        languages: &["php"],
+        packages: &["Illuminate", "laravel"],
+        decorators: &[],
+        markers: &["Route::", "extends Model"],
+    },
+    FrameworkRule {
+        name: "ASP.NET Core",
+        languages: &["csharp"],
+        packages: &["Microsoft.AspNetCore"],
+        decorators: &["ApiController", "HttpGet", "HttpPost", "Route"],
+        markers: &["WebApplication.CreateBuilder"],
+    },
+    FrameworkRule {
+        name: "Gin",
+        languages: &["go"],
+        packages: &["github.com/gin-gonic/gin"],
+        decorators: &[],
+        markers: &["gin.Default()", "*gin.Context"],
+    },
+    FrameworkRule {
+        name: "Echo",
+        languages: &["go"],
+        packages: &["github.com/labstack/echo"],
+        decorators: &[],
+        markers: &["echo.New()", "echo.Context"],
+    },
+    FrameworkRule {
+        name: "Actix Web",
+        languages: &["rust"],
+        packages: &["actix_web", "actix-web"],
+        decorators: &["get", "post", "actix_web::main"],
+        markers: &["HttpServer::new"],
+    },
+    FrameworkRule {
+        name: "Axum",
+        languages: &["rust"],
+        packages: &["axum"],
+        decorators: &[],
+        markers: &["Router::new()"],
+    },
+    FrameworkRule {
+        name: "Rocket",
+        languages: &["rust"],
+        packages: &["rocket"],
+        decorators: &["launch", "get", "post"],
+        markers: &["rocket::build()"],
+    },
+];
+
+// Weights of a single signal; several signals combine as independent evidence
+const IMPORT_WEIGHT: f64 = 0.9;
+const MANIFEST_WEIGHT: f64 = 0.95;
+const DECORATOR_WEIGHT: f64 = 0.5;
+const MARKER_WEIGHT: f64 = 0.25;
+
+/// Detect frameworks a file uses from its imports, decorators and characteristic code
+#[napi]
+pub fn detect_frameworks(code: String, language_id: String) -> Result<Vec<FrameworkInfo>> {
+    let signals = file_signals(&code, &language_id)?;
+    Ok(rank(signals.into_iter().map(|(name, signals)| (name, signals, 1)).collect()))
+}
+
+/// Detect frameworks across a workspace
+///
+/// Dependency manifests (`package.json`, `requirements.txt`, `Gemfile`, `go.mod`,
+/// `Cargo.toml`, `pom.xml`, ...) count as strong evidence alongside source files.
+#[napi]
+pub fn detect_workspace_frameworks(files: Vec<FileInput>) -> Result<Vec<FrameworkInfo>> {
+    let per_file = files
+        .par_iter()
+        .map(|file| {
+            if let Some(dependencies) = manifest_dependencies(&file.path, &file.code) {
+                Ok(manifest_signals(&dependencies))
+            } else {
+                file_signals(&file.code, &file.language_id)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut merged: HashMap<&'static str, (Vec<Signal>, u32)> = HashMap::new();
+    for signals in per_file {
+        for (name, signals) in signals {
+            let entry = merged.entry(name).or_default();
+            entry.1 += 1;
+            for signal in signals {
+                if !entry.0.iter().any(|(evidence, _)| *evidence == signal.0) {
+                    entry.0.push(signal);
+                }
+            }
+        }
+    }
+
+    Ok(rank(merged.into_iter().map(|(name, (signals, files))| (name, signals, files)).collect()))
+}
+
+/// Evidence text and its weight
+type Signal = (String, f64);
+type Signals = HashMap<&'static str, Vec<Signal>>;
+
+fn file_signals(code: &str, language_id: &str) -> Result<Signals> {
+    let mut signals: Signals = HashMap::new();
+    let rules: Vec<&FrameworkRule> = RULES.iter().filter(|r| r.languages.contains(&language_id)).collect();
+    if rules.is_empty() {
+        return Ok(signals);
+    }
+
+    let imports = extract_imports(code.to_string(), language_id.to_string())?;
+    let decorators = extract_decorators(code.to_string(), language_id.to_string())?;
+
+    for rule in rules {
+        let mut found = Vec::new();
+        for import in &imports {
+            if rule.packages.iter().any(|p| matches_package(&import.module, p)) {
+                found.push((format!("import {}", import.module), IMPORT_WEIGHT));
+            }
+        }
+        for decorator in &decorators {
+            if rule.decorators.contains(&decorator.name.as_str()) {
+                found.push((format!("@{}", decorator.name), DECORATOR_WEIGHT));
+            }
+        }
+        for marker in rule.markers {
+            if code.contains(marker) {
+                found.push((marker.to_string(), MARKER_WEIGHT));
+            }
+        }
+        if !found.is_empty() {
+            signals.insert(rule.name, found);
+        }
+    }
+
+    Ok(signals)
+}
+
+fn manifest_signals(dependencies: &[String]) -> Signals {
+    let mut signals: Signals = HashMap::new();
+    for rule in RULES {
+        let found: Vec<Signal> = dependencies
+            .iter()
+            .filter(|d| rule.packages.iter().any(|p| matches_package(d, p)))
+            .map(|d| (format!("dependency {}", d), MANIFEST_WEIGHT))
+            .collect();
+        if !found.is_empty() {
+            signals.insert(rule.name, found);
+        }
+    }
+    signals
+}
+
+/// Dependency names declared by a manifest file, or `None` if the path is not a manifest
+fn manifest_dependencies(path: &str, content: &str) -> Option<Vec<String>> {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+
+    if file_name == "package.json" || file_name == "composer.json" {
+        let manifest: serde_json::Value = serde_json::from_str(content).ok()?;
+        let sections = ["dependencies", "devDependencies", "peerDependencies", "require", "require-dev"];
+        return Some(
+            sections
+                .iter()
+                .filter_map(|s| manifest.get(s)?.as_object())
+                .flat_map(|deps| deps.keys().cloned())
+                .collect(),
+        );
+    }
+
+    let is_manifest = matches!(
+        file_name,
+        "requirements.txt" | "pyproject.toml" | "Pipfile" | "Gemfile" | "go.mod" | "Cargo.toml" | "pom.xml"
+            | "build.gradle" | "build.gradle.kts"
+    ) || (file_name.starts_with("requirements") && file_name.ends_with(".txt"))
+        || file_name.ends_with(".csproj");
+    if !is_manifest {
+        return None;
+    }
+
+    // Text manifests: take every package-like token and let the rules pick the relevant ones
+    let tokens = content
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '_' | '/' | '@')))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.trim_matches('.').to_string())
+        .collect();
+    Some(tokens)
+}
+
+/// Whether a module path is the package or lives under it
+fn matches_package(module: &str, package: &str) -> bool {
+    match module.strip_prefix(package) {
+        Some(rest) => rest.is_empty() || rest.starts_with(['/', '.', '\\', ':']),
+        None => false,
+    }
+}
+
+fn rank(detected: Vec<(&'static str, Vec<Signal>, u32)>) -> Vec<FrameworkInfo> {
+    let mut frameworks: Vec<FrameworkInfo> = detected
+        .into_iter()
+        .map(|(name, signals, file_count)| {
+            let miss: f64 = signals.iter().map(|(_, weight)| 1.0 - weight).product();
+            FrameworkInfo {
+                name: name.to_string(),
+                confidence: 1.0 - miss,
+                evidence: signals.into_iter().map(|(evidence, _)| evidence).collect(),
+                file_count,
+            }
+        })
+        .collect();
+
+    frameworks.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then_with(|| a.name.cmp(&b.name)));
+    frameworks
+}
@@ -0,0 +1,97 @@
+/// A result from one retriever; lists are ordered best first
+pub(crate) struct Ranked {
+    pub path: String,
+    pub start_line: u32,
+    /// Exclusive
+    pub end_line: u32,
+    /// Higher is better
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Fusion {
+    /// Reciprocal rank fusion: each list adds `1 / (k + rank)`
+    Reciprocal { k: f64 },
+    /// Scores scaled to [0, 1] within each list, then mixed
+    Weighted { text_weight: f64 },
+}
+
+/// A span of a file found by either list or both
+pub(crate) struct Fused {
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub score: f64,
+    /// Index into the text list of the best text result merged in
+    pub text: Option<usize>,
+    /// Index into the vector list of the best vector result merged in
+    pub vector: Option<usize>,
+}
+
+/// Merge text and vector results into one ranking, highest score first
+///
+/// Results for overlapping lines of the same file are merged into one span
+/// covering both, scored by the best result of each list within it
+pub(crate) fn fuse(text: &[Ranked], vector: &[Ranked], fusion: Fusion) -> Vec<Fused> {
+    let mut fused: Vec<Fused> = Vec::new();
+    for (source, list) in [text, vector].into_iter().enumerate() {
+        for (index, result) in list.iter().enumerate() {
+            let existing = fused.iter_mut().find(|span| {
+                span.path == result.path && span.start_line < result.end_line && result.start_line < span.end_line
+            });
+            let span = match existing {
+                Some(span) => span,
+                None => {
+                    fused.push(Fused {
+                        path: result.path.clone(),
+                        start_line: result.start_line,
+                        end_line: result.end_line,
+                        score: 0.0,
+                        text: None,
+                        vector: None,
+                    });
+                    fused.last_mut().unwrap()
+                }
+            };
+            let slot = if source == 0 { &mut span.text } else { &mut span.vector };
+            // Lists are best first, so a span keeps the first result merged in
+            if slot.is_some() {
+                continue;
+            }
+            *slot = Some(index);
+            span.start_line = span.start_line.min(result.start_line);
+            span.end_line = span.end_line.max(result.end_line);
+        }
+    }
+
+    let text_scores = normalized(text);
+    let vector_scores = normalized(vector);
+    for span in &mut fused {
+        span.score = match fusion {
+            Fusion::Reciprocal { k } => {
+                span.text.map_or(0.0, |rank| 1.0 / (k + rank as f64 + 1.0))
+                    + span.vector.map_or(0.0, |rank| 1.0 / (k + rank as f64 + 1.0))
+            }
+            Fusion::Weighted { text_weight } => {
+                span.text.map_or(0.0, |index| text_weight * text_scores[index])
+                    + span.vector.map_or(0.0, |index| (1.0 - text_weight) * vector_scores[index])
+            }
+        };
+    }
+    fused.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+            .then(a.start_line.cmp(&b.start_line))
+    });
+    fused
+}
+
+/// Scores min-max scaled to [0, 1]; all 1 when they are equal
+fn normalized(list: &[Ranked]) -> Vec<f64> {
+    let min = list.iter().map(|result| result.score).fold(f64::INFINITY, f64::min);
+    let max = list.iter().map(|result| result.score).fold(f64::NEG_INFINITY, f64::max);
+    list.iter()
+        .map(|result| if max > min { (result.score - min) / (max - min) } else { 1.0 })
+        .collect()
+}
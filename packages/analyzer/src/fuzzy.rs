@@ -0,0 +1,243 @@
+use napi_derive::napi;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Options for `fuzzy_match`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FuzzyOptions {
+    /// Return at most this many matches (default: all)
+    pub limit: Option<u32>,
+}
+
+/// A candidate matching a fuzzy query
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzyMatch {
+    /// Index into the candidate list
+    pub index: u32,
+    pub score: i32,
+    /// UTF-16 offsets of the matched characters, for highlighting
+    pub positions: Vec<u32>,
+}
+
+/// Rank candidates against a fuzzy query the way fzf's v2 algorithm does
+///
+/// Every query character must appear in order. Alignments are scored with
+/// Smith-Waterman-style dynamic programming: matches at word starts, after
+/// `_`, `/` or `.`, at camelCase humps and in consecutive runs score higher,
+/// and gaps cost a little per skipped character. Matching is case-insensitive
+/// unless the query has an uppercase letter. Results are sorted by score, then
+/// by shorter candidate, then by index; candidates are scored in parallel.
+#[napi]
+pub fn fuzzy_match(query: String, candidates: Vec<String>, options: Option<FuzzyOptions>) -> Vec<FuzzyMatch> {
+    let limit = options.unwrap_or_default().limit.map_or(usize::MAX, |l| l as usize);
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let query: Vec<char> = query
+        .chars()
+        .map(|c| if case_sensitive { c } else { fold_case(c) })
+        .collect();
+
+    let mut matches: Vec<(FuzzyMatch, usize)> = candidates
+        .par_iter()
+        .enumerate()
+        .filter_map(|(index, candidate)| {
+            let (score, positions) = fuzzy_score(&query, candidate, case_sensitive)?;
+            let fuzzy_match = FuzzyMatch { index: index as u32, score, positions };
+            Some((fuzzy_match, candidate.chars().count()))
+        })
+        .collect();
+
+    matches.sort_by(|(a, a_len), (b, b_len)| {
+        b.score.cmp(&a.score).then(a_len.cmp(b_len)).then(a.index.cmp(&b.index))
+    });
+    matches.truncate(limit);
+    matches.into_iter().map(|(fuzzy_match, _)| fuzzy_match).collect()
+}
+
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+const BONUS_BOUNDARY_WHITE: i32 = BONUS_BOUNDARY + 2;
+const BONUS_BOUNDARY_DELIMITER: i32 = BONUS_BOUNDARY + 1;
+const BONUS_NON_WORD: i32 = SCORE_MATCH / 2;
+const BONUS_CAMEL_123: i32 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+/// Enough to outweigh starting a gap, so runs stay together
+const BONUS_CONSECUTIVE: i32 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// Ordered as in fzf, which compares classes against `NonWord`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CharClass {
+    White,
+    NonWord,
+    Delimiter,
+    Lower,
+    Upper,
+    Letter,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    match c {
+        'a'..='z' => CharClass::Lower,
+        'A'..='Z' => CharClass::Upper,
+        '0'..='9' => CharClass::Number,
+        '/' | ',' | ':' | ';' | '|' => CharClass::Delimiter,
+        c if c.is_whitespace() => CharClass::White,
+        c if c.is_lowercase() => CharClass::Lower,
+        c if c.is_uppercase() => CharClass::Upper,
+        c if c.is_alphabetic() => CharClass::Letter,
+        c if c.is_numeric() => CharClass::Number,
+        _ => CharClass::NonWord,
+    }
+}
+
+/// Bonus for matching a character of class `class` right after one of `previous`
+fn bonus_for(previous: CharClass, class: CharClass) -> i32 {
+    if class > CharClass::NonWord {
+        match previous {
+            CharClass::White => return BONUS_BOUNDARY_WHITE,
+            CharClass::Delimiter => return BONUS_BOUNDARY_DELIMITER,
+            CharClass::NonWord => return BONUS_BOUNDARY,
+            _ => {}
+        }
+    }
+    if (previous == CharClass::Lower && class == CharClass::Upper)
+        || (previous != CharClass::Number && class == CharClass::Number)
+    {
+        return BONUS_CAMEL_123;
+    }
+    match class {
+        CharClass::NonWord | CharClass::Delimiter => BONUS_NON_WORD,
+        CharClass::White => BONUS_BOUNDARY_WHITE,
+        _ => 0,
+    }
+}
+
+fn fold_case(c: char) -> char {
+    if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Best alignment score of `query` in `text` and the UTF-16 offsets of the
+/// matched characters, or `None` if the query is not a subsequence
+fn fuzzy_score(query: &[char], text: &str, case_sensitive: bool) -> Option<(i32, Vec<u32>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = if case_sensitive { chars.clone() } else { chars.iter().map(|c| fold_case(*c)).collect() };
+
+    // Cheap subsequence check, which also narrows the columns to search
+    let mut next = 0;
+    let mut first = None;
+    for (j, c) in folded.iter().enumerate() {
+        if next < query.len() && *c == query[next] {
+            first.get_or_insert(j);
+            next += 1;
+        }
+    }
+    if next < query.len() {
+        return None;
+    }
+    let first = first?;
+    let last = folded.iter().rposition(|c| *c == query[query.len() - 1])?;
+    let width = last - first + 1;
+
+    let mut previous_class = if first == 0 { CharClass::White } else { char_class(chars[first - 1]) };
+    let bonuses: Vec<i32> = chars[first..=last]
+        .iter()
+        .map(|c| {
+            let class = char_class(*c);
+            let bonus = bonus_for(previous_class, class);
+            previous_class = class;
+            bonus
+        })
+        .collect();
+
+    // Per query character and column: best score with that character matched
+    // there, the length of the consecutive run it ends, and the column the
+    // previous query character matched at
+    let mut scores: Vec<Option<i32>> = vec![None; query.len() * width];
+    let mut runs = vec![0usize; query.len() * width];
+    let mut from = vec![0usize; query.len() * width];
+
+    for (i, q) in query.iter().enumerate() {
+        let row = i * width;
+        // Best score of the previous row followed by a gap of at least one
+        // character before this column, and where that row matched
+        let mut gapped: Option<(i32, usize)> = None;
+        for j in 0..width {
+            if i > 0 && j > 1 {
+                let opened = scores[row - width + j - 2].map(|score| (score + SCORE_GAP_START, j - 2));
+                let extended = gapped.map(|(score, k)| (score + SCORE_GAP_EXTENSION, k));
+                gapped = match (opened, extended) {
+                    (Some(o), Some(e)) if e.0 > o.0 => Some(e),
+                    (Some(o), _) => Some(o),
+                    (None, e) => e,
+                };
+            }
+            if folded[first + j] != *q {
+                continue;
+            }
+
+            let bonus = bonuses[j];
+            if i == 0 {
+                scores[row + j] = Some(SCORE_MATCH + bonus * BONUS_FIRST_CHAR_MULTIPLIER);
+                runs[row + j] = 1;
+                continue;
+            }
+
+            // Continuing a run keeps the bonus its first character earned
+            let consecutive = j.checked_sub(1).and_then(|k| scores[row - width + k]).map(|diagonal| {
+                let run = runs[row - width + j - 1] + 1;
+                let run_bonus = bonuses[j + 1 - run];
+                if bonus >= BONUS_BOUNDARY && bonus > run_bonus {
+                    (diagonal + SCORE_MATCH + bonus, 1)
+                } else {
+                    (diagonal + SCORE_MATCH + bonus.max(BONUS_CONSECUTIVE).max(run_bonus), run)
+                }
+            });
+            let after_gap = gapped.map(|(score, k)| (score + SCORE_MATCH + bonus, k));
+
+            match (consecutive, after_gap) {
+                (Some((score, run)), gap) if gap.is_none_or(|(gap_score, _)| score >= gap_score) => {
+                    scores[row + j] = Some(score);
+                    runs[row + j] = run;
+                    from[row + j] = j - 1;
+                }
+                (_, Some((score, k))) => {
+                    scores[row + j] = Some(score);
+                    runs[row + j] = 1;
+                    from[row + j] = k;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let last_row = (query.len() - 1) * width;
+    let (mut column, score) = (0..width)
+        .filter_map(|j| Some((j, scores[last_row + j]?)))
+        .rev()
+        .max_by_key(|(_, score)| *score)?;
+
+    let mut columns = vec![0; query.len()];
+    for i in (0..query.len()).rev() {
+        columns[i] = column;
+        column = from[i * width + column];
+    }
+
+    let mut utf16 = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0u32;
+    for c in &chars {
+        utf16.push(offset);
+        offset += c.len_utf16() as u32;
+    }
+    Some((score, columns.into_iter().map(|j| utf16[first + j]).collect()))
+}
@@ -0,0 +1,165 @@
+use memchr::{memchr, memrchr};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use regex::bytes::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::walker::{walk_workspace, WalkOptions};
+
+/// Files searched in parallel before checking the result limit, so results
+/// don't depend on thread scheduling
+const BATCH_FILES: usize = 256;
+
+/// Bytes checked for a NUL when deciding whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Options for `grep_workspace`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrepOptions {
+    /// Treat the pattern as a regex rather than a literal (default: false)
+    pub regex: Option<bool>,
+    /// Match case exactly (default: true)
+    #[napi(js_name = "caseSensitive")]
+    pub case_sensitive: Option<bool>,
+    /// Lines of context before and after each match (default: 0)
+    #[napi(js_name = "contextLines")]
+    pub context_lines: Option<u32>,
+    /// Stop after this many matches (default: 1000)
+    #[napi(js_name = "maxResults")]
+    pub max_results: Option<u32>,
+    /// Skip files ignored by git (default: true)
+    #[napi(js_name = "respectGitignore")]
+    pub respect_gitignore: Option<bool>,
+    /// Only search files matching one of these globs, relative to the root
+    #[napi(js_name = "includeGlobs")]
+    pub include_globs: Option<Vec<String>>,
+    /// Skip files and directories matching any of these globs
+    #[napi(js_name = "excludeGlobs")]
+    pub exclude_globs: Option<Vec<String>>,
+    /// Skip files larger than this many bytes
+    #[napi(js_name = "maxFileSize")]
+    pub max_file_size: Option<f64>,
+}
+
+/// A match found by `grep_workspace`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepMatch {
+    pub path: String,
+    pub line: u32,
+    /// Column in bytes
+    pub column: u32,
+    #[napi(js_name = "matchText")]
+    pub match_text: String,
+    /// The line the match starts on, without its line break
+    #[napi(js_name = "lineText")]
+    pub line_text: String,
+    /// Up to `contextLines` lines before the match line
+    #[napi(js_name = "contextBefore")]
+    pub context_before: Vec<String>,
+    /// Up to `contextLines` lines after the line the match ends on
+    #[napi(js_name = "contextAfter")]
+    pub context_after: Vec<String>,
+}
+
+/// Search the files under a workspace root on all cores
+///
+/// Files are chosen as by `walk_workspace`; binary files are skipped.
+/// Matches are ordered by path, then position
+#[napi]
+pub fn grep_workspace(root: String, pattern: String, options: Option<GrepOptions>) -> Result<Vec<GrepMatch>> {
+    let options = options.unwrap_or_default();
+    let max_results = options.max_results.unwrap_or(1000) as usize;
+    let context_lines = options.context_lines.unwrap_or(0) as usize;
+    let source = if options.regex.unwrap_or(false) { pattern } else { regex::escape(&pattern) };
+    let regex = RegexBuilder::new(&source)
+        .case_insensitive(!options.case_sensitive.unwrap_or(true))
+        .multi_line(true)
+        .build()
+        .map_err(|e| Error::from_reason(format!("Invalid pattern: {}", e)))?;
+
+    let files = walk_workspace(
+        root,
+        Some(WalkOptions {
+            respect_gitignore: options.respect_gitignore,
+            include_globs: options.include_globs,
+            exclude_globs: options.exclude_globs,
+            max_file_size: options.max_file_size,
+        }),
+    )?;
+
+    let mut matches = Vec::new();
+    for batch in files.chunks(BATCH_FILES) {
+        if matches.len() >= max_results {
+            break;
+        }
+        let found: Vec<Vec<GrepMatch>> = batch
+            .par_iter()
+            .map(|file| grep_file(&file.path, &regex, context_lines, max_results))
+            .collect();
+        matches.extend(found.into_iter().flatten());
+    }
+    matches.truncate(max_results);
+    Ok(matches)
+}
+
+/// The first `limit` matches in one file
+fn grep_file(path: &str, regex: &Regex, context_lines: usize, limit: usize) -> Vec<GrepMatch> {
+    let Ok(bytes) = fs::read(path) else { return Vec::new() };
+    if memchr(0, &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)]).is_some() {
+        return Vec::new();
+    }
+
+    let mut line = 0;
+    let mut counted = 0;
+    let mut matches = Vec::new();
+    for found in regex.find_iter(&bytes).take(limit) {
+        line += memchr::memchr_iter(b'\n', &bytes[counted..found.start()]).count();
+        counted = found.start();
+        let line_start = memrchr(b'\n', &bytes[..found.start()]).map_or(0, |i| i + 1);
+        let start_line_end = line_end(&bytes, found.start());
+        // A match ending in a line break ends on that line, not the next
+        let last_byte = if found.is_empty() { found.start() } else { found.end() - 1 };
+        let end_line_end = line_end(&bytes, last_byte);
+
+        let mut context_before = Vec::new();
+        let mut start = line_start;
+        while context_before.len() < context_lines && start > 0 {
+            let previous = memrchr(b'\n', &bytes[..start - 1]).map_or(0, |i| i + 1);
+            context_before.push(line_text(&bytes[previous..start - 1]));
+            start = previous;
+        }
+        context_before.reverse();
+
+        let mut context_after = Vec::new();
+        let mut end = end_line_end;
+        while context_after.len() < context_lines && end + 1 < bytes.len() {
+            let next = line_end(&bytes, end + 1);
+            context_after.push(line_text(&bytes[end + 1..next]));
+            end = next;
+        }
+
+        matches.push(GrepMatch {
+            path: path.to_string(),
+            line: line as u32,
+            column: (found.start() - line_start) as u32,
+            match_text: String::from_utf8_lossy(found.as_bytes()).into_owned(),
+            line_text: line_text(&bytes[line_start..start_line_end]),
+            context_before,
+            context_after,
+        });
+    }
+    matches
+}
+
+/// Offset of the line break ending the line containing `pos`, or the end
+fn line_end(bytes: &[u8], pos: usize) -> usize {
+    memchr(b'\n', &bytes[pos.min(bytes.len())..]).map_or(bytes.len(), |i| pos + i)
+}
+
+fn line_text(line: &[u8]) -> String {
+    String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned()
+}
@@ -1,28 +1,62 @@
-// use napi::bindgen_prelude::*;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::WalkBuilder;
+use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 use xxhash_rust::xxh3::Xxh3;
 
+use crate::file_io::map_file;
+use crate::streaming_tokenizer::lexical_tokens;
+
 /// Generate fast hash for cache keys
 /// 
 /// Uses xxHash3 which is 10-20x faster than JavaScript's simple hash
-/// and provides excellent distribution for cache keys
+/// and provides excellent distribution for cache keys. Pass a per-workspace
+/// `seed` to make hashes incomparable across workspaces
 #[napi]
-pub fn hash_prompt(prompt: String) -> String {
-    let mut hasher = Xxh3::new();
+pub fn hash_prompt(prompt: String, seed: Option<i64>) -> String {
+    let mut hasher = seeded(seed);
     hasher.update(prompt.as_bytes());
     format!("{:x}", hasher.digest())
 }
 
 /// Generate hash from multiple strings (for composite keys)
 #[napi]
-pub fn hash_composite(parts: Vec<String>) -> String {
-    let mut hasher = Xxh3::new();
+pub fn hash_composite(parts: Vec<String>, seed: Option<i64>) -> String {
+    let mut hasher = seeded(seed);
     for part in parts {
         hasher.update(part.as_bytes());
     }
     format!("{:x}", hasher.digest())
 }
 
+/// Hash a batch of strings or buffers in parallel, returning digests in order
+/// 
+/// Each digest matches `hash_prompt` with the same seed
+#[napi]
+pub fn hash_many(parts: Vec<Either<String, Buffer>>, seed: Option<i64>) -> Vec<String> {
+    let bytes: Vec<&[u8]> = parts
+        .iter()
+        .map(|part| match part {
+            Either::A(text) => text.as_bytes(),
+            Either::B(buffer) => buffer,
+        })
+        .collect();
+    bytes
+        .par_iter()
+        .map(|bytes| {
+            let mut hasher = seeded(seed);
+            hasher.update(bytes);
+            format!("{:x}", hasher.digest())
+        })
+        .collect()
+}
+
 /// Generate 128-bit hash for extra collision resistance
 #[napi]
 pub fn hash_prompt_128(prompt: String) -> String {
@@ -31,12 +65,243 @@ pub fn hash_prompt_128(prompt: String) -> String {
     format!("{:032x}", hasher.digest128())
 }
 
+/// Hash a file's contents without passing them through JS
+/// 
+/// The digest matches `hash_prompt` over the same contents
+#[napi]
+pub fn hash_file(path: String) -> Result<String> {
+    hash_file_contents(Path::new(&path))
+        .map(|digest| format!("{:x}", digest))
+        .map_err(|e| Error::from_reason(format!("Failed to hash {}: {}", path, e)))
+}
+
+/// Hash many files in parallel, returning a path to digest map
+/// 
+/// Files that can't be read (deleted since they were listed, permissions)
+/// are left out of the map rather than failing the batch
+#[napi]
+pub fn hash_files_parallel(paths: Vec<String>) -> HashMap<String, String> {
+    paths
+        .into_par_iter()
+        .filter_map(|path| {
+            let digest = hash_file_contents(Path::new(&path)).ok()?;
+            Some((path, format!("{:x}", digest)))
+        })
+        .collect()
+}
+
+/// Files smaller than this are read; mapping them costs more than copying
+const MMAP_THRESHOLD: u64 = 64 * 1024;
+
+fn hash_file_contents(path: &Path) -> std::io::Result<u64> {
+    let mut file = File::open(path)?;
+    if file.metadata()?.len() < MMAP_THRESHOLD {
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        return Ok(xxhash_rust::xxh3::xxh3_64(&bytes));
+    }
+    let map = map_file(&file)?;
+    Ok(xxhash_rust::xxh3::xxh3_64(&map))
+}
+
+/// Options for `hash_directory`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryHashOptions {
+    /// Extra gitignore-style patterns to skip, on top of `.gitignore` files
+    pub ignore: Option<Vec<String>>,
+}
+
+/// Merkle tree hashes of a directory
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryHash {
+    #[napi(js_name = "rootHash")]
+    pub root_hash: String,
+    /// Hash of every directory, keyed by `/`-separated path relative to the
+    /// root (`""` for the root itself)
+    pub directories: HashMap<String, String>,
+}
+
+/// Hash a directory tree bottom-up, Merkle style
+/// 
+/// A file hashes as in `hash_file`; a directory hashes the names, kinds and
+/// hashes of its entries in name order. A change to one file therefore only
+/// changes the hashes of the directories above it, so callers can compare
+/// per-directory hashes and rescan just the subtrees that differ.
+/// `.gitignore` files are respected (even outside a git repository), `.git`
+/// is skipped, and files are hashed in parallel
+#[napi]
+pub fn hash_directory(root: String, options: Option<DirectoryHashOptions>) -> Result<DirectoryHash> {
+    let options = options.unwrap_or_default();
+    let root_path = Path::new(&root);
+    if !root_path.is_dir() {
+        return Err(Error::from_reason(format!("Not a directory: {}", root)));
+    }
+
+    let mut matcher = GitignoreBuilder::new(root_path);
+    for pattern in options.ignore.unwrap_or_default() {
+        matcher
+            .add_line(None, &pattern)
+            .map_err(|e| Error::from_reason(format!("Invalid ignore pattern {}: {}", pattern, e)))?;
+    }
+    let matcher = matcher
+        .build()
+        .map_err(|e| Error::from_reason(format!("Invalid ignore patterns: {}", e)))?;
+
+    let walker = WalkBuilder::new(root_path)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.file_name() != ".git" && !matcher.matched(entry.path(), is_dir).is_ignore()
+        })
+        .build();
+
+    let mut directories = Vec::new();
+    let mut files = Vec::new();
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        let Some(file_type) = entry.file_type() else { continue };
+        let Ok(relative) = entry.path().strip_prefix(root_path) else { continue };
+        let relative: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+        let relative = relative.join("/");
+        if file_type.is_dir() {
+            directories.push(relative);
+        } else if file_type.is_file() {
+            files.push((relative, entry.into_path()));
+        }
+    }
+
+    // Entries of each directory: name, kind and hash
+    let mut entries: HashMap<String, Vec<(String, u8, u64)>> = HashMap::new();
+    let file_hashes: Vec<(String, u64)> = files
+        .into_par_iter()
+        .filter_map(|(relative, path)| Some((relative, hash_file_contents(&path).ok()?)))
+        .collect();
+    for (relative, digest) in file_hashes {
+        let (parent, name) = split_parent(&relative);
+        entries.entry(parent.to_string()).or_default().push((name.to_string(), b'f', digest));
+    }
+
+    // Deepest first, so every directory's children are done before it
+    directories.sort_by_key(|relative| std::cmp::Reverse(depth(relative)));
+    let mut hashes = HashMap::with_capacity(directories.len());
+    for relative in directories {
+        let mut children = entries.remove(&relative).unwrap_or_default();
+        children.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut hasher = Xxh3::new();
+        for (name, kind, digest) in &children {
+            hasher.update(&[*kind]);
+            hasher.update(name.as_bytes());
+            hasher.update(&[0]);
+            hasher.update(&digest.to_le_bytes());
+        }
+        let digest = hasher.digest();
+        if !relative.is_empty() {
+            let (parent, name) = split_parent(&relative);
+            entries.entry(parent.to_string()).or_default().push((name.to_string(), b'd', digest));
+        }
+        hashes.insert(relative, format!("{:x}", digest));
+    }
+
+    Ok(DirectoryHash {
+        root_hash: hashes.get("").cloned().unwrap_or_default(),
+        directories: hashes,
+    })
+}
+
+fn split_parent(relative: &str) -> (&str, &str) {
+    relative.rsplit_once('/').unwrap_or(("", relative))
+}
+
+fn depth(relative: &str) -> usize {
+    if relative.is_empty() {
+        0
+    } else {
+        relative.matches('/').count() + 1
+    }
+}
+
+/// Generate a BLAKE3 content hash
+/// 
+/// Cryptographic, unlike xxHash3: use it for cache integrity checks and for
+/// deduplicating untrusted input, where collisions could be crafted
+#[napi]
+pub fn hash_blake3(data: String) -> String {
+    blake3::hash(data.as_bytes()).to_hex().to_string()
+}
+
+/// CRC32 (IEEE, as in zlib and gzip) checksum, for exchanging checksums
+/// with services that expect it. Not for change detection: prefer xxHash3
+#[napi]
+pub fn crc32(data: Either<String, Buffer>) -> u32 {
+    match &data {
+        Either::A(text) => crc32fast::hash(text.as_bytes()),
+        Either::B(buffer) => crc32fast::hash(buffer),
+    }
+}
+
+/// 32-bit xxHash checksum, for interop with systems that use xxh32
+#[napi]
+pub fn xxh32(data: Either<String, Buffer>, seed: Option<u32>) -> u32 {
+    let seed = seed.unwrap_or(0);
+    match &data {
+        Either::A(text) => xxhash_rust::xxh32::xxh32(text.as_bytes(), seed),
+        Either::B(buffer) => xxhash_rust::xxh32::xxh32(buffer, seed),
+    }
+}
+
+/// Context for deriving BLAKE3 keys from string secrets
+const KEY_CONTEXT: &str = "inline-analyzer 2024 keyed hash";
+
+/// Keyed BLAKE3 hash (a MAC), for authenticating cache entries
+/// 
+/// A Buffer key must be exactly 32 bytes; a string key of any length is
+/// first turned into one with BLAKE3's key derivation. Digests from
+/// different keys can't be forged or compared without the key
+#[napi]
+pub fn hash_keyed(key: Either<String, Buffer>, data: Either<String, Buffer>) -> Result<String> {
+    let key: [u8; 32] = match &key {
+        Either::A(secret) => blake3::derive_key(KEY_CONTEXT, secret.as_bytes()),
+        Either::B(buffer) => buffer[..].try_into().map_err(|_| {
+            Error::from_reason(format!("Key must be 32 bytes, got {}", buffer.len()))
+        })?,
+    };
+    let data: &[u8] = match &data {
+        Either::A(text) => text.as_bytes(),
+        Either::B(buffer) => buffer,
+    };
+    Ok(blake3::keyed_hash(&key, data).to_hex().to_string())
+}
+
+/// Deterministic UUID-formatted ID for content, the same on every machine
+/// 
+/// Like UUIDv5 but over BLAKE3, so it is marked as version 8 (custom). The
+/// namespace and every part are length-prefixed, so `["a:b", "c"]` and
+/// `["a", "b:c"]` get different IDs whatever delimiters the parts contain
+#[napi]
+pub fn content_id(namespace: String, parts: Vec<String>) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for field in std::iter::once(&namespace).chain(&parts) {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hasher.finalize().as_bytes()[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x80;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
 /// Streaming hash for large inputs
 /// 
 /// Useful for hashing large files without loading entirely into memory
 #[napi]
 pub struct StreamingHasher {
     hasher: Xxh3,
+    seed: Option<i64>,
 }
 
 #[napi]
@@ -45,12 +310,25 @@ impl StreamingHasher {
     pub fn new() -> Self {
         Self {
             hasher: Xxh3::new(),
+            seed: None,
+        }
+    }
+    
+    /// Hasher whose digests match `hashPrompt(data, seed)`
+    #[napi(factory)]
+    pub fn with_seed(seed: i64) -> Self {
+        Self {
+            hasher: seeded(Some(seed)),
+            seed: Some(seed),
         }
     }
     
     #[napi]
-    pub fn update(&mut self, data: String) {
-        self.hasher.update(data.as_bytes());
+    pub fn update(&mut self, data: Either<String, Buffer>) {
+        match data {
+            Either::A(text) => self.hasher.update(text.as_bytes()),
+            Either::B(buffer) => self.hasher.update(&buffer),
+        }
     }
     
     #[napi]
@@ -58,13 +336,491 @@ impl StreamingHasher {
         format!("{:x}", self.hasher.digest())
     }
     
+    /// The 64-bit digest as 8 big-endian bytes, the same value as `digest`
+    #[napi]
+    pub fn digest_bytes(&self) -> Buffer {
+        self.hasher.digest().to_be_bytes().to_vec().into()
+    }
+    
     #[napi]
     pub fn digest128(&self) -> String {
         format!("{:032x}", self.hasher.digest128())
     }
     
+    /// Independent copy of the current state, to hash a shared prefix once
+    /// and branch per suffix
+    #[napi(js_name = "clone")]
+    pub fn fork(&self) -> StreamingHasher {
+        Self {
+            hasher: self.hasher.clone(),
+            seed: self.seed,
+        }
+    }
+    
+    #[napi]
+    pub fn reset(&mut self) {
+        self.hasher = seeded(self.seed);
+    }
+}
+
+impl Default for StreamingHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// xxHash3 hasher, seeded when a seed is given (negative seeds wrap)
+fn seeded(seed: Option<i64>) -> Xxh3 {
+    match seed {
+        Some(seed) => Xxh3::with_seed(seed as u64),
+        None => Xxh3::new(),
+    }
+}
+
+/// Streaming BLAKE3 hash for large inputs
+#[napi]
+pub struct StreamingBlake3Hasher {
+    hasher: blake3::Hasher,
+}
+
+#[napi]
+impl StreamingBlake3Hasher {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            hasher: blake3::Hasher::new(),
+        }
+    }
+    
+    #[napi]
+    pub fn update(&mut self, data: Either<String, Buffer>) {
+        match data {
+            Either::A(text) => self.hasher.update(text.as_bytes()),
+            Either::B(buffer) => self.hasher.update(&buffer),
+        };
+    }
+    
+    #[napi]
+    pub fn digest(&self) -> String {
+        self.hasher.finalize().to_hex().to_string()
+    }
+    
     #[napi]
     pub fn reset(&mut self) {
-        self.hasher = Xxh3::new();
+        self.hasher.reset();
     }
 }
+
+impl Default for StreamingBlake3Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tokens per shingle for MinHash and SimHash
+const SHINGLE_SIZE: usize = 3;
+
+/// MinHash signature of a text, for estimating similarity between documents
+/// 
+/// The text is split into word and punctuation tokens and shingled into
+/// overlapping runs of three, so whitespace and formatting don't matter.
+/// Signatures from the same `num_hashes` compare with `minhash_similarity`
+#[napi]
+pub fn minhash_signature(text: String, num_hashes: u32) -> Vec<u32> {
+    let mut signature = vec![u32::MAX; num_hashes as usize];
+    for digest in shingle_hashes(&text) {
+        for (i, min) in signature.iter_mut().enumerate() {
+            // One hash per shingle, remixed into a family of independent ones
+            let value = (splitmix64(digest ^ (i as u64).wrapping_mul(0x9e3779b97f4a7c15)) >> 32) as u32;
+            *min = (*min).min(value);
+        }
+    }
+    signature
+}
+
+/// Estimated Jaccard similarity of the texts behind two MinHash signatures
+#[napi]
+pub fn minhash_similarity(sig_a: Vec<u32>, sig_b: Vec<u32>) -> Result<f64> {
+    if sig_a.len() != sig_b.len() {
+        return Err(Error::from_reason(format!(
+            "Signature lengths differ: {} and {}",
+            sig_a.len(),
+            sig_b.len()
+        )));
+    }
+    if sig_a.is_empty() {
+        return Ok(0.0);
+    }
+    let equal = sig_a.iter().zip(&sig_b).filter(|(a, b)| a == b).count();
+    Ok(equal as f64 / sig_a.len() as f64)
+}
+
+/// 64-bit SimHash fingerprint of a text, as 16 hex digits
+/// 
+/// Shingled like `minhash_signature`. Near-duplicate texts get fingerprints a
+/// small `hamming_distance` apart, where exact hashes would share nothing
+#[napi]
+pub fn simhash(text: String) -> String {
+    format!("{:016x}", simhash_of(&text))
+}
+
+pub(crate) fn simhash_of(text: &str) -> u64 {
+    let mut weights = [0i32; 64];
+    for digest in shingle_hashes(text) {
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if digest >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0u64, |fingerprint, (bit, _)| fingerprint | 1 << bit)
+}
+
+/// Number of differing bits between two hex fingerprints from `simhash`
+#[napi]
+pub fn hamming_distance(a: String, b: String) -> Result<u32> {
+    let parse = |hex: &str| {
+        u64::from_str_radix(hex, 16).map_err(|_| Error::from_reason(format!("Invalid fingerprint: {}", hex)))
+    };
+    Ok((parse(&a)? ^ parse(&b)?).count_ones())
+}
+
+/// Hashes of the overlapping token shingles of a text
+fn shingle_hashes(text: &str) -> Vec<u64> {
+    let tokens = shingle_tokens(text);
+    let shingle_size = SHINGLE_SIZE.min(tokens.len()).max(1);
+    tokens
+        .windows(shingle_size)
+        .map(|shingle| {
+            let mut hasher = Xxh3::new();
+            for token in shingle {
+                hasher.update(token.as_bytes());
+                hasher.update(&[0]);
+            }
+            hasher.digest()
+        })
+        .collect()
+}
+
+/// Identifier-like runs and single punctuation characters
+fn shingle_tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() || c == '_' {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            tokens.push(&text[start..i]);
+        }
+        if !c.is_whitespace() {
+            tokens.push(&text[i..i + c.len_utf8()]);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+const fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Mersenne prime modulus for `RollingHasher`
+const ROLLING_MODULUS: u64 = (1 << 61) - 1;
+const ROLLING_BASE: u64 = 0x1f3d_5b79_a2c4_e681 % ROLLING_MODULUS;
+const ROLLING_BASE_INVERSE: u64 = pow_mod(ROLLING_BASE, ROLLING_MODULUS - 2);
+
+/// Rabin-Karp rolling hash over a window of bytes
+/// 
+/// Bytes enter with `push` and leave, oldest first, with `pop`, which takes
+/// the byte being removed so the window itself isn't stored. The digest is
+/// `sum((b[i] + 1) * BASE^(n - 1 - i)) mod 2^61 - 1`, so equal windows get
+/// equal digests however they were built up
+#[napi]
+pub struct RollingHasher {
+    hash: u64,
+    len: u32,
+    /// `BASE^(len - 1)`, the weight of the oldest byte
+    top: u64,
+}
+
+#[napi]
+impl RollingHasher {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self { hash: 0, len: 0, top: 0 }
+    }
+    
+    /// Append a byte to the end of the window
+    #[napi]
+    pub fn push(&mut self, byte: u32) -> Result<()> {
+        let value = rolling_value(byte)?;
+        self.hash = (mul_mod(self.hash, ROLLING_BASE) + value) % ROLLING_MODULUS;
+        self.top = if self.len == 0 { 1 } else { mul_mod(self.top, ROLLING_BASE) };
+        self.len += 1;
+        Ok(())
+    }
+    
+    /// Remove `byte`, which must be the oldest byte in the window
+    #[napi]
+    pub fn pop(&mut self, byte: u32) -> Result<()> {
+        let value = rolling_value(byte)?;
+        if self.len == 0 {
+            return Err(Error::from_reason("Cannot pop from an empty window"));
+        }
+        self.hash = (self.hash + ROLLING_MODULUS - mul_mod(value, self.top)) % ROLLING_MODULUS;
+        self.top = mul_mod(self.top, ROLLING_BASE_INVERSE);
+        self.len -= 1;
+        Ok(())
+    }
+    
+    #[napi]
+    pub fn digest(&self) -> String {
+        format!("{:x}", self.hash)
+    }
+    
+    /// Number of bytes in the window
+    #[napi(getter)]
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+    
+    #[napi]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    #[napi]
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for RollingHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bytes count from 1 so leading zero bytes still change the digest
+fn rolling_value(byte: u32) -> Result<u64> {
+    if byte > 0xff {
+        return Err(Error::from_reason(format!("Not a byte: {}", byte)));
+    }
+    Ok(byte as u64 + 1)
+}
+
+const fn mul_mod(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % ROLLING_MODULUS as u128) as u64
+}
+
+const fn pow_mod(mut base: u64, mut exponent: u64) -> u64 {
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Options for `cdc_chunks`, in bytes
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CdcOptions {
+    /// Smallest chunk, except at the end of the data (default: avg / 4)
+    pub min: Option<u32>,
+    /// Target average chunk size, rounded up to a power of two of at most
+    /// 2^31 (default: 8192)
+    pub avg: Option<u32>,
+    /// Largest chunk (default: avg * 8)
+    pub max: Option<u32>,
+}
+
+/// A content-defined chunk
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdcChunk {
+    #[napi(js_name = "startByte")]
+    pub start_byte: u32,
+    #[napi(js_name = "endByte")]
+    pub end_byte: u32,
+    /// xxHash3 of the chunk, as from `hash_prompt`
+    pub hash: String,
+}
+
+/// Split data into content-defined chunks with FastCDC
+/// 
+/// Boundaries depend only on nearby bytes, so an edit moves at most the
+/// chunks around it and the rest keep their hashes, which makes the chunks
+/// good dedup units. Uses a gear hash with normalized chunking: cuts are
+/// harder to find before `avg` bytes and easier after
+#[napi]
+pub fn cdc_chunks(data: Buffer, options: Option<CdcOptions>) -> Result<Vec<CdcChunk>> {
+    let options = options.unwrap_or_default();
+    let avg = options.avg.unwrap_or(8192).max(64);
+    let avg = avg.checked_next_power_of_two().ok_or_else(|| {
+        Error::from_reason(format!("Average chunk size must be at most 2^31, got {}", avg))
+    })? as usize;
+    let min = options.min.map_or(avg / 4, |min| min as usize);
+    let max = options.max.map_or(avg * 8, |max| max as usize);
+    if min > avg || avg > max {
+        return Err(Error::from_reason(format!(
+            "Chunk sizes must satisfy min <= avg <= max, got {} / {} / {}",
+            min, avg, max
+        )));
+    }
+
+    let bits = avg.trailing_zeros();
+    // Top bits of the gear hash depend on the most bytes
+    let mask_hard = !0u64 << (64 - (bits + 1));
+    let mask_easy = !0u64 << (64 - (bits - 1));
+
+    let data: &[u8] = &data;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let rest = &data[start..];
+        let length = cdc_cut_point(rest, min, avg, max, mask_hard, mask_easy);
+        chunks.push(CdcChunk {
+            start_byte: start as u32,
+            end_byte: (start + length) as u32,
+            hash: format!("{:x}", xxhash_rust::xxh3::xxh3_64(&rest[..length])),
+        });
+        start += length;
+    }
+    Ok(chunks)
+}
+
+/// Length of the next chunk of `data`
+fn cdc_cut_point(data: &[u8], min: usize, avg: usize, max: usize, mask_hard: u64, mask_easy: u64) -> usize {
+    if data.len() <= min {
+        return data.len();
+    }
+    let end = data.len().min(max);
+    let normal = avg.min(end);
+    let mut hash = 0u64;
+    for (i, byte) in data.iter().enumerate().take(end).skip(min) {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+        let mask = if i < normal { mask_hard } else { mask_easy };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Random values for the gear hash, fixed so boundaries are stable everywhere
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = splitmix64(i as u64);
+        i += 1;
+    }
+    table
+};
+
+/// Hash the meaningful tokens of code, ignoring comments and whitespace
+/// 
+/// Tokens are lexed as by `StreamingTokenizer`, so edits that only touch
+/// comments or formatting keep the digest
+#[napi]
+pub fn hash_tokens(code: String, language_id: String) -> String {
+    let mut hasher = Xxh3::new();
+    for (start, end, token_type) in lexical_tokens(&code, &language_id) {
+        if token_type == "comment" {
+            continue;
+        }
+        // Length-prefixed so token boundaries are part of the hash
+        hasher.update(&((end - start) as u32).to_le_bytes());
+        hasher.update(&code.as_bytes()[start..end]);
+    }
+    format!("{:x}", hasher.digest())
+}
+
+/// Options for `hash_ast`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AstHashOptions {
+    /// Hash identifiers by first appearance, so consistent renames keep the
+    /// digest (default: false)
+    #[napi(js_name = "ignoreIdentifiers")]
+    pub ignore_identifiers: Option<bool>,
+    /// Hash strings, numbers and booleans by kind only (default: false)
+    #[napi(js_name = "ignoreLiterals")]
+    pub ignore_literals: Option<bool>,
+}
+
+/// Hash the shape of a syntax tree rather than its text
+/// 
+/// Whitespace, formatting and comments don't change the digest; with the
+/// options, neither do renames or literal values. Useful as a cache key
+/// for analysis results that only depend on the code's structure
+#[napi]
+pub fn hash_ast(code: String, language_id: String, options: Option<AstHashOptions>) -> Result<String> {
+    let options = options.unwrap_or_default();
+    let ignore_identifiers = options.ignore_identifiers.unwrap_or(false);
+    let ignore_literals = options.ignore_literals.unwrap_or(false);
+    let tree = crate::ast_parser::parse_tree(&code, &language_id)?;
+
+    let mut hasher = Xxh3::new();
+    let mut identifiers: HashMap<&str, u32> = HashMap::new();
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        let kind = node.kind();
+        let comment = kind.contains("comment");
+        // Literals are hashed as a whole, without their fragments
+        let literal = ignore_literals && is_literal_kind(kind);
+
+        if !comment {
+            hasher.update(b"(");
+            hasher.update(kind.as_bytes());
+            let text = &code[node.byte_range()];
+            if node.is_named() && kind.contains("identifier") {
+                if ignore_identifiers {
+                    let next = identifiers.len() as u32;
+                    let index = *identifiers.entry(text).or_insert(next);
+                    hasher.update(&index.to_le_bytes());
+                } else {
+                    hasher.update(text.as_bytes());
+                }
+            } else if node.is_named() && node.child_count() == 0 && !literal {
+                hasher.update(text.as_bytes());
+            }
+            if !literal && cursor.goto_first_child() {
+                continue;
+            }
+            hasher.update(b")");
+        }
+
+        // Close every node whose last child this was
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+            hasher.update(b")");
+        }
+    }
+    Ok(format!("{:x}", hasher.digest()))
+}
+
+fn is_literal_kind(kind: &str) -> bool {
+    kind.contains("string")
+        || kind.contains("number")
+        || kind.contains("integer")
+        || kind.contains("float")
+        || kind.ends_with("_literal")
+        || matches!(kind, "true" | "false" | "char")
+}
@@ -0,0 +1,99 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Approximate count of distinct items in fixed memory
+/// 
+/// Uses `2^precision` one-byte registers (16 KiB at the default precision of
+/// 14) for a standard error of about `1.04 / sqrt(2^precision)`, 0.8% at 14.
+/// Sketches with the same precision can be merged, e.g. per file into one
+/// per repository
+#[napi]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+    precision: u32,
+}
+
+#[napi]
+impl HyperLogLog {
+    /// Sketch with `2^precision` registers, for a precision of 4 to 18
+    #[napi(constructor)]
+    pub fn new(precision: Option<u32>) -> Result<Self> {
+        let precision = precision.unwrap_or(14);
+        if !(4..=18).contains(&precision) {
+            return Err(Error::from_reason(format!(
+                "Precision must be between 4 and 18, got {}",
+                precision
+            )));
+        }
+        Ok(Self {
+            registers: vec![0; 1 << precision],
+            precision,
+        })
+    }
+
+    #[napi]
+    pub fn insert(&mut self, item: String) {
+        self.insert_hash(xxh3_64(item.as_bytes()));
+    }
+
+    /// Insert a batch, hashing in parallel
+    #[napi]
+    pub fn insert_many(&mut self, items: Vec<String>) {
+        let hashes: Vec<u64> = items.par_iter().map(|item| xxh3_64(item.as_bytes())).collect();
+        for hash in hashes {
+            self.insert_hash(hash);
+        }
+    }
+
+    /// Estimated number of distinct items inserted
+    #[napi]
+    pub fn count(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+
+        // Linear counting is more accurate while many registers are empty
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+
+    /// Add another sketch's items to this one
+    #[napi]
+    pub fn merge(&mut self, other: &HyperLogLog) -> Result<()> {
+        if other.precision != self.precision {
+            return Err(Error::from_reason(format!(
+                "Cannot merge precision {} into precision {}",
+                other.precision, self.precision
+            )));
+        }
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+        Ok(())
+    }
+
+    #[napi]
+    pub fn reset(&mut self) {
+        self.registers.fill(0);
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        // Top bits pick the register, the rest give the rank
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+}
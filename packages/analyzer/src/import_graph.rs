@@ -0,0 +1,608 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::semantic_analyzer::{extract_exports, extract_imports};
+
+/// Options for building an import graph
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportGraphOptions {
+    /// File extensions to include (default: JS/TS family and `.py`)
+    pub extensions: Option<Vec<String>>,
+    /// Directory names to skip (default: `node_modules`, `dist`, `build`, `out`, `target`, `coverage`, `__pycache__`, `venv`)
+    pub exclude: Option<Vec<String>>,
+    /// Report bare package specifiers such as `react` (default: false)
+    #[napi(js_name = "includeExternal")]
+    pub include_external: Option<bool>,
+}
+
+/// A file in the import graph
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportGraphNode {
+    /// Path relative to the root directory, `/`-separated
+    pub path: String,
+    /// Workspace files this file imports
+    pub imports: Vec<String>,
+    /// Workspace files that import this file
+    #[napi(js_name = "importedBy")]
+    pub imported_by: Vec<String>,
+    /// Package specifiers, only filled when `includeExternal` is set
+    pub external: Vec<String>,
+    /// Relative specifiers that match no file
+    pub unresolved: Vec<String>,
+}
+
+/// Workspace import graph
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportGraph {
+    pub nodes: Vec<ImportGraphNode>,
+    #[napi(js_name = "edgeCount")]
+    pub edge_count: u32,
+}
+
+/// Build the import graph of a directory tree
+///
+/// Relative specifiers are resolved the way Node and TypeScript do: exact file,
+/// then added extensions, then `.js` → `.ts` source mapping, then `index` files.
+/// Python relative (`.mod`) and root-level absolute (`pkg.mod`) imports resolve
+/// to `mod.py` or `mod/__init__.py`.
+#[napi]
+pub fn build_import_graph(root_dir: String, options: Option<ImportGraphOptions>) -> Result<ImportGraph> {
+    let options = options.unwrap_or_default();
+    let include_external = options.include_external.unwrap_or(false);
+    let (files, resolved) = resolve_workspace(&root_dir, options)?;
+
+    let mut imported_by: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (path, resolved) in files.iter().zip(&resolved) {
+        for target in &resolved.internal {
+            imported_by.entry(target.as_str()).or_default().insert(path.as_str());
+        }
+    }
+
+    let mut edge_count = 0;
+    let nodes = files
+        .iter()
+        .zip(resolved.iter())
+        .map(|(path, resolved)| {
+            edge_count += resolved.internal.len() as u32;
+            ImportGraphNode {
+                path: path.clone(),
+                imports: resolved.internal.iter().cloned().collect(),
+                imported_by: imported_by
+                    .get(path.as_str())
+                    .map(|sources| sources.iter().map(|s| s.to_string()).collect())
+                    .unwrap_or_default(),
+                external: if include_external { resolved.external.iter().cloned().collect() } else { Vec::new() },
+                unresolved: resolved.unresolved.iter().cloned().collect(),
+            }
+        })
+        .collect();
+
+    Ok(ImportGraph { nodes, edge_count })
+}
+
+/// An import of one workspace file by another
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyEdge {
+    /// The importing file
+    pub from: String,
+    /// The imported file
+    pub to: String,
+}
+
+/// Module-level dependency structure of a workspace
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    /// Files, sorted, as paths relative to the root
+    pub modules: Vec<String>,
+    pub edges: Vec<DependencyEdge>,
+    /// Groups of files that import each other, directly or not; largest
+    /// first, each sorted
+    pub cycles: Vec<Vec<String>>,
+    /// Files by layer: the first imports no workspace file and each later
+    /// one imports only from earlier layers and its own cycles
+    pub layers: Vec<Vec<String>>,
+}
+
+/// Build the file-level dependency graph of a directory tree, with its
+/// import cycles and topological layers
+///
+/// Takes the same options as `build_import_graph` and resolves imports the
+/// same way; external packages are left out. Files in a
+/// cycle share a layer, so the layers always exist
+#[napi]
+pub fn build_dependency_graph(root_dir: String, options: Option<ImportGraphOptions>) -> Result<DependencyGraph> {
+    let (modules, resolved) = resolve_workspace(&root_dir, options.unwrap_or_default())?;
+    let positions: HashMap<&str, usize> = modules.iter().enumerate().map(|(i, path)| (path.as_str(), i)).collect();
+    let adjacency: Vec<Vec<usize>> = resolved
+        .iter()
+        .map(|resolved| resolved.internal.iter().filter_map(|target| positions.get(target.as_str()).copied()).collect())
+        .collect();
+
+    let components = strongly_connected(&adjacency);
+    let mut component_of = vec![0; modules.len()];
+    for (component, members) in components.iter().enumerate() {
+        for &member in members {
+            component_of[member] = component;
+        }
+    }
+    // Components come after everything they import, so each layer is known
+    // by the time a component importing it is reached
+    let mut component_layers = vec![0usize; components.len()];
+    for (component, members) in components.iter().enumerate() {
+        component_layers[component] = members
+            .iter()
+            .flat_map(|&member| &adjacency[member])
+            .filter(|&&target| component_of[target] != component)
+            .map(|&target| component_layers[component_of[target]] + 1)
+            .max()
+            .unwrap_or(0);
+    }
+
+    let mut layers: Vec<Vec<String>> = vec![Vec::new(); component_layers.iter().max().map_or(0, |max| max + 1)];
+    for (module, path) in modules.iter().enumerate() {
+        layers[component_layers[component_of[module]]].push(path.clone());
+    }
+    let mut cycles: Vec<Vec<String>> = components
+        .iter()
+        .filter(|members| members.len() > 1 || adjacency[members[0]].contains(&members[0]))
+        .map(|members| {
+            let mut cycle: Vec<String> = members.iter().map(|&member| modules[member].clone()).collect();
+            cycle.sort();
+            cycle
+        })
+        .collect();
+    cycles.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    let edges = adjacency
+        .iter()
+        .enumerate()
+        .flat_map(|(from, targets)| targets.iter().map(move |&to| (from, to)))
+        .map(|(from, to)| DependencyEdge {
+            from: modules[from].clone(),
+            to: modules[to].clone(),
+        })
+        .collect();
+
+    Ok(DependencyGraph { modules, edges, cycles, layers })
+}
+
+/// Strongly connected components by Tarjan's algorithm, each listed after
+/// every component it has edges into
+fn strongly_connected(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let mut index = vec![UNVISITED; adjacency.len()];
+    let mut low = vec![0; adjacency.len()];
+    let mut on_stack = vec![false; adjacency.len()];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next = 0;
+
+    for root in 0..adjacency.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        // Explicit stack of (node, next edge to follow), as import chains
+        // can be deeper than the native stack allows
+        let mut work = vec![(root, 0)];
+        index[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&(node, edge)) = work.last() {
+            if let Some(&target) = adjacency[node].get(edge) {
+                work.last_mut().unwrap().1 += 1;
+                if index[target] == UNVISITED {
+                    index[target] = next;
+                    low[target] = next;
+                    next += 1;
+                    stack.push(target);
+                    on_stack[target] = true;
+                    work.push((target, 0));
+                } else if on_stack[target] {
+                    low[node] = low[node].min(index[target]);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == index[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// A symbol exposed by a barrel file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReexportInfo {
+    /// Name the barrel exports the symbol under
+    pub name: String,
+    /// Name of the export in the module that declares it (`default`, `*` for namespaces)
+    #[napi(js_name = "originalName")]
+    pub original_name: String,
+    pub kind: String, // 'function' | 'class' | 'variable' | 'type' | 'interface' | 'enum' | 'value' | 'namespace'
+    /// File that declares the symbol, or the specifier when it could not be resolved
+    pub source: String,
+    #[napi(js_name = "isResolved")]
+    pub is_resolved: bool,
+    /// Number of re-export hops between the barrel and the declaring file
+    pub depth: u32,
+}
+
+/// Flatten the symbols a barrel file exposes
+///
+/// Follows `export * from './x'`, `export * as ns from './x'` and
+/// `export { a as b } from './y'` chains through the file system. When `code`
+/// is given it is used instead of reading `filePath`, which then only anchors
+/// relative specifiers. Explicit exports shadow names from `export *`, and
+/// `export *` never re-exports `default`, as in ES modules.
+#[napi]
+pub fn expand_reexports(file_path: String, code: Option<String>) -> Result<Vec<ReexportInfo>> {
+    let code = match code {
+        Some(code) => code,
+        None => std::fs::read_to_string(&file_path)
+            .map_err(|e| Error::from_reason(format!("Failed to read {}: {}", file_path, e)))?,
+    };
+
+    let absolute = match Path::new(&file_path).is_absolute() {
+        true => file_path.clone(),
+        false => std::env::current_dir()
+            .map(|dir| dir.join(&file_path).to_string_lossy().to_string())
+            .unwrap_or_else(|_| file_path.clone()),
+    };
+    let path = absolute.replace('\\', "/");
+    let anchor_len = if path.starts_with('/') {
+        1
+    } else if path.as_bytes().get(1) == Some(&b':') && path.as_bytes().get(2) == Some(&b'/') {
+        3
+    } else {
+        0
+    };
+    let (anchor, relative) = path.split_at(anchor_len);
+
+    let mut expander = BarrelExpander {
+        anchor,
+        memo: HashMap::new(),
+        in_progress: HashSet::new(),
+    };
+    let relative = normalize("", relative);
+    expander.in_progress.insert(relative.clone());
+    Ok(expander.expand(&relative, &code))
+}
+
+struct BarrelExpander<'a> {
+    /// Root of absolute paths (`/`, `C:/`), empty for relative ones
+    anchor: &'a str,
+    memo: HashMap<String, Vec<ReexportInfo>>,
+    in_progress: HashSet<String>,
+}
+
+impl BarrelExpander<'_> {
+    fn expand(&mut self, path: &str, code: &str) -> Vec<ReexportInfo> {
+        let language = if path.ends_with(".js") || path.ends_with(".jsx") || path.ends_with(".mjs") || path.ends_with(".cjs") {
+            "javascript"
+        } else {
+            "typescript"
+        };
+        let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+        let mut explicit = Vec::new();
+        let mut star = Vec::new();
+        for export in extract_exports(code.to_string(), language.to_string()).unwrap_or_default() {
+            let Some(specifier) = export.source else {
+                explicit.push(ReexportInfo {
+                    original_name: export.name.clone(),
+                    name: export.name,
+                    kind: export.kind,
+                    source: self.full_path(path),
+                    is_resolved: true,
+                    depth: 0,
+                });
+                continue;
+            };
+            let target = self.resolve(dir, &specifier);
+
+            if export.kind == "namespace" {
+                match (&target, export.name == "*") {
+                    (Some(target), true) => star.extend(
+                        self.load(target)
+                            .into_iter()
+                            .filter(|symbol| symbol.name != "default")
+                            .map(|symbol| ReexportInfo { depth: symbol.depth + 1, ..symbol }),
+                    ),
+                    _ => {
+                        let list = if export.name == "*" { &mut star } else { &mut explicit };
+                        list.push(ReexportInfo {
+                            name: export.name,
+                            original_name: "*".to_string(),
+                            kind: export.kind,
+                            is_resolved: target.is_some(),
+                            source: target.map(|t| self.full_path(&t)).unwrap_or(specifier),
+                            depth: 1,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let local = export.local_name.unwrap_or_else(|| export.name.clone());
+            let origin = target
+                .as_ref()
+                .and_then(|target| self.load(target).into_iter().find(|symbol| symbol.name == local));
+            explicit.push(match origin {
+                Some(origin) => ReexportInfo {
+                    name: export.name,
+                    depth: origin.depth + 1,
+                    ..origin
+                },
+                None => ReexportInfo {
+                    name: export.name,
+                    original_name: local,
+                    kind: export.kind,
+                    is_resolved: target.is_some(),
+                    source: target.map(|t| self.full_path(&t)).unwrap_or(specifier),
+                    depth: 1,
+                },
+            });
+        }
+
+        let mut seen: HashSet<String> = explicit.iter().map(|symbol| symbol.name.clone()).collect();
+        explicit.extend(star.into_iter().filter(|symbol| symbol.name == "*" || seen.insert(symbol.name.clone())));
+        explicit
+    }
+
+    /// Expansion of another file, memoized; cycles expand to nothing
+    fn load(&mut self, path: &str) -> Vec<ReexportInfo> {
+        if let Some(symbols) = self.memo.get(path) {
+            return symbols.clone();
+        }
+        if !self.in_progress.insert(path.to_string()) {
+            return Vec::new();
+        }
+        let code = std::fs::read_to_string(self.full_path(path)).unwrap_or_default();
+        let symbols = self.expand(path, &code);
+        self.in_progress.remove(path);
+        self.memo.insert(path.to_string(), symbols.clone());
+        symbols
+    }
+
+    fn resolve(&self, dir: &str, specifier: &str) -> Option<String> {
+        if !specifier.starts_with('.') {
+            return None;
+        }
+        resolve_relative(dir, specifier, &|candidate| Path::new(&self.full_path(candidate)).is_file())
+    }
+
+    fn full_path(&self, path: &str) -> String {
+        format!("{}{}", self.anchor, path)
+    }
+}
+
+const DEFAULT_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs", ".py"];
+const DEFAULT_EXCLUDE: &[&str] = &["node_modules", "dist", "build", "out", "target", "coverage", "__pycache__", "venv"];
+
+/// Extensions tried, in order, for extensionless relative specifiers
+const RESOLVE_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".d.ts", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs"];
+
+/// Files under a root, sorted, with the imports of each resolved
+fn resolve_workspace(root_dir: &str, options: ImportGraphOptions) -> Result<(Vec<String>, Vec<ResolvedImports>)> {
+    let root = Path::new(root_dir);
+    if !root.is_dir() {
+        return Err(Error::from_reason(format!("Not a directory: {}", root_dir)));
+    }
+
+    let extensions = options
+        .extensions
+        .unwrap_or_else(|| DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect());
+    let exclude: HashSet<String> = options
+        .exclude
+        .unwrap_or_else(|| DEFAULT_EXCLUDE.iter().map(|e| e.to_string()).collect())
+        .into_iter()
+        .collect();
+
+    let mut files = Vec::new();
+    collect_files(root, "", &extensions, &exclude, &mut files)
+        .map_err(|e| Error::from_reason(format!("Failed to read {}: {}", root_dir, e)))?;
+    let file_set: HashSet<&str> = files.iter().map(String::as_str).collect();
+
+    let resolved: Vec<ResolvedImports> = files
+        .par_iter()
+        .map(|path| {
+            let code = std::fs::read_to_string(root.join(path)).unwrap_or_default();
+            resolve_file(path, &code, &file_set)
+        })
+        .collect();
+    Ok((files, resolved))
+}
+
+#[derive(Default)]
+struct ResolvedImports {
+    internal: BTreeSet<String>,
+    external: BTreeSet<String>,
+    unresolved: BTreeSet<String>,
+}
+
+/// Recursively collect files under `dir`, as `/`-separated paths relative to the root
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    extensions: &[String],
+    exclude: &HashSet<String>,
+    files: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || exclude.contains(&name) {
+            continue;
+        }
+        let relative = if prefix.is_empty() { name.clone() } else { format!("{}/{}", prefix, name) };
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &relative, extensions, exclude, files)?;
+        } else if file_type.is_file() && extensions.iter().any(|ext| name.ends_with(ext.as_str())) {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+fn resolve_file(path: &str, code: &str, files: &HashSet<&str>) -> ResolvedImports {
+    let mut resolved = ResolvedImports::default();
+    let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+    if path.ends_with(".py") {
+        for import in extract_imports(code.to_string(), "python".to_string()).unwrap_or_default() {
+            match resolve_python(dir, &import.module, &import.imports, files) {
+                Some(targets) => resolved.internal.extend(targets),
+                None if import.module.starts_with('.') => {
+                    resolved.unresolved.insert(import.module);
+                }
+                None => {
+                    resolved.external.insert(import.module);
+                }
+            }
+        }
+        return resolved;
+    }
+
+    for specifier in module_specifiers(code) {
+        if !(specifier.starts_with('.') || specifier.starts_with('/')) {
+            resolved.external.insert(specifier.to_string());
+            continue;
+        }
+        match resolve_relative(dir, specifier, &|candidate| files.contains(candidate)) {
+            Some(target) => {
+                resolved.internal.insert(target);
+            }
+            None => {
+                resolved.unresolved.insert(specifier.to_string());
+            }
+        }
+    }
+    resolved
+}
+
+static SPECIFIER_RE: OnceLock<Regex> = OnceLock::new();
+
+/// Module specifiers of static and dynamic imports, re-exports and `require` calls
+fn module_specifiers(code: &str) -> Vec<&str> {
+    let re = SPECIFIER_RE.get_or_init(|| {
+        Regex::new(concat!(
+            r#"\b(?:import|export)\s[^;'"`]*?\bfrom\s*['"]([^'"\n]+)['"]"#,
+            r#"|\bimport\s*['"]([^'"\n]+)['"]"#,
+            r#"|\b(?:require|import)\s*\(\s*['"`]([^'"`\n]+)['"`]\s*\)"#,
+        ))
+        .unwrap()
+    });
+    re.captures_iter(code)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)).or_else(|| caps.get(3)))
+        .map(|m| m.as_str())
+        .collect()
+}
+
+/// Resolve a relative or root-absolute specifier to a file for which `is_file` holds
+fn resolve_relative(dir: &str, specifier: &str, is_file: &dyn Fn(&str) -> bool) -> Option<String> {
+    let base = if specifier.starts_with('/') { normalize("", specifier) } else { normalize(dir, specifier) };
+    let base = base.trim_end_matches('/');
+    let exists = |candidate: &str| is_file(candidate).then(|| candidate.to_string());
+
+    if let Some(found) = exists(base) {
+        return Some(found);
+    }
+    for ext in RESOLVE_EXTENSIONS {
+        if let Some(found) = exists(&format!("{}{}", base, ext)) {
+            return Some(found);
+        }
+    }
+    // TypeScript ESM code imports `./x.js` for a `./x.ts` source
+    for (js, ts) in [(".js", &[".ts", ".tsx"][..]), (".jsx", &[".tsx"]), (".mjs", &[".mts"]), (".cjs", &[".cts"])] {
+        if let Some(stem) = base.strip_suffix(js) {
+            for ext in ts {
+                if let Some(found) = exists(&format!("{}{}", stem, ext)) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+    let index = if base.is_empty() { "index".to_string() } else { format!("{}/index", base) };
+    RESOLVE_EXTENSIONS.iter().find_map(|ext| exists(&format!("{}{}", index, ext)))
+}
+
+/// Resolve a Python module to workspace files; `None` if it is not part of the workspace
+fn resolve_python(dir: &str, module: &str, names: &[String], files: &HashSet<&str>) -> Option<Vec<String>> {
+    let dots = module.len() - module.trim_start_matches('.').len();
+    let rest = module[dots..].replace('.', "/");
+    // One dot is the current package, each extra dot goes one level up
+    let package = if dots == 0 { String::new() } else { normalize(dir, &"../".repeat(dots - 1)) };
+    let base = join(&package, &rest);
+
+    // A package directory takes precedence over a module file of the same name
+    let module_file = |base: &str| {
+        [join(base, "__init__.py"), format!("{}.py", base)]
+            .into_iter()
+            .filter(|candidate| candidate != ".py")
+            .find(|candidate| files.contains(candidate.as_str()))
+    };
+
+    // `from . import a, b` imports submodules when they exist
+    if rest.is_empty() {
+        let submodules: Vec<String> = names
+            .iter()
+            .filter_map(|name| module_file(&join(&base, name.split_whitespace().next().unwrap_or(name))))
+            .collect();
+        if !submodules.is_empty() {
+            return Some(submodules);
+        }
+    }
+    module_file(&base).map(|file| vec![file])
+}
+
+fn join(dir: &str, name: &str) -> String {
+    match (dir.is_empty(), name.is_empty()) {
+        (true, _) => name.to_string(),
+        (_, true) => dir.to_string(),
+        _ => format!("{}/{}", dir, name),
+    }
+}
+
+/// Lexically join `path` onto `dir`, resolving `.` and `..`
+fn normalize(dir: &str, path: &str) -> String {
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
@@ -0,0 +1,698 @@
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use rayon::prelude::*;
+use regex::RegexBuilder;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::fusion::{fuse, Fusion, Ranked};
+use crate::semantic_analyzer::FileInput;
+use crate::text_index::{chunk_terms, ChunkTerms, TextIndex};
+use crate::trigram_index::{regex_query, trigrams_of, TrigramIndex};
+use crate::vector_store::VectorStore;
+use crate::walker::language_from_path;
+use crate::watcher::{watch_workspace, FileChange, WorkspaceWatcher};
+
+/// Bumped whenever the stored layout changes; older indexes are dropped
+const SCHEMA_VERSION: u32 = 4;
+
+/// SQLite database inside the storage directory, one row per file
+const INDEX_FILE: &str = "workspace-index.db";
+
+/// An indexed file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedFileInfo {
+    pub path: String,
+    #[napi(js_name = "languageId")]
+    pub language_id: String,
+    /// xxh3 of the content, 16 hex digits
+    #[napi(js_name = "contentHash")]
+    pub content_hash: String,
+    /// Size on disk when indexed, in bytes
+    pub size: i64,
+    /// Modification time on disk when indexed, in ms since the epoch
+    pub modified: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct IndexedDocument {
+    language_id: String,
+    content_hash: u64,
+    size: u64,
+    modified: Option<f64>,
+    chunks: Vec<ChunkTerms>,
+    /// Distinct byte trigrams of the content, sorted
+    trigrams: Vec<u32>,
+}
+
+impl IndexedDocument {
+    fn new(path: &str, code: &str, language_id: String) -> Self {
+        let (size, modified) = disk_stamp(Path::new(path)).unwrap_or((code.len() as u64, None));
+        Self {
+            content_hash: xxh3_64(code.as_bytes()),
+            size,
+            modified,
+            chunks: chunk_terms(code, &language_id),
+            trigrams: trigrams_of(code.as_bytes()),
+            language_id,
+        }
+    }
+}
+
+/// Options for `WorkspaceIndexer.watch`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchOptions {
+    /// Quiet time before a batch of changes is handled (default: 200)
+    #[napi(js_name = "debounceMs")]
+    pub debounce_ms: Option<u32>,
+    /// Ignore changes matched by the root .gitignore (default: true)
+    #[napi(js_name = "respectGitignore")]
+    pub respect_gitignore: Option<bool>,
+}
+
+/// A change the watcher applied to the index
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexChange {
+    pub kind: String, // 'added' | 'changed' | 'removed' | 'renamed'
+    pub path: String,
+    /// Previous path of a renamed file
+    #[napi(js_name = "oldPath")]
+    pub old_path: Option<String>,
+}
+
+/// A chunk found by `WorkspaceIndexer.searchText`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSearchHit {
+    pub path: String,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    /// Exclusive
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    /// BM25 score
+    pub score: f64,
+}
+
+/// Options for `WorkspaceIndexer.hybridSearch`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HybridSearchOptions {
+    /// 'rrf' | 'weighted' (default: 'rrf')
+    pub fusion: Option<String>,
+    /// Rank offset for reciprocal rank fusion (default: 60)
+    #[napi(js_name = "rrfK")]
+    pub rrf_k: Option<f64>,
+    /// Share of the weighted score from BM25, the rest from vectors
+    /// (default: 0.5)
+    #[napi(js_name = "textWeight")]
+    pub text_weight: Option<f64>,
+    /// Results taken from each search before fusing (default: 4 * k)
+    pub candidates: Option<u32>,
+    /// Metadata the vectors must match, as for `VectorStore.search`
+    pub filter: Option<HashMap<String, String>>,
+}
+
+/// A span of a file found by `WorkspaceIndexer.hybridSearch`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridSearchHit {
+    pub path: String,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    /// Exclusive
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    /// Fused score, higher is better
+    pub score: f64,
+    /// BM25 score, when the text search found the span
+    #[napi(js_name = "textScore")]
+    pub text_score: Option<f64>,
+    /// Distance of the nearest vector in the span, when the vector search
+    /// found it
+    #[napi(js_name = "vectorDistance")]
+    pub vector_distance: Option<f64>,
+    #[napi(js_name = "vectorId")]
+    pub vector_id: Option<String>,
+}
+
+/// Options for `WorkspaceIndexer.searchRegex`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegexSearchOptions {
+    /// Ignore case (default: false)
+    #[napi(js_name = "caseInsensitive")]
+    pub case_insensitive: Option<bool>,
+    /// Stop after this many matches (default: 1000)
+    #[napi(js_name = "maxResults")]
+    pub max_results: Option<u32>,
+}
+
+/// A regex match in an indexed file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegexSearchHit {
+    pub path: String,
+    pub line: u32,
+    /// Column in bytes
+    pub column: u32,
+    /// The line the match starts on, without its line break
+    #[napi(js_name = "lineText")]
+    pub line_text: String,
+}
+
+/// What the indexer knows, shared with its watcher thread
+#[derive(Default)]
+struct IndexState {
+    files: HashMap<String, IndexedDocument>,
+    text: TextIndex,
+    trigrams: TrigramIndex,
+    /// Paths added, changed or removed since the last load or save
+    dirty: HashSet<String>,
+    /// Whether storage holds the index as of the last load or save
+    synced: bool,
+}
+
+impl IndexState {
+    /// Index a file, returning false when its content is unchanged
+    fn update(&mut self, path: String, code: &str, language_id: String) -> bool {
+        let content_hash = xxh3_64(code.as_bytes());
+        if let Some(file) = self.files.get_mut(&path) {
+            if file.content_hash == content_hash && file.language_id == language_id {
+                (file.size, file.modified) = disk_stamp(Path::new(&path)).unwrap_or((code.len() as u64, None));
+                self.dirty.insert(path);
+                return false;
+            }
+        }
+        let document = IndexedDocument::new(&path, code, language_id);
+        self.insert(path, document);
+        true
+    }
+
+    fn insert(&mut self, path: String, document: IndexedDocument) {
+        self.remove(&path);
+        self.text.add(&path, &document.chunks);
+        self.trigrams.add(&path, &document.trigrams);
+        self.dirty.insert(path.clone());
+        self.files.insert(path, document);
+    }
+
+    fn remove(&mut self, path: &str) -> bool {
+        let Some(document) = self.files.remove(path) else { return false };
+        self.text.remove(path);
+        self.trigrams.remove(path, &document.trigrams);
+        self.dirty.insert(path.to_string());
+        true
+    }
+
+    fn replace(&mut self, files: HashMap<String, IndexedDocument>) {
+        self.text.clear();
+        self.trigrams.clear();
+        for (path, file) in &files {
+            self.text.add(path, &file.chunks);
+            self.trigrams.add(path, &file.trigrams);
+        }
+        self.files = files;
+        self.dirty.clear();
+        self.synced = false;
+    }
+
+    /// Apply a batch of changes from disk, returning those that changed the
+    /// index
+    fn apply(&mut self, changes: Vec<FileChange>) -> Vec<IndexChange> {
+        let mut applied = Vec::new();
+        for change in changes {
+            match change {
+                FileChange::Changed(path) => {
+                    let path = path.to_string_lossy().into_owned();
+                    let added = !self.files.contains_key(&path);
+                    if self.update_from_disk(&path) {
+                        let kind = if added { "added" } else { "changed" };
+                        applied.push(IndexChange { kind: kind.to_string(), path, old_path: None });
+                    }
+                }
+                FileChange::Removed(path) => {
+                    // A removed directory takes its files with it
+                    for path in self.paths_under(&path) {
+                        self.remove(&path);
+                        applied.push(IndexChange { kind: "removed".to_string(), path, old_path: None });
+                    }
+                }
+                FileChange::Renamed(from, to) => {
+                    let moved: Vec<(String, String)> = self
+                        .paths_under(&from)
+                        .into_iter()
+                        .filter_map(|old| {
+                            let relative = Path::new(&old).strip_prefix(&from).ok()?;
+                            let new = if relative.as_os_str().is_empty() { to.clone() } else { to.join(relative) };
+                            Some((old.clone(), new.to_string_lossy().into_owned()))
+                        })
+                        .collect();
+                    for (old, new) in moved {
+                        self.remove(&old);
+                        // Gone again, or unreadable, by the time the batch is applied
+                        if !self.update_from_disk(&new) && !self.files.contains_key(&new) {
+                            applied.push(IndexChange { kind: "removed".to_string(), path: old, old_path: None });
+                            continue;
+                        }
+                        applied.push(IndexChange { kind: "renamed".to_string(), path: new, old_path: Some(old) });
+                    }
+                    // Renamed in from outside the index
+                    let to = to.to_string_lossy().into_owned();
+                    if to.is_empty() || self.files.contains_key(&to) || !Path::new(&to).is_file() {
+                        continue;
+                    }
+                    if self.update_from_disk(&to) {
+                        applied.push(IndexChange { kind: "added".to_string(), path: to, old_path: None });
+                    }
+                }
+            }
+        }
+        applied
+    }
+
+    /// Read and index a file, returning false if unchanged or unreadable
+    fn update_from_disk(&mut self, path: &str) -> bool {
+        let Ok(code) = fs::read_to_string(path) else { return false };
+        let language_id = language_from_path(Path::new(path)).unwrap_or("plaintext").to_string();
+        self.update(path.to_string(), &code, language_id)
+    }
+
+    /// Indexed paths equal to or inside `path`
+    fn paths_under(&self, path: &Path) -> Vec<String> {
+        self.files.keys().filter(|indexed| Path::new(indexed).starts_with(path)).cloned().collect()
+    }
+}
+
+/// Index of the files in a workspace, persisted between editor sessions
+///
+/// The index is kept in a SQLite database in the storage directory. `load`
+/// restores it and `staleFiles` tells which files changed on disk since, so
+/// start-up only re-indexes those; `save` writes only the files that changed.
+/// The database carries a schema version; one written by a different version
+/// is ignored and the index starts cold. `watch` keeps the index current as
+/// files change on disk
+#[napi]
+pub struct WorkspaceIndexer {
+    storage_dir: PathBuf,
+    state: Arc<Mutex<IndexState>>,
+    watcher: Option<WorkspaceWatcher>,
+}
+
+#[napi]
+impl WorkspaceIndexer {
+    /// Create an empty index stored under `storageDir`
+    #[napi(constructor)]
+    pub fn new(storage_dir: String) -> Self {
+        Self {
+            storage_dir: PathBuf::from(storage_dir),
+            state: Arc::default(),
+            watcher: None,
+        }
+    }
+
+    /// Replace the index with the stored one
+    ///
+    /// Returns false, leaving the index empty, when nothing is stored or it
+    /// has another schema version
+    #[napi]
+    pub fn load(&mut self) -> Result<bool> {
+        let path = self.storage_dir.join(INDEX_FILE);
+        let mut state = self.state.lock().unwrap();
+        state.replace(HashMap::new());
+        if !path.exists() {
+            return Ok(false);
+        }
+        let failed =
+            |e: rusqlite::Error| Error::from_reason(format!("Failed to read index {}: {}", path.display(), e));
+        let connection = Connection::open(&path).map_err(failed)?;
+        let schema: u32 =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(failed)?;
+        if schema != SCHEMA_VERSION {
+            return Ok(false);
+        }
+        let rows: Vec<(String, Vec<u8>)> = connection
+            .prepare("SELECT path, document FROM files")
+            .and_then(|mut statement| statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect())
+            .map_err(failed)?;
+        let files = rows
+            .into_par_iter()
+            .map(|(file, document)| {
+                let document = bincode::deserialize(&document)
+                    .map_err(|e| Error::from_reason(format!("Invalid index {}: {}: {}", path.display(), file, e)))?;
+                Ok((file, document))
+            })
+            .collect::<Result<HashMap<String, IndexedDocument>>>()?;
+        state.replace(files);
+        state.synced = true;
+        Ok(true)
+    }
+
+    /// Write the index to the storage directory, creating it if needed
+    ///
+    /// Only files changed since the last `load` or `save` are written, unless
+    /// the stored index is from another schema version or was never loaded
+    #[napi]
+    pub fn save(&self) -> Result<()> {
+        let path = self.storage_dir.join(INDEX_FILE);
+        let failed =
+            |e: rusqlite::Error| Error::from_reason(format!("Failed to write index {}: {}", path.display(), e));
+        fs::create_dir_all(&self.storage_dir).map_err(|e| {
+            Error::from_reason(format!("Failed to create {}: {}", self.storage_dir.display(), e))
+        })?;
+        let mut connection = Connection::open(&path).map_err(failed)?;
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let paths: Vec<&String> =
+            if state.synced { state.dirty.iter().collect() } else { state.files.keys().collect() };
+        // Removed files have no document
+        let documents = paths
+            .into_par_iter()
+            .map(|file| {
+                let document = state
+                    .files
+                    .get(file)
+                    .map(bincode::serialize)
+                    .transpose()
+                    .map_err(|e| Error::from_reason(format!("Serialization error: {}", e)))?;
+                Ok((file, document))
+            })
+            .collect::<Result<Vec<(&String, Option<Vec<u8>>)>>>()?;
+
+        let transaction = connection.transaction().map_err(failed)?;
+        if !state.synced {
+            transaction
+                .execute_batch(
+                    "DROP TABLE IF EXISTS files;
+                     CREATE TABLE files (path TEXT PRIMARY KEY, document BLOB NOT NULL) WITHOUT ROWID;",
+                )
+                .and_then(|_| transaction.pragma_update(None, "user_version", SCHEMA_VERSION))
+                .map_err(failed)?;
+        }
+        {
+            let mut upsert = transaction
+                .prepare("INSERT OR REPLACE INTO files (path, document) VALUES (?1, ?2)")
+                .map_err(failed)?;
+            let mut delete = transaction.prepare("DELETE FROM files WHERE path = ?1").map_err(failed)?;
+            for (file, document) in documents {
+                match document {
+                    Some(document) => upsert.execute(params![file, document]),
+                    None => delete.execute(params![file]),
+                }
+                .map_err(failed)?;
+            }
+        }
+        transaction.commit().map_err(failed)?;
+        state.dirty.clear();
+        state.synced = true;
+        Ok(())
+    }
+
+    /// Index a file, returning false when its content is unchanged
+    ///
+    /// Size and modification time are taken from the file on disk, if any
+    #[napi]
+    pub fn update_file(&mut self, path: String, code: String, language_id: String) -> bool {
+        self.state.lock().unwrap().update(path, &code, language_id)
+    }
+
+    /// Index many files in parallel, returning how many changed
+    #[napi]
+    pub fn update_files(&mut self, files: Vec<FileInput>) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        let changed: Vec<(String, IndexedDocument)> = files
+            .into_par_iter()
+            .filter(|input| {
+                let content_hash = xxh3_64(input.code.as_bytes());
+                !state
+                    .files
+                    .get(&input.path)
+                    .is_some_and(|file| file.content_hash == content_hash && file.language_id == input.language_id)
+            })
+            .map(|input| {
+                let document = IndexedDocument::new(&input.path, &input.code, input.language_id);
+                (input.path, document)
+            })
+            .collect();
+        let count = changed.len() as u32;
+        for (path, document) in changed {
+            state.insert(path, document);
+        }
+        count
+    }
+
+    /// Drop a file, returning whether it was indexed
+    #[napi]
+    pub fn remove_file(&mut self, path: String) -> bool {
+        self.state.lock().unwrap().remove(&path)
+    }
+
+    /// The paths that need indexing: unknown ones and those whose size or
+    /// modification time on disk differ from when they were indexed
+    #[napi]
+    pub fn stale_files(&self, paths: Vec<String>) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        paths
+            .into_par_iter()
+            .filter(|path| match state.files.get(path) {
+                Some(file) => disk_stamp(Path::new(path)) != Some((file.size, file.modified)),
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Indexed paths that no longer exist on disk
+    #[napi]
+    pub fn deleted_files(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.files.par_iter().filter(|(path, _)| !Path::new(path).exists()).map(|(path, _)| path.clone()).collect()
+    }
+
+    #[napi]
+    pub fn get_file(&self, path: String) -> Option<IndexedFileInfo> {
+        let state = self.state.lock().unwrap();
+        state.files.get(&path).map(|file| IndexedFileInfo {
+            path,
+            language_id: file.language_id.clone(),
+            content_hash: format!("{:016x}", file.content_hash),
+            size: file.size as i64,
+            modified: file.modified,
+        })
+    }
+
+    /// Number of indexed files
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.state.lock().unwrap().files.len() as u32
+    }
+
+    #[napi]
+    pub fn clear(&mut self) {
+        self.state.lock().unwrap().replace(HashMap::new());
+    }
+
+    /// Chunks of the indexed files best matching a keyword query, by BM25
+    ///
+    /// Files are indexed in chunks of 40 lines. Terms are the words of
+    /// identifiers, keywords, strings and comments, with identifiers also
+    /// split at camelCase and snake_case boundaries
+    #[napi]
+    pub fn search_text(&self, query: String, limit: Option<u32>) -> Vec<TextSearchHit> {
+        let state = self.state.lock().unwrap();
+        state
+            .text
+            .search(&query, limit.unwrap_or(20) as usize)
+            .into_iter()
+            .map(|hit| TextSearchHit {
+                path: hit.path.to_string(),
+                start_line: hit.start_line,
+                end_line: hit.end_line,
+                score: hit.score,
+            })
+            .collect()
+    }
+
+    /// The `k` best spans for a query by both BM25 and embedding similarity
+    ///
+    /// Vectors are placed by the `path`, `startLine` and `endLine` of their
+    /// metadata; vectors without them are left out. Results from the two
+    /// searches that overlap in a file are merged into one span
+    #[napi]
+    pub fn hybrid_search(
+        &self,
+        query_text: String,
+        query_embedding: Float32Array,
+        k: u32,
+        vectors: &VectorStore,
+        options: Option<HybridSearchOptions>,
+    ) -> Result<Vec<HybridSearchHit>> {
+        let options = options.unwrap_or_default();
+        let fusion = match options.fusion.as_deref().unwrap_or("rrf") {
+            "rrf" => Fusion::Reciprocal { k: options.rrf_k.unwrap_or(60.0) },
+            "weighted" => Fusion::Weighted { text_weight: options.text_weight.unwrap_or(0.5).clamp(0.0, 1.0) },
+            other => return Err(Error::from_reason(format!("Unknown fusion: {}", other))),
+        };
+        let candidates = options.candidates.unwrap_or(k.saturating_mul(4)).max(k);
+
+        let text: Vec<Ranked> = self
+            .state
+            .lock()
+            .unwrap()
+            .text
+            .search(&query_text, candidates as usize)
+            .into_iter()
+            .map(|hit| Ranked {
+                path: hit.path.to_string(),
+                start_line: hit.start_line,
+                end_line: hit.end_line,
+                score: hit.score,
+            })
+            .collect();
+        let matches = vectors.search(query_embedding, candidates, options.filter)?;
+        let mut placed = Vec::new();
+        let vector: Vec<Ranked> = matches
+            .iter()
+            .filter_map(|found| {
+                let line = |key: &str| found.metadata.get(key)?.parse::<u32>().ok();
+                let ranked = Ranked {
+                    path: found.metadata.get("path")?.clone(),
+                    start_line: line("startLine")?,
+                    end_line: line("endLine")?,
+                    score: -found.distance,
+                };
+                placed.push(found);
+                Some(ranked)
+            })
+            .collect();
+
+        Ok(fuse(&text, &vector, fusion)
+            .into_iter()
+            .take(k as usize)
+            .map(|span| HybridSearchHit {
+                path: span.path,
+                start_line: span.start_line,
+                end_line: span.end_line,
+                score: span.score,
+                text_score: span.text.map(|index| text[index].score),
+                vector_distance: span.vector.map(|index| placed[index].distance),
+                vector_id: span.vector.map(|index| placed[index].id.clone()),
+            })
+            .collect())
+    }
+
+    /// Regex matches in the indexed files, read from disk
+    ///
+    /// Only files holding the trigrams every match needs are read, so
+    /// patterns with a literal part of three or more characters skip most of
+    /// the workspace. Results are ordered by path and position
+    #[napi]
+    pub fn search_regex(&self, pattern: String, options: Option<RegexSearchOptions>) -> Result<Vec<RegexSearchHit>> {
+        let options = options.unwrap_or_default();
+        let case_insensitive = options.case_insensitive.unwrap_or(false);
+        let max_results = options.max_results.unwrap_or(1000) as usize;
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| Error::from_reason(format!("Invalid pattern: {}", e)))?;
+        let query = regex_query(&pattern, case_insensitive)?;
+
+        let mut candidates: Vec<Arc<str>> = self.state.lock().unwrap().trigrams.candidates(&query).into_iter().collect();
+        candidates.sort();
+        let mut hits: Vec<RegexSearchHit> = candidates
+            .par_iter()
+            .flat_map_iter(|path| {
+                let code = fs::read_to_string(&**path).unwrap_or_default();
+                let mut line = 0;
+                let mut counted = 0;
+                let mut hits = Vec::new();
+                for found in regex.find_iter(&code).take(max_results) {
+                    line += memchr::memchr_iter(b'\n', &code.as_bytes()[counted..found.start()]).count();
+                    counted = found.start();
+                    let line_start = code[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+                    let line_end = code[found.start()..].find('\n').map_or(code.len(), |i| found.start() + i);
+                    hits.push(RegexSearchHit {
+                        path: path.to_string(),
+                        line: line as u32,
+                        column: (found.start() - line_start) as u32,
+                        line_text: code[line_start..line_end].trim_end_matches('\r').to_string(),
+                    });
+                }
+                hits
+            })
+            .collect();
+        hits.truncate(max_results);
+        Ok(hits)
+    }
+
+    /// Watch `root` and re-index files as they change on disk
+    ///
+    /// Changes are debounced and only files whose content changed are
+    /// re-indexed; each batch that changed the index is passed to
+    /// `onChange`. Replaces any earlier watch
+    #[napi(ts_args_type = "root: string, onChange: (changes: Array<IndexChange>) => void, options?: WatchOptions | undefined | null")]
+    pub fn watch(
+        &mut self,
+        root: String,
+        on_change: ThreadsafeFunction<Vec<IndexChange>, ErrorStrategy::Fatal>,
+        options: Option<WatchOptions>,
+    ) -> Result<()> {
+        let options = options.unwrap_or_default();
+        self.watcher = None;
+        let state = self.state.clone();
+        let watcher = watch_workspace(
+            Path::new(&root),
+            Duration::from_millis(options.debounce_ms.unwrap_or(200) as u64),
+            options.respect_gitignore.unwrap_or(true),
+            move |changes| {
+                let applied = state.lock().unwrap().apply(changes);
+                if !applied.is_empty() {
+                    on_change.call(applied, ThreadsafeFunctionCallMode::NonBlocking);
+                }
+            },
+        )?;
+        self.watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Stop watching, returning whether a watch was running
+    #[napi]
+    pub fn unwatch(&mut self) -> bool {
+        self.watcher.take().is_some()
+    }
+}
+
+/// Size and modification time (ms since the epoch) of a file on disk
+fn disk_stamp(path: &Path) -> Option<(u64, Option<f64>)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs_f64() * 1000.0);
+    Some((metadata.len(), modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_to_missing_files_are_removals() {
+        let mut state = IndexState::default();
+        state.update("old.ts".to_string(), "export const a = 1;\n", "typescript".to_string());
+        let changes = state.apply(vec![FileChange::Renamed(PathBuf::from("old.ts"), PathBuf::from("missing/new.ts"))]);
+        let changes: Vec<(&str, &str)> = changes.iter().map(|c| (c.kind.as_str(), c.path.as_str())).collect();
+        assert_eq!(changes, [("removed", "old.ts")]);
+        assert!(state.files.is_empty());
+    }
+}
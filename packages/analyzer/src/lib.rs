@@ -5,14 +5,74 @@ use napi_derive::napi;
 mod ast_parser;
 mod semantic_analyzer;
 mod text_processor;
+mod line_index;
 mod hash;
+mod file_io;
 mod duplication;
+mod framework_detector;
+mod test_extractor;
+mod string_extractor;
+mod comment_extractor;
+mod env_extractor;
+mod route_extractor;
+mod import_graph;
+mod chunker;
+mod compressor;
+mod type_stripper;
+mod naming;
+mod streaming_tokenizer;
+mod redaction;
+mod llm_response;
+mod diff;
+mod similarity;
+mod fuzzy;
+mod bloom_filter;
+mod hyperloglog;
+mod lsh_index;
+mod duplication_index;
+mod indexer;
+mod walker;
+mod watcher;
+mod text_index;
+mod trigram_index;
+mod grep;
+mod quantization;
+mod fusion;
+mod chunk_store;
+mod vector_store;
 
 pub use ast_parser::*;
 pub use semantic_analyzer::*;
 pub use text_processor::*;
+pub use line_index::*;
 pub use hash::*;
 pub use duplication::*;
+pub use framework_detector::*;
+pub use test_extractor::*;
+pub use string_extractor::*;
+pub use comment_extractor::*;
+pub use env_extractor::*;
+pub use route_extractor::*;
+pub use import_graph::*;
+pub use chunker::*;
+pub use compressor::*;
+pub use type_stripper::*;
+pub use naming::*;
+pub use streaming_tokenizer::*;
+pub use redaction::*;
+pub use llm_response::*;
+pub use diff::*;
+pub use similarity::*;
+pub use fuzzy::*;
+pub use bloom_filter::*;
+pub use hyperloglog::*;
+pub use lsh_index::*;
+pub use duplication_index::*;
+pub use indexer::*;
+pub use walker::*;
+pub use grep::*;
+pub use vector_store::*;
+pub use chunk_store::*;
 
 /// Initialize the native module
 #[napi]
@@ -0,0 +1,210 @@
+use memchr::memmem;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+
+use crate::semantic_analyzer::LineIndex;
+
+/// Zero-based line and column
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// Byte range to search in, for `LineIndex.findSubstring`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchRange {
+    /// First byte searched (default: 0)
+    #[napi(js_name = "startByte")]
+    pub start_byte: Option<u32>,
+    /// Byte after the last one searched (default: end of the document)
+    #[napi(js_name = "endByte")]
+    pub end_byte: Option<u32>,
+}
+
+/// Where a substring was found
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstringPosition {
+    pub offset: u32,
+    pub line: u32,
+    /// Column in bytes
+    pub column: u32,
+    /// Column in UTF-16 code units
+    #[napi(js_name = "utf16Column")]
+    pub utf16_column: u32,
+}
+
+/// Line map of a document for converting between offsets and positions
+///
+/// Build once per document version and reuse it for every result set. Plain
+/// methods work in UTF-8 bytes, matching the offsets the analyzers return;
+/// the `Utf16` variants match VS Code's `offsetAt` and `positionAt`.
+/// Out-of-range input is clamped to the document.
+#[napi(js_name = "LineIndex")]
+pub struct DocumentLineIndex {
+    text: String,
+    lines: LineIndex,
+    /// UTF-16 offset of each line start
+    utf16_line_starts: Vec<u32>,
+}
+
+#[napi]
+impl DocumentLineIndex {
+    #[napi(constructor)]
+    pub fn new(text: String) -> Self {
+        let lines = LineIndex::new(&text);
+        let mut utf16_line_starts = Vec::with_capacity(lines.line_count());
+        let mut utf16_offset = 0u32;
+        utf16_line_starts.push(0);
+        for c in text.chars() {
+            utf16_offset += c.len_utf16() as u32;
+            if c == '\n' {
+                utf16_line_starts.push(utf16_offset);
+            }
+        }
+
+        Self { text, lines, utf16_line_starts }
+    }
+
+    #[napi(getter)]
+    pub fn line_count(&self) -> u32 {
+        self.lines.line_count() as u32
+    }
+
+    /// Position of a byte offset, with the column in bytes
+    #[napi]
+    pub fn offset_to_position(&self, offset: u32) -> TextPosition {
+        let offset = self.clamp_offset(offset as usize);
+        let line = self.lines.get_line(offset);
+        TextPosition {
+            line,
+            character: (offset - self.line_start(line)) as u32,
+        }
+    }
+
+    /// Byte offset of a position with the column in bytes
+    #[napi]
+    pub fn position_to_offset(&self, line: u32, character: u32) -> u32 {
+        let (start, end) = self.line_bounds(line);
+        self.clamp_offset((start + character as usize).min(end)) as u32
+    }
+
+    /// Position of a byte offset, with the column in UTF-16 code units
+    #[napi]
+    pub fn offset_to_position_utf16(&self, offset: u32) -> TextPosition {
+        let offset = self.clamp_offset(offset as usize);
+        let line = self.lines.get_line(offset);
+        TextPosition {
+            line,
+            character: self.text[self.line_start(line)..offset].encode_utf16().count() as u32,
+        }
+    }
+
+    /// Byte offset of a position with the column in UTF-16 code units
+    #[napi]
+    pub fn position_utf16_to_offset(&self, line: u32, character: u32) -> u32 {
+        let (start, end) = self.line_bounds(line);
+        let mut units = 0;
+        for (i, c) in self.text[start..end].char_indices() {
+            if units >= character {
+                return (start + i) as u32;
+            }
+            units += c.len_utf16() as u32;
+        }
+        end as u32
+    }
+
+    /// Convert a byte offset into a UTF-16 document offset
+    #[napi]
+    pub fn to_utf16_offset(&self, offset: u32) -> u32 {
+        let position = self.offset_to_position_utf16(offset);
+        self.utf16_line_starts[position.line as usize] + position.character
+    }
+
+    /// Convert a UTF-16 document offset into a byte offset
+    #[napi]
+    pub fn from_utf16_offset(&self, utf16_offset: u32) -> u32 {
+        let line = match self.utf16_line_starts.binary_search(&utf16_offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        self.position_utf16_to_offset(line as u32, utf16_offset - self.utf16_line_starts[line])
+    }
+
+    /// Convert many byte offsets at once, with UTF-16 columns if `utf16` is set
+    #[napi]
+    pub fn offsets_to_positions(&self, offsets: Vec<u32>, utf16: Option<bool>) -> Vec<TextPosition> {
+        let utf16 = utf16.unwrap_or(false);
+        offsets
+            .into_iter()
+            .map(|offset| {
+                if utf16 {
+                    self.offset_to_position_utf16(offset)
+                } else {
+                    self.offset_to_position(offset)
+                }
+            })
+            .collect()
+    }
+
+    /// First occurrence of `needle`, like `findSubstring`, with its position
+    #[napi]
+    pub fn find_substring(&self, needle: String, range: Option<SearchRange>) -> Option<SubstringPosition> {
+        let (start, end) = self.search_bounds(range);
+        memmem::find(&self.text.as_bytes()[start..end], needle.as_bytes()).map(|pos| self.substring_position(start + pos))
+    }
+
+    /// Every occurrence of `needle`, like `findAllOccurrences`, with positions
+    #[napi]
+    pub fn find_all_occurrences(&self, needle: String, range: Option<SearchRange>) -> Vec<SubstringPosition> {
+        let (start, end) = self.search_bounds(range);
+        memmem::find_iter(&self.text.as_bytes()[start..end], needle.as_bytes())
+            .map(|pos| self.substring_position(start + pos))
+            .collect()
+    }
+}
+
+impl DocumentLineIndex {
+    fn line_start(&self, line: u32) -> usize {
+        self.lines.line_start(line as usize).unwrap_or(self.text.len())
+    }
+
+    /// Byte range of a line without its line break
+    fn line_bounds(&self, line: u32) -> (usize, usize) {
+        let start = self.line_start(line);
+        let next = self.lines.line_start(line as usize + 1).unwrap_or(self.text.len());
+        let content = self.text[start..next].trim_end_matches(['\n', '\r']);
+        (start, start + content.len())
+    }
+
+    /// Byte range of the document selected by `range`
+    fn search_bounds(&self, range: Option<SearchRange>) -> (usize, usize) {
+        let range = range.unwrap_or_default();
+        let start = self.clamp_offset(range.start_byte.unwrap_or(0) as usize);
+        let end = self.clamp_offset(range.end_byte.map_or(self.text.len(), |end| end as usize));
+        (start, end.max(start))
+    }
+
+    fn substring_position(&self, offset: usize) -> SubstringPosition {
+        let line = self.lines.get_line(offset);
+        let line_start = self.line_start(line);
+        SubstringPosition {
+            offset: offset as u32,
+            line,
+            column: (offset - line_start) as u32,
+            utf16_column: self.text[line_start..offset].encode_utf16().count() as u32,
+        }
+    }
+
+    /// Clamp to the document and back onto a character boundary
+    fn clamp_offset(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
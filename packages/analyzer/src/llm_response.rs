@@ -0,0 +1,175 @@
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+
+/// Code pulled out of a model response
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedCode {
+    pub code: String,
+    /// Language on the opening fence, as written (`ts`, `python`, ...)
+    pub language: Option<String>,
+    /// The code came from a fenced block rather than the whole response
+    pub fenced: bool,
+    /// Number of fenced blocks in the response
+    #[napi(js_name = "blockCount")]
+    pub block_count: u32,
+}
+
+/// Extract the best code block from a markdown model response
+///
+/// Prefers blocks tagged with `expectedLanguage` (aliases such as `ts` or `py`
+/// count), then untagged blocks, then other code, and the longest block among
+/// equals; shell sessions and output are picked last. A fence left open by a
+/// truncated response runs to the end. Without fences, leading and trailing
+/// prose lines such as "Here is the updated code:" are dropped.
+#[napi]
+pub fn extract_llm_code(markdown: String, expected_language: Option<String>) -> ExtractedCode {
+    let blocks = fenced_blocks(&markdown);
+    let expected = expected_language.as_deref().map(canonical_language);
+
+    // `typescript` also accepts `tsx` blocks and the other way round
+    let is_expected = |language: &str| {
+        expected.as_deref().is_some_and(|e| e.trim_end_matches("react") == language.trim_end_matches("react"))
+    };
+    let rank = |block: &CodeBlock| match block.language.as_deref().map(canonical_language) {
+        Some(language) if is_expected(&language) => 3,
+        None => 2,
+        Some(language) if is_output_language(&language) => 0,
+        Some(_) if expected.is_none() => 2,
+        Some(_) => 1,
+    };
+    // `rev` makes ties go to the earlier block
+    let best = blocks.iter().rev().max_by_key(|block| (rank(block), block.code.trim().len()));
+
+    match best {
+        Some(block) => ExtractedCode {
+            code: block.code.clone(),
+            language: block.language.clone(),
+            fenced: true,
+            block_count: blocks.len() as u32,
+        },
+        None => ExtractedCode {
+            code: strip_prose(&markdown),
+            language: None,
+            fenced: false,
+            block_count: 0,
+        },
+    }
+}
+
+struct CodeBlock {
+    language: Option<String>,
+    code: String,
+}
+
+/// Fenced code blocks, following CommonMark fence rules
+fn fenced_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    // Fence character, fence length, indentation and the block so far
+    let mut open: Option<(char, usize, usize, CodeBlock)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        let fence_len = fence_char.map_or(0, |c| trimmed.chars().take_while(|x| *x == c).count());
+
+        match &mut open {
+            Some((c, len, _, _)) if indent <= 3 && fence_char == Some(*c) && fence_len >= *len
+                && trimmed[fence_len..].trim().is_empty() =>
+            {
+                let (_, _, _, block) = open.take().unwrap();
+                blocks.push(block);
+            }
+            Some((_, _, fence_indent, block)) => {
+                let strip = indent.min(*fence_indent);
+                block.code.push_str(&line[strip..]);
+                block.code.push('\n');
+            }
+            None => {
+                let info = trimmed[fence_len..].trim();
+                if indent <= 3 && fence_len >= 3 && !(fence_char == Some('`') && info.contains('`')) {
+                    let language = info
+                        .split(|c: char| c.is_whitespace() || c == ':' || c == ',')
+                        .next()
+                        .map(|l| l.trim_start_matches(['{', '.']).trim_end_matches('}'))
+                        .filter(|l| !l.is_empty())
+                        .map(str::to_string);
+                    let block = CodeBlock { language, code: String::new() };
+                    open = Some((fence_char.unwrap(), fence_len, indent, block));
+                }
+            }
+        }
+    }
+
+    // An unclosed fence means the response was cut off
+    if let Some((_, _, _, block)) = open {
+        blocks.push(block);
+    }
+    for block in &mut blocks {
+        let len = block.code.trim_end().len();
+        block.code.truncate(len);
+    }
+    blocks
+}
+
+/// Language id for a fence tag, file name or extension
+fn canonical_language(tag: &str) -> String {
+    let tag = tag.rsplit(['.', '/']).next().unwrap_or(tag).to_ascii_lowercase();
+    let language = match tag.as_str() {
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" | "node" => "javascript",
+        "jsx" => "javascriptreact",
+        "py" | "py3" | "python3" => "python",
+        "rs" => "rust",
+        "golang" => "go",
+        "c++" | "cc" | "cxx" | "hpp" | "hh" => "cpp",
+        "h" => "c",
+        "cs" | "c#" => "csharp",
+        "rb" => "ruby",
+        "kt" | "kts" => "kotlin",
+        "sh" | "bash" | "zsh" | "shell" => "shellscript",
+        "yml" => "yaml",
+        "md" => "markdown",
+        _ => return tag,
+    };
+    language.to_string()
+}
+
+/// Tags for terminal sessions and program output rather than source
+fn is_output_language(language: &str) -> bool {
+    matches!(
+        language,
+        "shellscript" | "console" | "terminal" | "text" | "txt" | "plaintext" | "output" | "log"
+    )
+}
+
+/// Drop prose lines before and after unfenced code
+fn strip_prose(text: &str) -> String {
+    let lines: Vec<&str> = text.trim().lines().collect();
+    let first = lines.iter().position(|line| !is_prose(line)).unwrap_or(lines.len());
+    let last = lines.iter().rposition(|line| !is_prose(line)).map_or(first, |i| i + 1);
+    let code = lines[first..last.max(first)].join("\n");
+
+    // A lone inline code span, `like this`
+    let trimmed = code.trim();
+    match trimmed.strip_prefix('`').and_then(|c| c.strip_suffix('`')) {
+        Some(inner) if !trimmed.contains('\n') && !inner.contains('`') => inner.to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// A sentence such as "Here is the fixed function:" rather than code
+fn is_prose(line: &str) -> bool {
+    let line = line.trim();
+    if line.is_empty() {
+        return true;
+    }
+    let words = line.split_whitespace().count();
+    let starts_capitalized = line.starts_with(|c: char| c.is_uppercase());
+    let ends_sentence = line.ends_with(['.', ':', '!', '?']);
+    let has_code_chars = line.contains([';', '{', '}', '(', ')', '=', '<', '>', '[', ']'])
+        || line.starts_with(['#', '/', '*', '@', '-']);
+    starts_capitalized && ends_sentence && words >= 3 && !has_code_chars
+}
@@ -0,0 +1,147 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::hash::minhash_signature;
+
+/// Options for `LshIndex`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LshOptions {
+    /// Number of bands (default: 16)
+    pub bands: Option<u32>,
+    /// MinHash values per band (default: 8)
+    pub rows: Option<u32>,
+}
+
+/// A stored text near a query
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LshMatch {
+    pub id: String,
+    /// Estimated Jaccard similarity, as from `minhash_similarity`
+    pub similarity: f64,
+}
+
+/// Index for finding near-duplicate texts without scanning every one
+/// 
+/// Texts are stored as MinHash signatures split into bands; two texts
+/// become candidates when any band matches exactly. With `b` bands of `r`
+/// rows a pair of similarity `s` is found with probability
+/// `1 - (1 - s^r)^b`, which rises steeply around `(1/b)^(1/r)`, 0.71 by
+/// default. Use more bands to catch less similar pairs
+#[napi]
+pub struct LshIndex {
+    rows: u32,
+    /// MinHash values per signature, `bands * rows`
+    hashes: u32,
+    signatures: HashMap<String, Vec<u32>>,
+    /// Per band, ids by band hash
+    buckets: Vec<HashMap<u64, HashSet<String>>>,
+}
+
+#[napi]
+impl LshIndex {
+    #[napi(constructor)]
+    pub fn new(options: Option<LshOptions>) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        let bands = options.bands.unwrap_or(16);
+        let rows = options.rows.unwrap_or(8);
+        if bands == 0 || rows == 0 {
+            return Err(Error::from_reason("Bands and rows must be at least 1"));
+        }
+        let hashes = bands.checked_mul(rows).ok_or_else(|| {
+            Error::new(Status::InvalidArg, format!("{} bands of {} rows exceed 2^32 MinHash values", bands, rows))
+        })?;
+        Ok(Self {
+            rows,
+            hashes,
+            signatures: HashMap::new(),
+            buckets: vec![HashMap::new(); bands as usize],
+        })
+    }
+
+    /// Add a text, replacing any text already stored under `id`
+    #[napi]
+    pub fn insert(&mut self, id: String, text: String) {
+        self.remove(id.clone());
+        let signature = minhash_signature(text, self.hashes);
+        for (band, key) in self.band_keys(&signature).enumerate() {
+            self.buckets[band].entry(key).or_default().insert(id.clone());
+        }
+        self.signatures.insert(id, signature);
+    }
+
+    /// Remove a text, returning whether it was stored
+    #[napi]
+    pub fn remove(&mut self, id: String) -> bool {
+        let Some(signature) = self.signatures.remove(&id) else {
+            return false;
+        };
+        let keys: Vec<u64> = self.band_keys(&signature).collect();
+        for (band, key) in keys.into_iter().enumerate() {
+            if let Some(ids) = self.buckets[band].get_mut(&key) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.buckets[band].remove(&key);
+                }
+            }
+        }
+        true
+    }
+
+    /// Stored texts with an estimated similarity of at least `threshold`,
+    /// most similar first
+    #[napi]
+    pub fn query_near(&self, text: String, threshold: f64) -> Vec<LshMatch> {
+        let signature = minhash_signature(text, self.hashes);
+        let mut candidates = HashSet::new();
+        for (band, key) in self.band_keys(&signature).enumerate() {
+            if let Some(ids) = self.buckets[band].get(&key) {
+                candidates.extend(ids);
+            }
+        }
+
+        let mut matches: Vec<LshMatch> = candidates
+            .into_iter()
+            .filter_map(|id| {
+                let stored = &self.signatures[id];
+                let equal = stored.iter().zip(&signature).filter(|(a, b)| a == b).count();
+                let similarity = equal as f64 / signature.len() as f64;
+                (similarity >= threshold).then(|| LshMatch { id: id.clone(), similarity })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| a.id.cmp(&b.id)));
+        matches
+    }
+
+    /// Number of stored texts
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.signatures.len() as u32
+    }
+
+    fn band_keys<'s>(&self, signature: &'s [u32]) -> impl Iterator<Item = u64> + 's {
+        signature.chunks(self.rows as usize).map(|band| {
+            let mut hasher = Xxh3::new();
+            for value in band {
+                hasher.update(&value.to_le_bytes());
+            }
+            hasher.digest()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflowing_signature_sizes_are_rejected() {
+        let options = LshOptions { bands: Some(u32::MAX), rows: Some(2) };
+        let error = LshIndex::new(Some(options)).err().unwrap();
+        assert_eq!(error.status, Status::InvalidArg);
+    }
+}
@@ -0,0 +1,214 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::semantic_analyzer::{is_declaration_name, is_identifier_kind, node_text, visit_named};
+
+/// Naming convention used by one kind of symbol
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamingConvention {
+    pub kind: String, // 'function' | 'method' | 'parameter' | 'variable' | 'constant' | 'type' | 'property'
+    /// Most common case among unambiguous names, if any
+    pub dominant: Option<String>,
+    /// Share of unambiguous names that use the dominant case
+    pub confidence: f64,
+    #[napi(js_name = "camelCase")]
+    pub camel_case: u32,
+    #[napi(js_name = "snakeCase")]
+    pub snake_case: u32,
+    #[napi(js_name = "pascalCase")]
+    pub pascal_case: u32,
+    #[napi(js_name = "screamingSnakeCase")]
+    pub screaming_snake_case: u32,
+    /// Mixed styles such as `get_HTTP_value`
+    pub other: u32,
+    /// Single lowercase words, which fit both camelCase and snake_case
+    pub ambiguous: u32,
+}
+
+/// Classify declared names by case and find the dominant convention per symbol kind
+///
+/// Each distinct name is counted once per kind. Cases are 'camelCase' |
+/// 'snake_case' | 'PascalCase' | 'SCREAMING_SNAKE_CASE'.
+#[napi]
+pub fn analyze_naming(code: String, language_id: String) -> Result<Vec<NamingConvention>> {
+    let Ok(tree) = parse_tree(&code, &language_id) else {
+        return Ok(Vec::new());
+    };
+
+    let mut seen = HashSet::new();
+    let mut conventions: BTreeMap<&'static str, NamingConvention> = BTreeMap::new();
+    visit_named(tree.root_node(), &mut |node| {
+        if node.child_count() != 0 || !is_identifier_kind(node.kind()) {
+            return;
+        }
+        let Some(kind) = symbol_kind(node) else { return };
+        let name = node_text(node, &code);
+        if !seen.insert((kind, name)) {
+            return;
+        }
+
+        let convention = conventions.entry(kind).or_insert_with(|| NamingConvention {
+            kind: kind.to_string(),
+            ..Default::default()
+        });
+        match classify_case(name) {
+            Some("camelCase") => convention.camel_case += 1,
+            Some("snake_case") => convention.snake_case += 1,
+            Some("PascalCase") => convention.pascal_case += 1,
+            Some("SCREAMING_SNAKE_CASE") => convention.screaming_snake_case += 1,
+            Some(_) => convention.other += 1,
+            None => convention.ambiguous += 1,
+        }
+    });
+
+    Ok(conventions
+        .into_values()
+        .map(|mut convention| {
+            let counts = [
+                ("camelCase", convention.camel_case),
+                ("snake_case", convention.snake_case),
+                ("PascalCase", convention.pascal_case),
+                ("SCREAMING_SNAKE_CASE", convention.screaming_snake_case),
+            ];
+            let total: u32 = counts.iter().map(|(_, count)| count).sum::<u32>() + convention.other;
+            // `rev` makes ties go to the earlier case in the list
+            let dominant = counts.iter().rev().filter(|(_, count)| *count > 0).max_by_key(|(_, count)| *count);
+            if let Some((case, count)) = dominant {
+                convention.dominant = Some(case.to_string());
+                convention.confidence = *count as f64 / total as f64;
+            }
+            convention
+        })
+        .collect())
+}
+
+/// Kind of symbol a declaration name belongs to
+fn symbol_kind(node: Node) -> Option<&'static str> {
+    let parent = node.parent()?;
+    let kind = parent.kind();
+    let declares = is_declaration_name(node)
+        || (matches!(kind, "let_declaration" | "short_var_declaration" | "expression_list")
+            && parent.child_by_field_name("pattern").or(parent.child_by_field_name("left")) == Some(node));
+    if !declares {
+        return None;
+    }
+
+    let symbol = if kind.contains("parameter") {
+        "parameter"
+    } else if matches!(kind, "const_item" | "static_item" | "const_spec" | "const_declaration" | "const_element") {
+        "constant"
+    } else if ["class", "struct", "interface", "enum", "trait", "type", "record", "union"]
+        .iter()
+        .any(|k| kind.contains(k))
+    {
+        "type"
+    } else if kind.contains("method") || kind.contains("constructor") {
+        "method"
+    } else if kind.contains("function") {
+        "function"
+    } else if kind.contains("field") || kind.contains("property") {
+        "property"
+    } else if kind.contains("variable") || kind.contains("declarator") || kind.contains("assignment")
+        || matches!(kind, "let_declaration" | "short_var_declaration" | "var_spec")
+    {
+        "variable"
+    } else {
+        return None;
+    };
+    Some(symbol)
+}
+
+/// Case of an identifier, ignoring leading and trailing `_` and `$`, or `None`
+/// for single lowercase words that fit more than one convention
+fn classify_case(name: &str) -> Option<&'static str> {
+    let core = name.trim_matches(['_', '$']);
+    let has_underscore = core.contains('_');
+    let has_upper = core.chars().any(char::is_uppercase);
+    let has_lower = core.chars().any(char::is_lowercase);
+    let starts_upper = core.starts_with(char::is_uppercase);
+
+    match (has_underscore, has_upper, has_lower) {
+        (_, false, false) => None,
+        (_, true, false) if core.chars().filter(|c| c.is_alphabetic()).count() > 1 => Some("SCREAMING_SNAKE_CASE"),
+        (_, true, false) => Some("PascalCase"),
+        (true, false, true) => Some("snake_case"),
+        (false, false, true) => None,
+        (false, true, true) if starts_upper => Some("PascalCase"),
+        (false, true, true) => Some("camelCase"),
+        (true, true, true) => Some("mixed"),
+    }
+}
+
+/// Convert names to another case
+///
+/// Words are split at `_`, `-`, spaces and case changes, so `HTTPServer`
+/// becomes `http_server`. Leading underscores and `$` are kept. Target is
+/// 'camelCase' | 'snake_case' | 'PascalCase' | 'SCREAMING_SNAKE_CASE' | 'kebab-case'.
+#[napi]
+pub fn convert_case(names: Vec<String>, target: String) -> Result<Vec<String>> {
+    if !matches!(target.as_str(), "camelCase" | "snake_case" | "PascalCase" | "SCREAMING_SNAKE_CASE" | "kebab-case") {
+        return Err(Error::from_reason(format!("Unknown case: {}", target)));
+    }
+
+    Ok(names
+        .iter()
+        .map(|name| {
+            let core = name.trim_start_matches(['_', '$']);
+            let prefix = &name[..name.len() - core.len()];
+            let words = split_words(core);
+            let converted = match target.as_str() {
+                "snake_case" => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("_"),
+                "SCREAMING_SNAKE_CASE" => words.iter().map(|w| w.to_uppercase()).collect::<Vec<_>>().join("_"),
+                "kebab-case" => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("-"),
+                _ => words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| if i == 0 && target == "camelCase" { w.to_lowercase() } else { capitalize(w) })
+                    .collect(),
+            };
+            format!("{}{}", prefix, converted)
+        })
+        .collect())
+}
+
+pub(crate) fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if matches!(c, '_' | '-' | ' ') {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        // `fooBar` splits before `B`; `HTTPServer` splits before `S`
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_lowercase() || p.is_ascii_digit() || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
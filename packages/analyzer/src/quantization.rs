@@ -0,0 +1,157 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Centroids per subvector, so each code fits a byte
+pub(crate) const CENTROIDS: usize = 256;
+
+/// Lloyd iterations when training a codebook
+const KMEANS_ITERATIONS: usize = 12;
+
+pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
+    lanes(a, b, |x, y| x * y)
+}
+
+pub(crate) fn squared_distance(a: &[f32], b: &[f32]) -> f32 {
+    lanes(a, b, |x, y| (x - y) * (x - y))
+}
+
+/// Sum of `term` over paired components, kept in eight independent
+/// accumulators so the compiler can vectorize it
+#[inline(always)]
+pub(crate) fn lanes<T: Copy>(a: &[f32], b: &[T], term: impl Fn(f32, T) -> f32) -> f32 {
+    let mut sums = [0.0f32; 8];
+    for (x, y) in a.chunks_exact(8).zip(b.chunks_exact(8)) {
+        for i in 0..8 {
+            sums[i] += term(x[i], y[i]);
+        }
+    }
+    let tail = a.len() - a.len() % 8;
+    let rest: f32 = a[tail..].iter().zip(&b[tail..]).map(|(x, y)| term(*x, *y)).sum();
+    sums.iter().sum::<f32>() + rest
+}
+
+/// Quantize to int8 with one scale for the whole vector, returning the scale
+pub(crate) fn int8_encode(vector: &[f32], codes: &mut Vec<i8>) -> f32 {
+    let max = vector.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+    let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+    codes.extend(vector.iter().map(|x| (x / scale).round() as i8));
+    scale
+}
+
+pub(crate) fn int8_decode(codes: &[i8], scale: f32) -> Vec<f32> {
+    codes.iter().map(|&code| code as f32 * scale).collect()
+}
+
+/// Codes a vector as the nearest of `CENTROIDS` learned centroids for each
+/// of its subvectors, one byte per subvector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ProductQuantizer {
+    /// Start of each subvector, then the dimensions
+    bounds: Vec<usize>,
+    /// Per subvector, its centroids one after another
+    centroids: Vec<Vec<f32>>,
+}
+
+impl ProductQuantizer {
+    /// Learn codebooks with k-means from a flat sample of vectors
+    pub fn train(sample: &[f32], dimensions: usize, subvectors: usize) -> Self {
+        let bounds: Vec<usize> = (0..=subvectors).map(|i| i * dimensions / subvectors).collect();
+        let count = sample.len() / dimensions;
+        let centroids = bounds
+            .windows(2)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|range| {
+                let points: Vec<&[f32]> = (0..count)
+                    .map(|i| &sample[i * dimensions + range[0]..i * dimensions + range[1]])
+                    .collect();
+                kmeans(&points, range[1] - range[0])
+            })
+            .collect();
+        Self { bounds, centroids }
+    }
+
+    pub fn subvectors(&self) -> usize {
+        self.centroids.len()
+    }
+
+    pub fn encode(&self, vector: &[f32]) -> Vec<u8> {
+        self.bounds
+            .windows(2)
+            .zip(&self.centroids)
+            .map(|(range, centroids)| nearest(&vector[range[0]..range[1]], centroids) as u8)
+            .collect()
+    }
+
+    pub fn decode(&self, codes: &[u8]) -> Vec<f32> {
+        let mut vector = Vec::with_capacity(self.bounds[self.bounds.len() - 1]);
+        for ((range, centroids), &code) in self.bounds.windows(2).zip(&self.centroids).zip(codes) {
+            let width = range[1] - range[0];
+            vector.extend_from_slice(&centroids[code as usize * width..(code as usize + 1) * width]);
+        }
+        vector
+    }
+
+    /// Per subvector, the dot product of the query's part with every
+    /// centroid, or with `squared` their squared distance
+    pub fn table(&self, query: &[f32], squared: bool) -> Vec<f32> {
+        let mut table = Vec::with_capacity(self.subvectors() * CENTROIDS);
+        for (range, centroids) in self.bounds.windows(2).zip(&self.centroids) {
+            let part = &query[range[0]..range[1]];
+            for centroid in centroids.chunks_exact(range[1] - range[0]) {
+                table.push(if squared { squared_distance(part, centroid) } else { dot(part, centroid) });
+            }
+        }
+        table
+    }
+
+    /// The dot product or squared distance a `table` gives for coded vector
+    pub fn lookup(&self, table: &[f32], codes: &[u8]) -> f32 {
+        codes
+            .iter()
+            .enumerate()
+            .map(|(i, &code)| table[i * CENTROIDS + code as usize])
+            .sum()
+    }
+}
+
+/// Index of the centroid nearest a point
+fn nearest(point: &[f32], centroids: &[f32]) -> usize {
+    centroids
+        .chunks_exact(point.len().max(1))
+        .map(|centroid| squared_distance(point, centroid))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(i, _)| i)
+}
+
+/// `CENTROIDS` centroids for points of `width` components
+///
+/// Seeded with evenly spaced points so training is deterministic; with
+/// fewer points than centroids some start out, and stay, duplicated
+fn kmeans(points: &[&[f32]], width: usize) -> Vec<f32> {
+    let mut centroids: Vec<f32> = (0..CENTROIDS)
+        .flat_map(|i| points[i * points.len() / CENTROIDS].iter().copied())
+        .collect();
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![0.0f32; CENTROIDS * width];
+        let mut counts = vec![0u32; CENTROIDS];
+        for point in points {
+            let cluster = nearest(point, &centroids);
+            counts[cluster] += 1;
+            for (sum, x) in sums[cluster * width..(cluster + 1) * width].iter_mut().zip(*point) {
+                *sum += x;
+            }
+        }
+        // An empty cluster keeps its centroid
+        for (cluster, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            for (centroid, sum) in centroids[cluster * width..(cluster + 1) * width]
+                .iter_mut()
+                .zip(&sums[cluster * width..(cluster + 1) * width])
+            {
+                *centroid = sum / count as f32;
+            }
+        }
+    }
+    centroids
+}
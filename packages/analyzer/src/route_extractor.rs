@@ -0,0 +1,378 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::semantic_analyzer::{field_text, node_line, node_text};
+use crate::string_extractor::string_value;
+
+/// HTTP route information
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteInfo {
+    /// Upper-case HTTP method, or `ALL` when the route accepts any method
+    pub method: String,
+    /// Path including any controller prefix
+    pub path: String,
+    /// Handler function name; `None` for inline handlers
+    pub handler: Option<String>,
+    pub framework: String, // 'express' | 'flask' | 'fastapi' | 'nestjs' | 'spring'
+    #[napi(js_name = "lineNumber")]
+    pub line_number: u32,
+}
+
+/// Extract HTTP routes declared in a file
+///
+/// Recognizes Express-style `app.get('/x', handler)` and `app.route('/x').get(..)`
+/// calls, Flask and FastAPI decorators, NestJS controller decorators and
+/// Spring `@RequestMapping` / `@GetMapping` annotations.
+#[napi]
+pub fn extract_routes(code: String, language_id: String) -> Result<Vec<RouteInfo>> {
+    let mut routes = Vec::new();
+    match language_id.as_str() {
+        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" | "python" | "java" => {}
+        _ => return Ok(routes),
+    }
+
+    let tree = parse_tree(&code, &language_id)?;
+    let root = tree.root_node();
+    match language_id.as_str() {
+        "python" => extract_python_routes(root, &code, &mut routes),
+        "java" => extract_spring_routes(root, &code, &mut routes),
+        _ => extract_js_routes(root, &code, &mut routes),
+    }
+
+    routes.sort_by_key(|r| r.line_number);
+    Ok(routes)
+}
+
+const HTTP_METHODS: &[&str] = &["get", "post", "put", "delete", "patch", "options", "head", "all"];
+
+fn extract_js_routes(root: Node, code: &str, routes: &mut Vec<RouteInfo>) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        match node.kind() {
+            "call_expression" => {
+                if let Some(route) = express_route(node, code) {
+                    routes.push(route);
+                }
+            }
+            "class_declaration" => nest_routes(node, code, routes),
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+}
+
+/// `app.get('/x', ..., handler)` or `app.route('/x').get(handler)`
+fn express_route(call: Node, code: &str) -> Option<RouteInfo> {
+    let callee = call.child_by_field_name("function").filter(|f| f.kind() == "member_expression")?;
+    let method = field_text(callee, "property", code)?;
+    if !HTTP_METHODS.contains(&method.as_str()) {
+        return None;
+    }
+    let arguments = call.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let args: Vec<Node> = arguments.named_children(&mut cursor).collect();
+
+    let path = match args.first().and_then(|a| string_value(*a, code)) {
+        // A lone string argument is more likely `map.get('key')` than a route
+        Some(path) if args.len() >= 2 => path,
+        Some(_) => return None,
+        None => chained_route_path(callee.child_by_field_name("object")?, code)?,
+    };
+    if !path.starts_with(['/', '*']) {
+        return None;
+    }
+
+    Some(RouteInfo {
+        method: method.to_uppercase(),
+        path,
+        handler: args.last().and_then(|h| js_handler_name(*h, code)),
+        framework: "express".to_string(),
+        line_number: node_line(call),
+    })
+}
+
+/// Path of `app.route('/x')` at the start of a `.get(..).post(..)` chain
+fn chained_route_path(mut object: Node, code: &str) -> Option<String> {
+    loop {
+        if object.kind() != "call_expression" {
+            return None;
+        }
+        let callee = object.child_by_field_name("function").filter(|f| f.kind() == "member_expression")?;
+        let property = field_text(callee, "property", code)?;
+        if property == "route" {
+            let path = object.child_by_field_name("arguments")?.named_child(0)?;
+            return string_value(path, code);
+        }
+        if !HTTP_METHODS.contains(&property.as_str()) {
+            return None;
+        }
+        object = callee.child_by_field_name("object")?;
+    }
+}
+
+fn js_handler_name(handler: Node, code: &str) -> Option<String> {
+    match handler.kind() {
+        "identifier" | "member_expression" => Some(node_text(handler, code).to_string()),
+        "function_expression" | "function" => field_text(handler, "name", code),
+        _ => None,
+    }
+}
+
+/// NestJS `@Controller('prefix')` classes with `@Get(':id')` methods
+fn nest_routes(class: Node, code: &str, routes: &mut Vec<RouteInfo>) {
+    // Class decorators sit on the export statement when the class is exported
+    let mut decorators = child_decorators(class);
+    if let Some(parent) = class.parent().filter(|p| p.kind() == "export_statement") {
+        decorators.extend(child_decorators(parent));
+    }
+    let Some(prefix) = decorators.iter().find_map(|d| {
+        let (name, args) = decorator_call(*d, code)?;
+        (name == "Controller").then(|| args.first().and_then(|a| string_value(*a, code)).unwrap_or_default())
+    }) else {
+        return;
+    };
+
+    let Some(body) = class.child_by_field_name("body") else { return };
+    let mut pending: Vec<Node> = Vec::new();
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        match member.kind() {
+            "decorator" => pending.push(member),
+            "method_definition" => {
+                for decorator in pending.drain(..) {
+                    let Some((name, args)) = decorator_call(decorator, code) else { continue };
+                    if !HTTP_METHODS.contains(&name.to_lowercase().as_str()) {
+                        continue;
+                    }
+                    let path = args.first().and_then(|a| string_value(*a, code)).unwrap_or_default();
+                    routes.push(RouteInfo {
+                        method: name.to_uppercase(),
+                        path: join_paths(&prefix, &path),
+                        handler: field_text(member, "name", code),
+                        framework: "nestjs".to_string(),
+                        line_number: node_line(decorator),
+                    });
+                }
+            }
+            _ => pending.clear(),
+        }
+    }
+}
+
+fn child_decorators(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let decorators = node.named_children(&mut cursor).filter(|c| c.kind() == "decorator").collect();
+    decorators
+}
+
+/// Name and arguments of a `@Name(...)` decorator
+fn decorator_call<'a>(decorator: Node<'a>, code: &str) -> Option<(String, Vec<Node<'a>>)> {
+    let call = decorator.named_child(0).filter(|c| c.kind() == "call_expression")?;
+    let name = node_text(call.child_by_field_name("function")?, code).to_string();
+    let arguments = call.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let args = arguments.named_children(&mut cursor).collect();
+    Some((name, args))
+}
+
+fn extract_python_routes(root: Node, code: &str, routes: &mut Vec<RouteInfo>) {
+    let uses_flask = code.contains("from flask") || code.contains("import flask");
+    let uses_fastapi = code.contains("from fastapi") || code.contains("import fastapi");
+
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        stack.extend(children);
+        if node.kind() != "decorated_definition" {
+            continue;
+        }
+        let Some(definition) = node.child_by_field_name("definition") else { continue };
+        let handler = field_text(definition, "name", code);
+
+        let mut decorator_cursor = node.walk();
+        for decorator in node.named_children(&mut decorator_cursor).filter(|d| d.kind() == "decorator") {
+            let Some(call) = decorator.named_child(0).filter(|c| c.kind() == "call") else { continue };
+            let Some(function) = call.child_by_field_name("function").filter(|f| f.kind() == "attribute") else {
+                continue;
+            };
+            let Some(attribute) = field_text(function, "attribute", code) else { continue };
+            let Some(arguments) = call.child_by_field_name("arguments") else { continue };
+            let Some(path) = arguments.named_child(0).and_then(|a| string_value(a, code)) else { continue };
+
+            let (methods, framework) = match attribute.as_str() {
+                "route" => (py_methods_argument(arguments, code).unwrap_or_else(|| vec!["GET".to_string()]), "flask"),
+                "api_route" => (py_methods_argument(arguments, code).unwrap_or_else(|| vec!["GET".to_string()]), "fastapi"),
+                method if HTTP_METHODS.contains(&method) && method != "all" => {
+                    let framework = if uses_flask && !uses_fastapi { "flask" } else { "fastapi" };
+                    (vec![method.to_uppercase()], framework)
+                }
+                _ => continue,
+            };
+
+            for method in methods {
+                routes.push(RouteInfo {
+                    method,
+                    path: path.clone(),
+                    handler: handler.clone(),
+                    framework: framework.to_string(),
+                    line_number: node_line(decorator),
+                });
+            }
+        }
+    }
+}
+
+/// Values of a `methods=[...]` keyword argument
+fn py_methods_argument(arguments: Node, code: &str) -> Option<Vec<String>> {
+    let mut cursor = arguments.walk();
+    let keyword = arguments
+        .named_children(&mut cursor)
+        .filter(|a| a.kind() == "keyword_argument")
+        .find(|a| field_text(*a, "name", code).is_some_and(|n| n == "methods"))?;
+    let list = keyword.child_by_field_name("value")?;
+    let mut list_cursor = list.walk();
+    let methods: Vec<String> = list
+        .named_children(&mut list_cursor)
+        .filter_map(|m| string_value(m, code))
+        .map(|m| m.to_uppercase())
+        .collect();
+    (!methods.is_empty()).then_some(methods)
+}
+
+fn extract_spring_routes(root: Node, code: &str, routes: &mut Vec<RouteInfo>) {
+    let mut stack = vec![(root, String::new())];
+    while let Some((node, prefix)) = stack.pop() {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "class_declaration" => {
+                    let class_prefix = java_annotations(child)
+                        .into_iter()
+                        .find(|a| annotation_name(*a, code) == "RequestMapping")
+                        .and_then(|a| annotation_paths(a, code).into_iter().next())
+                        .unwrap_or_default();
+                    if let Some(body) = child.child_by_field_name("body") {
+                        stack.push((body, join_paths(&prefix, &class_prefix)));
+                    }
+                }
+                "method_declaration" => {
+                    let handler = field_text(child, "name", code);
+                    for annotation in java_annotations(child) {
+                        let name = annotation_name(annotation, code);
+                        let methods = match name.as_str() {
+                            "RequestMapping" => spring_request_methods(annotation, code),
+                            _ => match name.strip_suffix("Mapping") {
+                                Some(method) if HTTP_METHODS.contains(&method.to_lowercase().as_str()) => {
+                                    vec![method.to_uppercase()]
+                                }
+                                _ => continue,
+                            },
+                        };
+                        let mut paths = annotation_paths(annotation, code);
+                        if paths.is_empty() {
+                            paths.push(String::new());
+                        }
+                        for path in &paths {
+                            for method in &methods {
+                                routes.push(RouteInfo {
+                                    method: method.clone(),
+                                    path: join_paths(&prefix, path),
+                                    handler: handler.clone(),
+                                    framework: "spring".to_string(),
+                                    line_number: node_line(annotation),
+                                });
+                            }
+                        }
+                    }
+                }
+                "class_body" | "program" => stack.push((child, prefix.clone())),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn java_annotations(declaration: Node) -> Vec<Node> {
+    let mut cursor = declaration.walk();
+    let Some(modifiers) = declaration.named_children(&mut cursor).find(|c| c.kind() == "modifiers") else {
+        return Vec::new();
+    };
+    let mut modifier_cursor = modifiers.walk();
+    let annotations = modifiers
+        .named_children(&mut modifier_cursor)
+        .filter(|m| matches!(m.kind(), "annotation" | "marker_annotation"))
+        .collect();
+    annotations
+}
+
+fn annotation_name(annotation: Node, code: &str) -> String {
+    let name = field_text(annotation, "name", code).unwrap_or_default();
+    name.rsplit('.').next().unwrap_or_default().to_string()
+}
+
+/// Paths from the positional value or a `value =` / `path =` element
+fn annotation_paths(annotation: Node, code: &str) -> Vec<String> {
+    let Some(arguments) = annotation.child_by_field_name("arguments") else {
+        return Vec::new();
+    };
+    let mut cursor = arguments.walk();
+    let mut paths = Vec::new();
+    for argument in arguments.named_children(&mut cursor) {
+        let value = match argument.kind() {
+            "element_value_pair" => match field_text(argument, "key", code).as_deref() {
+                Some("value" | "path") => argument.child_by_field_name("value"),
+                _ => None,
+            },
+            _ => Some(argument),
+        };
+        let Some(value) = value else { continue };
+        if value.kind() == "element_value_array_initializer" {
+            let mut value_cursor = value.walk();
+            paths.extend(value.named_children(&mut value_cursor).filter_map(|v| string_value(v, code)));
+        } else if let Some(path) = string_value(value, code) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Methods of a `@RequestMapping(method = ...)`, or `ALL` when unrestricted
+fn spring_request_methods(annotation: Node, code: &str) -> Vec<String> {
+    let mut methods = Vec::new();
+    if let Some(arguments) = annotation.child_by_field_name("arguments") {
+        let mut cursor = arguments.walk();
+        for argument in arguments.named_children(&mut cursor) {
+            if argument.kind() != "element_value_pair" || field_text(argument, "key", code).as_deref() != Some("method") {
+                continue;
+            }
+            let Some(value) = argument.child_by_field_name("value") else { continue };
+            let text = node_text(value, code);
+            methods.extend(
+                text.split(|c: char| !c.is_alphanumeric() && c != '.')
+                    .filter_map(|part| part.rsplit('.').next())
+                    .filter(|m| HTTP_METHODS.contains(&m.to_lowercase().as_str()) && *m == m.to_uppercase())
+                    .map(str::to_string),
+            );
+        }
+    }
+    if methods.is_empty() {
+        methods.push("ALL".to_string());
+    }
+    methods
+}
+
+/// Join a controller prefix and a route path into a single `/`-separated path
+fn join_paths(prefix: &str, path: &str) -> String {
+    let segments: Vec<&str> = [prefix, path]
+        .iter()
+        .map(|p| p.trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .collect();
+    format!("/{}", segments.join("/"))
+}
@@ -2,12 +2,16 @@ use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
+use std::time::Instant;
+use tree_sitter::{Node, Point, Tree};
+
+use crate::ast_parser::parse_tree;
 
 /// Import information
 #[napi(object)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ImportInfo {
     pub module: String,
     pub imports: Vec<String>,
@@ -17,11 +21,44 @@ pub struct ImportInfo {
     pub is_default: bool,
     #[napi(js_name = "isNamespace")]
     pub is_namespace: bool,
+    #[napi(js_name = "isDynamic")]
+    pub is_dynamic: bool,
+    /// `import type { ... }` - the whole statement is erased at runtime
+    #[napi(js_name = "isTypeOnly")]
+    pub is_type_only: bool,
+    /// Specifiers imported only as types, including inline `import { type Foo, bar }`
+    #[napi(js_name = "typeOnlyImports")]
+    pub type_only_imports: Vec<String>,
+    /// C/C++ `#include <header>` as opposed to `#include "header"`
+    #[napi(js_name = "isSystem")]
+    pub is_system: bool,
+}
+
+/// Export information
+///
+/// Shared shape for ES module exports and CommonJS `module.exports`/`exports.x` assignments
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportInfo {
+    /// Exported name (`default` for default exports and `module.exports = value`)
+    pub name: String,
+    /// Local binding when it differs from the exported name (`export { a as b }`)
+    #[napi(js_name = "localName")]
+    pub local_name: Option<String>,
+    pub kind: String, // 'function' | 'class' | 'variable' | 'type' | 'interface' | 'enum' | 'value' | 'namespace'
+    /// Source module for re-exports (`export { a } from './x'`, `export * from './y'`)
+    pub source: Option<String>,
+    #[napi(js_name = "lineNumber")]
+    pub line_number: u32,
+    #[napi(js_name = "isDefault")]
+    pub is_default: bool,
+    #[napi(js_name = "isCommonJs")]
+    pub is_common_js: bool,
 }
 
 /// Function information
 #[napi(object)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FunctionInfo {
     pub name: String,
     pub parameters: Vec<ParameterInfo>,
@@ -33,6 +70,24 @@ pub struct FunctionInfo {
     pub is_async: bool,
     #[napi(js_name = "isGenerator")]
     pub is_generator: bool,
+    /// Receiver type for Go methods (`*Server` in `func (s *Server) Start()`)
+    pub receiver: Option<String>,
+    /// Declaration modifiers such as `public`, `static` or `final`
+    pub modifiers: Vec<String>,
+    /// Signature without a body, such as a C prototype
+    #[napi(js_name = "isDeclaration")]
+    pub is_declaration: bool,
+    /// Reachable from other files: exported, `pub`, public or capitalized depending on the language
+    #[napi(js_name = "isExported")]
+    pub is_exported: bool,
+    #[napi(js_name = "isStatic")]
+    pub is_static: bool,
+    pub visibility: String, // 'public' | 'protected' | 'private' | 'internal' | 'package' | '#private'
+    #[napi(js_name = "isMethod")]
+    pub is_method: bool,
+    /// Class, struct, trait or module a method belongs to
+    #[napi(js_name = "parentClass")]
+    pub parent_class: Option<String>,
 }
 
 /// Parameter information
@@ -50,13 +105,42 @@ pub struct ParameterInfo {
 
 /// Class information
 #[napi(object)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ClassInfo {
     pub name: String,
+    pub kind: String, // 'class' | 'struct' | 'enum' | 'trait' | 'impl' | 'interface' | 'record' | 'annotation' | 'module' | 'union'
     pub extends: Option<String>,
     pub implements: Vec<String>,
     pub methods: Vec<String>,
     pub properties: Vec<String>,
+    /// Methods and properties with their modifiers, where the language extractor provides them
+    pub members: Vec<MemberInfo>,
+    #[napi(js_name = "lineNumber")]
+    pub line_number: u32,
+}
+
+/// Class member information
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemberInfo {
+    pub name: String,
+    pub kind: String, // 'method' | 'property' | 'constructor' | 'getter' | 'setter'
+    pub visibility: String, // 'public' | 'protected' | 'private' | '#private'
+    #[napi(js_name = "isStatic")]
+    pub is_static: bool,
+    #[napi(js_name = "isAbstract")]
+    pub is_abstract: bool,
+    #[napi(js_name = "isReadonly")]
+    pub is_readonly: bool,
+    #[napi(js_name = "isOptional")]
+    pub is_optional: bool,
+    /// Declared through a constructor parameter (`constructor(private repo: Repo)`)
+    #[napi(js_name = "isParameterProperty")]
+    pub is_parameter_property: bool,
+    #[napi(js_name = "memberType")]
+    pub member_type: Option<String>,
+    /// Signature of methods, constructors and accessors
+    pub signature: Option<FunctionInfo>,
     #[napi(js_name = "lineNumber")]
     pub line_number: u32,
 }
@@ -69,7 +153,7 @@ pub struct DecoratorInfo {
     pub arguments: Option<String>,
     #[napi(js_name = "lineNumber")]
     pub line_number: u32,
-    pub target: String, // 'class' | 'method' | 'property' | 'parameter'
+    pub target: String, // 'class' | 'function' | 'method' | 'property' | 'parameter' | 'variable' | 'module' | 'unknown'
 }
 
 /// Generic type information
@@ -84,15 +168,90 @@ pub struct GenericInfo {
     pub line_number: u32,
 }
 
+/// Preprocessor macro information
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroInfo {
+    pub name: String,
+    /// Parameter names for function-like macros, `None` for object-like ones
+    pub parameters: Option<Vec<String>>,
+    pub value: Option<String>,
+    #[napi(js_name = "lineNumber")]
+    pub line_number: u32,
+}
+
+/// Symbol reference count
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolReferences {
+    pub name: String,
+    pub count: u32,
+    /// Line of each reference, in source order
+    pub lines: Vec<u32>,
+}
+
+/// A file to analyze in a batch
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileInput {
+    pub path: String,
+    pub code: String,
+    #[napi(js_name = "languageId")]
+    pub language_id: String,
+}
+
+/// Analysis of one file in a batch
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAnalysis {
+    pub path: String,
+    pub analysis: Option<SemanticAnalysis>,
+    pub error: Option<String>,
+    #[napi(js_name = "durationMs")]
+    pub duration_ms: f64,
+}
+
+/// Batch analysis result
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchAnalysis {
+    /// Results in the same order as the input files
+    pub files: Vec<FileAnalysis>,
+    /// Wall-clock time for the whole batch
+    #[napi(js_name = "totalMs")]
+    pub total_ms: f64,
+}
+
+/// Symbol visible at a position
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopeSymbol {
+    pub name: String,
+    pub kind: String, // 'parameter' | 'variable' | 'constant' | 'function' | 'class' | 'type' | 'import'
+    #[napi(js_name = "lineNumber")]
+    pub line_number: u32,
+    /// Declared at the top level of the file rather than in an enclosing function or block
+    #[napi(js_name = "isModuleLevel")]
+    pub is_module_level: bool,
+}
+
 /// Semantic analysis result
 #[napi(object)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticAnalysis {
+    #[napi(js_name = "languageId")]
+    pub language_id: String,
+    /// Number of lines in the analyzed code, used to shift symbols on incremental updates
+    #[napi(js_name = "lineCount")]
+    pub line_count: u32,
+    /// Package or namespace the file declares (Java `package`, C#/C++/PHP `namespace`)
+    pub namespace: Option<String>,
     pub imports: Vec<ImportInfo>,
     pub functions: Vec<FunctionInfo>,
     pub classes: Vec<ClassInfo>,
     pub decorators: Vec<DecoratorInfo>,
     pub generics: Vec<GenericInfo>,
+    pub macros: Vec<MacroInfo>,
 }
 
 /// Compiled regex patterns cache
@@ -104,29 +263,31 @@ fn init_regex_cache() -> HashMap<String, Regex> {
     
     // TypeScript/JavaScript imports
     cache.insert("ts_import".to_string(), 
-        Regex::new(r#"import\s+(?:(?:\{([^}]+)\})|(?:(\w+))|\*\s+as\s+(\w+))\s+from\s+['"]([^'"]+)['"]"#).unwrap());
+        Regex::new(r#"import\s+(type\s+)?(?:(?:\{([^}]+)\})|(?:(\w+))|\*\s+as\s+(\w+))\s+from\s+['"]([^'"]+)['"]"#).unwrap());
     cache.insert("ts_require".to_string(),
         Regex::new(r#"(?:const|let|var)\s+(?:\{([^}]+)\}|(\w+))\s*=\s*require\(['"]([^'"]+)['"]\)"#).unwrap());
+    cache.insert("ts_dynamic_import".to_string(),
+        Regex::new(r#"(?:(?:const|let|var)\s+(?:\{([^}]+)\}|(\w+))\s*=\s*)?(?:await\s+)?\bimport\s*\(\s*['"`]([^'"`]+)['"`]\s*\)"#).unwrap());
+    
+    // TypeScript/JavaScript exports
+    cache.insert("ts_export_decl".to_string(),
+        Regex::new(r"(?m)^[ \t]*export\s+(default\s+)?(?:declare\s+)?(?:(async\s+)?function\s*\*?|(?:abstract\s+)?(class)|(?:const\s+)?(enum)|(const|let|var)|(type)|(interface)|(namespace|module))\s+([\w$]+)").unwrap());
+    cache.insert("ts_export_default".to_string(),
+        Regex::new(r"(?m)^[ \t]*export\s+default\s+(?:([\w$]+)\s*;?\s*$)?").unwrap());
+    cache.insert("ts_export_list".to_string(),
+        Regex::new(r#"export\s+(type\s+)?\{([^}]*)\}(?:\s*from\s*['"]([^'"]+)['"])?"#).unwrap());
+    cache.insert("ts_export_star".to_string(),
+        Regex::new(r#"export\s+\*(?:\s+as\s+([\w$]+))?\s+from\s*['"]([^'"]+)['"]"#).unwrap());
+    cache.insert("cjs_module_exports".to_string(),
+        Regex::new(r"\bmodule\.exports\s*=\s*").unwrap());
+    cache.insert("cjs_named_export".to_string(),
+        Regex::new(r"\b(?:module\.)?exports\.([\w$]+)\s*(=)[^=]").unwrap());
     
     // Python imports
     cache.insert("py_import".to_string(),
-        Regex::new(r"^import\s+([\w.]+)(?:\s+as\s+(\w+))?").unwrap());
+        Regex::new(r"(?m)^import\s+([\w.]+)(?:\s+as\s+(\w+))?").unwrap());
     cache.insert("py_from_import".to_string(),
-        Regex::new(r"^from\s+([\w.]+)\s+import\s+(.+)").unwrap());
-    
-    // Function patterns
-    cache.insert("ts_function".to_string(),
-        Regex::new(r"(?:async\s+)?function\s+(\w+)\s*\(([^)]*)\)(?:\s*:\s*([^{]+))?").unwrap());
-    cache.insert("ts_arrow".to_string(),
-        Regex::new(r"(?:const|let|var)\s+(\w+)\s*=\s*(?:async\s+)?\(([^)]*)\)(?:\s*:\s*([^=]+))?\s*=>").unwrap());
-    cache.insert("py_function".to_string(),
-        Regex::new(r"(?:async\s+)?def\s+(\w+)\s*\(([^)]*)\)(?:\s*->\s*([^:]+))?:").unwrap());
-    
-    // Class patterns
-    cache.insert("ts_class".to_string(),
-        Regex::new(r"class\s+(\w+)(?:\s+extends\s+(\w+))?(?:\s+implements\s+([^{]+))?").unwrap());
-    cache.insert("py_class".to_string(),
-        Regex::new(r"class\s+(\w+)(?:\(([^)]+)\))?:").unwrap());
+        Regex::new(r"(?m)^from\s+([\w.]+)\s+import\s+(.+)").unwrap());
     
     // Decorator patterns
     cache.insert("ts_decorator".to_string(),
@@ -135,8 +296,6 @@ fn init_regex_cache() -> HashMap<String, Regex> {
         Regex::new(r"@(\w+(?:\.\w+)*)\s*(?:\(([^)]*)\))?").unwrap());
     cache.insert("cpp_attribute".to_string(),
         Regex::new(r"\[\[(\w+)(?:\(([^)]*)\))?\]\]").unwrap());
-    cache.insert("java_annotation".to_string(),
-        Regex::new(r"@(\w+(?:\.\w+)*)(?:\(([^)]*)\))?").unwrap());
     
     // Generic patterns
     cache.insert("ts_generic".to_string(),
@@ -150,12 +309,44 @@ fn get_regex(key: &str) -> Option<&'static Regex> {
     REGEX_CACHE.get_or_init(init_regex_cache).get(key)
 }
 
-struct LineIndex {
+pub(crate) struct LineIndex {
     offsets: Vec<usize>,
 }
 
+/// A file shared by the extractors of one analysis
+///
+/// The line index and syntax tree are built on first use, so running every
+/// extractor over the same file indexes and parses it only once.
+struct SourceFile<'a> {
+    code: &'a str,
+    language_id: &'a str,
+    line_index: OnceLock<LineIndex>,
+    tree: OnceLock<Option<Tree>>,
+}
+
+impl<'a> SourceFile<'a> {
+    fn new(code: &'a str, language_id: &'a str) -> Self {
+        Self {
+            code,
+            language_id,
+            line_index: OnceLock::new(),
+            tree: OnceLock::new(),
+        }
+    }
+
+    fn line_index(&self) -> &LineIndex {
+        self.line_index.get_or_init(|| LineIndex::new(self.code))
+    }
+
+    fn tree(&self) -> Option<&Tree> {
+        self.tree
+            .get_or_init(|| parse_tree(self.code, self.language_id).ok())
+            .as_ref()
+    }
+}
+
 impl LineIndex {
-    fn new(code: &str) -> Self {
+    pub(crate) fn new(code: &str) -> Self {
         let mut offsets = vec![0];
         for (i, b) in code.bytes().enumerate() {
             if b == b'\n' {
@@ -165,12 +356,79 @@ impl LineIndex {
         Self { offsets }
     }
 
-    fn get_line(&self, offset: usize) -> u32 {
+    pub(crate) fn get_line(&self, offset: usize) -> u32 {
         match self.offsets.binary_search(&offset) {
             Ok(line) => line as u32,
             Err(line) => (line - 1) as u32,
         }
     }
+
+    pub(crate) fn line_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Byte offset where a line starts
+    pub(crate) fn line_start(&self, line: usize) -> Option<usize> {
+        self.offsets.get(line).copied()
+    }
+}
+
+/// Source text of a Tree-sitter node
+pub(crate) fn node_text<'a>(node: Node, code: &'a str) -> &'a str {
+    node.utf8_text(code.as_bytes()).unwrap_or("")
+}
+
+/// Zero-based line a Tree-sitter node starts on
+pub(crate) fn node_line(node: Node) -> u32 {
+    node.start_position().row as u32
+}
+
+/// Line of a declaration's name, skipping leading annotations or attributes
+fn decl_line(node: Node) -> u32 {
+    node.child_by_field_name("name").map(node_line).unwrap_or_else(|| node_line(node))
+}
+
+/// Text of a node's field, if present
+pub(crate) fn field_text(node: Node, field: &str, code: &str) -> Option<String> {
+    node.child_by_field_name(field).map(|n| node_text(n, code).to_string())
+}
+
+/// Visit every named node below `node` in document order
+pub(crate) fn visit_named<'a>(node: Node<'a>, visit: &mut impl FnMut(Node<'a>)) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        visit(child);
+        visit_named(child, visit);
+    }
+}
+
+/// Name of the class-like declaration a function is a member of
+///
+/// Stops at the nearest enclosing function, so nested helpers are not methods.
+fn enclosing_type(node: Node, code: &str) -> Option<String> {
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        let kind = ancestor.kind();
+        match kind {
+            "impl_item" => return field_text(ancestor, "type", code),
+            // Class expressions are named by the variable they are assigned to
+            "class" if ancestor.child_by_field_name("name").is_none() => {
+                return ancestor
+                    .parent()
+                    .filter(|p| p.kind() == "variable_declarator")
+                    .and_then(|p| field_text(p, "name", code));
+            }
+            "class_declaration" | "abstract_class_declaration" | "class_definition" | "class_specifier"
+            | "struct_specifier" | "union_specifier" | "interface_declaration" | "enum_declaration"
+            | "record_declaration" | "struct_declaration" | "trait_item" | "trait_declaration" | "class" | "module" => {
+                return field_text(ancestor, "name", code);
+            }
+            _ if ["function", "method", "lambda", "arrow", "closure"].iter().any(|k| kind.contains(k)) => return None,
+            _ => {}
+        }
+        current = ancestor.parent();
+    }
+    None
 }
 
 /// Extract imports from code
@@ -181,18 +439,39 @@ impl LineIndex {
 /// - No V8 overhead
 #[napi]
 pub fn extract_imports(code: String, language_id: String) -> Result<Vec<ImportInfo>> {
-    Ok(process_imports(&code, &language_id))
+    Ok(process_imports(&SourceFile::new(&code, &language_id)))
 }
 
-fn process_imports(code: &str, language_id: &str) -> Vec<ImportInfo> {
+fn process_imports(src: &SourceFile) -> Vec<ImportInfo> {
     let mut imports = Vec::new();
     
-    match language_id {
+    match src.language_id {
         "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
-            extract_ts_imports(code, &mut imports);
+            extract_ts_imports(src, &mut imports);
         }
         "python" => {
-            extract_py_imports(code, &mut imports);
+            extract_py_imports(src, &mut imports);
+        }
+        "rust" => {
+            extract_rust_imports(src, &mut imports);
+        }
+        "go" => {
+            extract_go_imports(src, &mut imports);
+        }
+        "java" => {
+            extract_java_imports(src, &mut imports);
+        }
+        "csharp" => {
+            extract_csharp_imports(src, &mut imports);
+        }
+        "ruby" => {
+            extract_ruby_imports(src, &mut imports);
+        }
+        "php" => {
+            extract_php_imports(src, &mut imports);
+        }
+        "cpp" | "c" => {
+            extract_cpp_imports(src, &mut imports);
         }
         _ => {}
     }
@@ -200,21 +479,38 @@ fn process_imports(code: &str, language_id: &str) -> Vec<ImportInfo> {
     imports
 }
 
-fn extract_ts_imports(code: &str, imports: &mut Vec<ImportInfo>) {
-    let line_index = LineIndex::new(code);
+fn extract_ts_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let line_index = src.line_index();
 
     if let Some(import_re) = get_regex("ts_import") {
         for caps in import_re.captures_iter(code) {
             let start = caps.get(0).unwrap().start();
             let line_num = line_index.get_line(start);
             
-            let named = caps.get(1).map(|m| m.as_str());
-            let default = caps.get(2).map(|m| m.as_str());
-            let namespace = caps.get(3).map(|m| m.as_str());
-            let module = caps.get(4).map(|m| m.as_str().to_string()).unwrap_or_default();
+            let is_type_only = caps.get(1).is_some();
+            let named = caps.get(2).map(|m| m.as_str());
+            let default = caps.get(3).map(|m| m.as_str());
+            let namespace = caps.get(4).map(|m| m.as_str());
+            let module = caps.get(5).map(|m| m.as_str().to_string()).unwrap_or_default();
             
-            let import_list = if let Some(named) = named {
-                named.split(',').map(|s| s.trim().to_string()).collect()
+            let mut type_only_imports = Vec::new();
+            let import_list: Vec<String> = if let Some(named) = named {
+                named
+                    .split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        let (specifier, inline_type) = match s.strip_prefix("type ") {
+                            Some(rest) => (rest.trim(), true),
+                            None => (s, false),
+                        };
+                        if is_type_only || inline_type {
+                            type_only_imports.push(specifier.to_string());
+                        }
+                        specifier.to_string()
+                    })
+                    .collect()
             } else if let Some(default) = default {
                 vec![default.to_string()]
             } else if let Some(namespace) = namespace {
@@ -223,12 +519,20 @@ fn extract_ts_imports(code: &str, imports: &mut Vec<ImportInfo>) {
                 vec![]
             };
             
+            if is_type_only && named.is_none() {
+                type_only_imports.clone_from(&import_list);
+            }
+            
             imports.push(ImportInfo {
                 module,
                 imports: import_list,
                 line_number: line_num,
                 is_default: default.is_some(),
                 is_namespace: namespace.is_some(),
+                is_dynamic: false,
+                is_type_only,
+                type_only_imports,
+                ..Default::default()
             });
         }
     }
@@ -257,13 +561,45 @@ fn extract_ts_imports(code: &str, imports: &mut Vec<ImportInfo>) {
                 line_number: line_num,
                 is_default: default.is_some(),
                 is_namespace: false,
+                ..Default::default()
+            });
+        }
+    }
+
+    // Dynamic `import('module')`, optionally awaited and destructured
+    if let Some(dynamic_re) = get_regex("ts_dynamic_import") {
+        for caps in dynamic_re.captures_iter(code) {
+            let start = caps.get(0).unwrap().start();
+            let line_num = line_index.get_line(start);
+
+            let named = caps.get(1).map(|m| m.as_str());
+            let binding = caps.get(2).map(|m| m.as_str());
+            let module = caps.get(3).map(|m| m.as_str().to_string()).unwrap_or_default();
+
+            let import_list = if let Some(named) = named {
+                named.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+            } else if let Some(binding) = binding {
+                vec![binding.to_string()]
+            } else {
+                vec![]
+            };
+
+            imports.push(ImportInfo {
+                module,
+                imports: import_list,
+                line_number: line_num,
+                is_default: false,
+                is_namespace: binding.is_some(),
+                is_dynamic: true,
+                ..Default::default()
             });
         }
     }
 }
 
-fn extract_py_imports(code: &str, imports: &mut Vec<ImportInfo>) {
-    let line_index = LineIndex::new(code);
+fn extract_py_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let line_index = src.line_index();
 
     if let Some(import_re) = get_regex("py_import") {
         for caps in import_re.captures_iter(code) {
@@ -279,6 +615,7 @@ fn extract_py_imports(code: &str, imports: &mut Vec<ImportInfo>) {
                 line_number: line_num,
                 is_default: false,
                 is_namespace: false,
+                ..Default::default()
             });
         }
     }
@@ -302,273 +639,2680 @@ fn extract_py_imports(code: &str, imports: &mut Vec<ImportInfo>) {
                 line_number: line_num,
                 is_default: false,
                 is_namespace: false,
+                ..Default::default()
             });
         }
     }
 }
 
-/// Extract functions from code
-#[napi]
-pub fn extract_functions(code: String, language_id: String) -> Result<Vec<FunctionInfo>> {
-    Ok(process_functions(&code, &language_id))
+fn extract_rust_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let line_number = node_line(node);
+        match node.kind() {
+            "use_declaration" => {
+                let Some(argument) = node.child_by_field_name("argument") else { return };
+                let (module, items, is_namespace) = match argument.kind() {
+                    // `use a::b::{c, d as e};`
+                    "scoped_use_list" => {
+                        let module = field_text(argument, "path", code).unwrap_or_default();
+                        let items = argument
+                            .child_by_field_name("list")
+                            .map(|list| {
+                                let mut cursor = list.walk();
+                                list.named_children(&mut cursor).map(|n| node_text(n, code).to_string()).collect()
+                            })
+                            .unwrap_or_default();
+                        (module, items, false)
+                    }
+                    // `use a::b::*;`
+                    "use_wildcard" => {
+                        let text = node_text(argument, code);
+                        (text.trim_end_matches("::*").to_string(), vec!["*".to_string()], true)
+                    }
+                    // `use a::b as c;`
+                    "use_as_clause" => {
+                        let path = argument.child_by_field_name("path");
+                        let module = path
+                            .and_then(|p| p.child_by_field_name("path"))
+                            .map(|p| node_text(p, code).to_string())
+                            .unwrap_or_else(|| path.map(|p| node_text(p, code).to_string()).unwrap_or_default());
+                        let name = path
+                            .and_then(|p| p.child_by_field_name("name").or(Some(p)))
+                            .map(|n| node_text(n, code))
+                            .unwrap_or("");
+                        let alias = field_text(argument, "alias", code).unwrap_or_default();
+                        (module, vec![format!("{} as {}", name, alias)], false)
+                    }
+                    // `use a::b;`
+                    "scoped_identifier" => (
+                        field_text(argument, "path", code).unwrap_or_default(),
+                        vec![field_text(argument, "name", code).unwrap_or_default()],
+                        false,
+                    ),
+                    _ => {
+                        let text = node_text(argument, code).to_string();
+                        (text.clone(), vec![text], false)
+                    }
+                };
+
+                imports.push(ImportInfo {
+                    module,
+                    imports: items,
+                    line_number,
+                    is_namespace,
+                    ..Default::default()
+                });
+            }
+            "extern_crate_declaration" => {
+                let name = field_text(node, "name", code).unwrap_or_default();
+                let alias = field_text(node, "alias", code);
+                imports.push(ImportInfo {
+                    imports: vec![alias.unwrap_or_else(|| name.clone())],
+                    module: name,
+                    line_number,
+                    ..Default::default()
+                });
+            }
+            _ => {}
+        }
+    });
 }
 
-fn process_functions(code: &str, language_id: &str) -> Vec<FunctionInfo> {
-    let mut functions = Vec::new();
-    
-    match language_id {
-        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
-            extract_ts_functions(code, &mut functions);
+fn extract_go_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "import_spec" {
+            return;
         }
-        "python" => {
-            extract_py_functions(code, &mut functions);
+
+        let module = field_text(node, "path", code)
+            .map(|p| p.trim_matches(['"', '`']).to_string())
+            .unwrap_or_default();
+        let name = field_text(node, "name", code);
+
+        // `_ "pkg"` is a side-effect import and `. "pkg"` merges the package namespace
+        let is_namespace = name.as_deref() == Some(".");
+        let import_list = match name.as_deref() {
+            Some("_") => vec![],
+            Some(".") => vec!["*".to_string()],
+            Some(alias) => vec![alias.to_string()],
+            None => vec![module.rsplit('/').next().unwrap_or(&module).to_string()],
+        };
+
+        imports.push(ImportInfo {
+            module,
+            imports: import_list,
+            line_number: node_line(node),
+            is_namespace,
+            ..Default::default()
+        });
+    });
+}
+
+fn extract_java_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "import_declaration" {
+            return;
         }
-        _ => {}
-    }
-    
-    functions
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        let Some(path) = children.iter().find(|n| matches!(n.kind(), "scoped_identifier" | "identifier")) else { return };
+        let is_wildcard = children.iter().any(|n| n.kind() == "asterisk");
+
+        // `import a.b.C;` imports `C` from `a.b`, `import a.b.*;` imports everything from `a.b`
+        let (module, import_list) = if is_wildcard {
+            (node_text(*path, code).to_string(), vec!["*".to_string()])
+        } else {
+            (
+                field_text(*path, "scope", code).unwrap_or_default(),
+                vec![field_text(*path, "name", code).unwrap_or_else(|| node_text(*path, code).to_string())],
+            )
+        };
+
+        imports.push(ImportInfo {
+            module,
+            imports: import_list,
+            line_number: node_line(node),
+            is_namespace: is_wildcard,
+            ..Default::default()
+        });
+    });
 }
 
-fn extract_ts_functions(code: &str, functions: &mut Vec<FunctionInfo>) {
-    let line_index = LineIndex::new(code);
-    
-    if let Some(func_re) = get_regex("ts_function") {
-        for caps in func_re.captures_iter(code) {
-            let start = caps.get(0).unwrap().start();
-            let line_num = line_index.get_line(start);
-            let full_match = caps.get(0).unwrap().as_str();
+fn extract_csharp_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
 
-            let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-            let params_str = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            let return_type = caps.get(3).map(|m| m.as_str().trim().to_string());
-            
-            let parameters = parse_parameters(params_str);
-            
-            functions.push(FunctionInfo {
-                name,
-                parameters,
-                return_type,
-                line_number: line_num,
-                is_async: full_match.contains("async"),
-                is_generator: full_match.contains("function*"),
-            });
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "using_directive" {
+            return;
         }
-    }
-    
-    // Also arrow functions
-    if let Some(arrow_re) = get_regex("ts_arrow") {
-        for caps in arrow_re.captures_iter(code) {
-            let start = caps.get(0).unwrap().start();
-            let line_num = line_index.get_line(start);
-            let full_match = caps.get(0).unwrap().as_str();
 
-            let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-            let params_str = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            let return_type = caps.get(3).map(|m| m.as_str().trim().to_string());
-             
-            let parameters = parse_parameters(params_str);
-            
-            functions.push(FunctionInfo {
-                name,
-                parameters,
-                return_type,
-                line_number: line_num,
-                is_async: full_match.contains("async"),
-                is_generator: false,
-            });
+        // `using Alias = Some.Namespace;` binds a single name, otherwise the whole namespace is imported
+        let mut cursor = node.walk();
+        let mut alias = None;
+        let mut module = String::new();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "name_equals" => alias = child.named_child(0).map(|n| node_text(n, code).to_string()),
+                _ => module = node_text(child, code).to_string(),
+            }
         }
-    }
+
+        imports.push(ImportInfo {
+            module,
+            is_namespace: alias.is_none(),
+            imports: vec![alias.unwrap_or_else(|| "*".to_string())],
+            line_number: node_line(node),
+            ..Default::default()
+        });
+    });
 }
 
-fn extract_py_functions(code: &str, functions: &mut Vec<FunctionInfo>) {
-    let line_index = LineIndex::new(code);
+fn extract_ruby_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
 
-    if let Some(func_re) = get_regex("py_function") {
-        for caps in func_re.captures_iter(code) {
-             let start = caps.get(0).unwrap().start();
-             let line_num = line_index.get_line(start);
-             let full_match = caps.get(0).unwrap().as_str();
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "call" || node.child_by_field_name("receiver").is_some() {
+            return;
+        }
+        let method = field_text(node, "method", code).unwrap_or_default();
+        if !matches!(method.as_str(), "require" | "require_relative" | "load" | "autoload") {
+            return;
+        }
 
-             let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-             let params_str = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-             let return_type = caps.get(3).map(|m| m.as_str().trim().to_string());
-             
-             let parameters = parse_parameters(params_str);
-             
-             functions.push(FunctionInfo {
-                name,
-                parameters,
-                return_type,
-                line_number: line_num,
-                is_async: full_match.contains("async"),
-                is_generator: false,
-            });
+        let Some(args) = node.child_by_field_name("arguments") else { return };
+        let mut cursor = args.walk();
+        let arguments: Vec<Node> = args.named_children(&mut cursor).collect();
+
+        // `autoload :Const, "path"` names the constant it defines
+        let (module, import_list) = match (method.as_str(), arguments.as_slice()) {
+            ("autoload", [constant, path, ..]) => (
+                ruby_string_value(*path, code),
+                vec![node_text(*constant, code).trim_start_matches(':').to_string()],
+            ),
+            (_, [path, ..]) => (ruby_string_value(*path, code), vec![]),
+            _ => return,
+        };
+
+        imports.push(ImportInfo {
+            module,
+            imports: import_list,
+            line_number: node_line(node),
+            ..Default::default()
+        });
+    });
+}
+
+/// Content of a Ruby string literal, or the raw expression for computed paths
+fn ruby_string_value(node: Node, code: &str) -> String {
+    if node.kind() == "string" && node.named_child_count() == 1 {
+        if let Some(content) = node.named_child(0).filter(|c| c.kind() == "string_content") {
+            return node_text(content, code).to_string();
         }
     }
+    node_text(node, code).to_string()
 }
 
-fn parse_parameters(params_str: &str) -> Vec<ParameterInfo> {
-    params_str
-        .split(',')
-        .filter(|s| !s.trim().is_empty())
-        .map(|param| {
-            let param = param.trim();
-            let parts: Vec<&str> = param.split(':').collect();
-            
-            let name_part = parts[0].trim();
-            let (name, default_value) = if let Some(eq_pos) = name_part.find('=') {
-                let (n, d) = name_part.split_at(eq_pos);
-                (n.trim().to_string(), Some(d[1..].trim().to_string()))
-            } else {
-                (name_part.to_string(), None)
-            };
-            
-            let param_type = parts.get(1).map(|t| t.trim().to_string());
-            let is_optional = name.ends_with('?');
-            
-            ParameterInfo {
-                name: name.trim_end_matches('?').to_string(),
-                param_type,
-                default_value,
-                is_optional,
+fn extract_php_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let line_number = node_line(node);
+        match node.kind() {
+            "namespace_use_declaration" => {
+                let mut cursor = node.walk();
+                let children: Vec<Node> = node.named_children(&mut cursor).collect();
+
+                // `use App\Models\{User, Post as P};`
+                if let Some(group) = children.iter().find(|n| n.kind() == "namespace_use_group") {
+                    let module = children
+                        .iter()
+                        .find(|n| n.kind() == "namespace_name")
+                        .map(|n| node_text(*n, code).to_string())
+                        .unwrap_or_default();
+                    let mut group_cursor = group.walk();
+                    let items = group
+                        .named_children(&mut group_cursor)
+                        .map(|clause| php_use_item(clause, code))
+                        .collect();
+                    imports.push(ImportInfo {
+                        module,
+                        imports: items,
+                        line_number,
+                        ..Default::default()
+                    });
+                    return;
+                }
+
+                // `use Illuminate\Support\Str;` - one import per clause
+                for clause in children.iter().filter(|n| n.kind() == "namespace_use_clause") {
+                    let full = clause
+                        .named_child(0)
+                        .map(|n| node_text(n, code).trim_start_matches('\\').to_string())
+                        .unwrap_or_default();
+                    let (module, name) = match full.rsplit_once('\\') {
+                        Some((module, name)) => (module.to_string(), name.to_string()),
+                        None => (String::new(), full.clone()),
+                    };
+                    let item = php_use_item(*clause, code);
+                    let item = if item.contains(" as ") { item.replacen(&full, &name, 1) } else { name };
+                    imports.push(ImportInfo {
+                        module,
+                        imports: vec![item],
+                        line_number,
+                        ..Default::default()
+                    });
+                }
             }
-        })
-        .collect()
+            "require_expression" | "require_once_expression" | "include_expression" | "include_once_expression" => {
+                let Some(path) = node.named_child(0) else { return };
+                let module = if path.kind() == "encapsed_string" || path.kind() == "string" {
+                    path.named_child(0)
+                        .map(|n| node_text(n, code).to_string())
+                        .unwrap_or_else(|| node_text(path, code).trim_matches(['"', '\'']).to_string())
+                } else {
+                    node_text(path, code).to_string()
+                };
+                imports.push(ImportInfo {
+                    module,
+                    imports: vec![],
+                    line_number,
+                    ..Default::default()
+                });
+            }
+            _ => {}
+        }
+    });
 }
 
+/// Imported name of a PHP use clause, with its alias as `Name as Alias`
+fn php_use_item(clause: Node, code: &str) -> String {
+    let mut cursor = clause.walk();
+    let mut name = String::new();
+    let mut alias = None;
+    for child in clause.named_children(&mut cursor) {
+        match child.kind() {
+            "namespace_aliasing_clause" => alias = child.named_child(0).map(|a| node_text(a, code).to_string()),
+            _ => name = node_text(child, code).trim_start_matches('\\').to_string(),
+        }
+    }
+    match alias {
+        Some(alias) => format!("{} as {}", name, alias),
+        None => name,
+    }
+}
 
-/// Extract classes from code
+fn extract_cpp_imports(src: &SourceFile, imports: &mut Vec<ImportInfo>) {
+    let code = src.code;
+    // C and C++ share the C++ grammar
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "preproc_include" {
+            return;
+        }
+        let Some(path) = node.child_by_field_name("path") else { return };
+
+        let is_system = path.kind() == "system_lib_string";
+        let module = node_text(path, code).trim_matches(['<', '>', '"']).to_string();
+
+        imports.push(ImportInfo {
+            module,
+            imports: vec![],
+            line_number: node_line(node),
+            is_system,
+            ..Default::default()
+        });
+    });
+}
+
+/// Extract exports from code
+///
+/// Covers ES module `export` forms and CommonJS `module.exports`/`exports.x` assignments
 #[napi]
-pub fn extract_classes(code: String, language_id: String) -> Result<Vec<ClassInfo>> {
-    Ok(process_classes(&code, &language_id))
+pub fn extract_exports(code: String, language_id: String) -> Result<Vec<ExportInfo>> {
+    Ok(process_exports(&SourceFile::new(&code, &language_id)))
 }
 
-fn process_classes(code: &str, language_id: &str) -> Vec<ClassInfo> {
-    let mut classes = Vec::new();
+fn process_exports(src: &SourceFile) -> Vec<ExportInfo> {
+    let mut exports = Vec::new();
     
-    match language_id {
+    match src.language_id {
         "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
-            extract_ts_classes(code, &mut classes);
-        }
-        "python" => {
-            extract_py_classes(code, &mut classes);
+            extract_ts_exports(src, &mut exports);
+            extract_commonjs_exports(src, &mut exports);
+            exports.sort_by_key(|e| e.line_number);
         }
         _ => {}
     }
     
-    classes
+    exports
 }
 
-fn extract_ts_classes(code: &str, classes: &mut Vec<ClassInfo>) {
-    let line_index = LineIndex::new(code);
-    
-    if let Some(class_re) = get_regex("ts_class") {
-        for caps in class_re.captures_iter(code) {
+fn extract_ts_exports(src: &SourceFile, exports: &mut Vec<ExportInfo>) {
+    let code = src.code;
+    let line_index = src.line_index();
+
+    if let Some(decl_re) = get_regex("ts_export_decl") {
+        for caps in decl_re.captures_iter(code) {
             let start = caps.get(0).unwrap().start();
-            let line_num = line_index.get_line(start);
-            
-            let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-            let extends = caps.get(2).map(|m| m.as_str().to_string());
-            let implements_str = caps.get(3).map(|m| m.as_str());
-            
-            let implements = if let Some(impl_str) = implements_str {
-                impl_str.split(',').map(|s| s.trim().to_string()).collect()
+            let is_default = caps.get(1).is_some();
+            let kind = if caps.get(3).is_some() {
+                "class"
+            } else if caps.get(4).is_some() {
+                "enum"
+            } else if caps.get(5).is_some() {
+                "variable"
+            } else if caps.get(6).is_some() {
+                "type"
+            } else if caps.get(7).is_some() {
+                "interface"
+            } else if caps.get(8).is_some() {
+                "namespace"
             } else {
-                Vec::new()
+                "function"
             };
-            
-            classes.push(ClassInfo {
+            let local = caps.get(9).map(|m| m.as_str().to_string()).unwrap_or_default();
+
+            let (name, local_name) = if is_default {
+                ("default".to_string(), Some(local))
+            } else {
+                (local, None)
+            };
+
+            exports.push(ExportInfo {
                 name,
-                extends,
-                implements,
-                methods: Vec::new(), // Would need deeper parsing
-                properties: Vec::new(), // Would need deeper parsing
-                line_number: line_num,
+                local_name,
+                kind: kind.to_string(),
+                line_number: line_index.get_line(start),
+                is_default,
+                ..Default::default()
             });
         }
     }
-}
 
-fn extract_py_classes(code: &str, classes: &mut Vec<ClassInfo>) {
-    let line_index = LineIndex::new(code);
-    
-    if let Some(class_re) = get_regex("py_class") {
-        for caps in class_re.captures_iter(code) {
+    // `export default <expression>` not already covered by a named declaration
+    if let Some(default_re) = get_regex("ts_export_default") {
+        for caps in default_re.captures_iter(code) {
             let start = caps.get(0).unwrap().start();
             let line_num = line_index.get_line(start);
-            
-            let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-            let args_str = caps.get(2).map(|m| m.as_str());
-            
-            // In Python, arguments in class definition are the parent classes
-            let extends = args_str.map(|s| s.to_string());
-            
-            classes.push(ClassInfo {
-                name,
-                extends,
-                implements: Vec::new(),
-                methods: Vec::new(),
-                properties: Vec::new(),
+            if exports.iter().any(|e| e.is_default && e.line_number == line_num) {
+                continue;
+            }
+
+            exports.push(ExportInfo {
+                name: "default".to_string(),
+                local_name: caps.get(1).map(|m| m.as_str().to_string()),
+                kind: "value".to_string(),
                 line_number: line_num,
+                is_default: true,
+                ..Default::default()
             });
         }
     }
-}
 
-/// Extract decorators from code
-#[napi]
-pub fn extract_decorators(code: String, language_id: String) -> Result<Vec<DecoratorInfo>> {
-    process_decorators(&code, &language_id)
-}
+    if let Some(list_re) = get_regex("ts_export_list") {
+        for caps in list_re.captures_iter(code) {
+            let start = caps.get(0).unwrap().start();
+            let line_num = line_index.get_line(start);
+            let is_type_only = caps.get(1).is_some();
+            let source = caps.get(3).map(|m| m.as_str().to_string());
 
-fn process_decorators(code: &str, language_id: &str) -> Result<Vec<DecoratorInfo>> {
-    let mut decorators = Vec::new();
-    
-    // Select appropriate regex pattern based on language
-    let decorator_re = match language_id {
-        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => get_regex("ts_decorator"),
-        "python" => get_regex("py_decorator"),
-        "cpp" | "c" => get_regex("cpp_attribute"),
-        "java" => get_regex("java_annotation"),
-        // For unsupported languages, return empty array (graceful fallback to Tree-sitter)
-        _ => return Ok(decorators),
-    };
-    
-    if let Some(re) = decorator_re {
-        let line_index = LineIndex::new(code);
-        for caps in re.captures_iter(code) {
-             let start = caps.get(0).unwrap().start();
-             let line_num = line_index.get_line(start);
+            for specifier in caps.get(2).map(|m| m.as_str()).unwrap_or("").split(',') {
+                let specifier = specifier.trim();
+                if specifier.is_empty() {
+                    continue;
+                }
+                let (specifier, inline_type) = match specifier.strip_prefix("type ") {
+                    Some(rest) => (rest.trim(), true),
+                    None => (specifier, false),
+                };
+                let (local, exported) = match specifier.split_once(" as ") {
+                    Some((local, exported)) => (local.trim(), exported.trim()),
+                    None => (specifier, specifier),
+                };
 
-             let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
-             let arguments = caps.get(2).map(|m| m.as_str().to_string());
+                exports.push(ExportInfo {
+                    name: exported.to_string(),
+                    local_name: (local != exported).then(|| local.to_string()),
+                    kind: if is_type_only || inline_type { "type" } else { "value" }.to_string(),
+                    source: source.clone(),
+                    line_number: line_num,
+                    is_default: exported == "default",
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    if let Some(star_re) = get_regex("ts_export_star") {
+        for caps in star_re.captures_iter(code) {
+            let start = caps.get(0).unwrap().start();
+            let alias = caps.get(1).map(|m| m.as_str().to_string());
+
+            exports.push(ExportInfo {
+                name: alias.clone().unwrap_or_else(|| "*".to_string()),
+                kind: "namespace".to_string(),
+                source: caps.get(2).map(|m| m.as_str().to_string()),
+                line_number: line_index.get_line(start),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+fn extract_commonjs_exports(src: &SourceFile, exports: &mut Vec<ExportInfo>) {
+    let code = src.code;
+    let line_index = src.line_index();
+
+    // `module.exports = { a, b: c, d() {} }` exposes each key, anything else is the default export
+    if let Some(module_re) = get_regex("cjs_module_exports") {
+        for m in module_re.find_iter(code) {
+            let line_num = line_index.get_line(m.start());
+            let rest = &code[m.end()..];
+
+            if rest.starts_with('{') {
+                for (key, local) in object_literal_keys(rest) {
+                    exports.push(ExportInfo {
+                        local_name: local.filter(|l| *l != key),
+                        name: key,
+                        kind: "value".to_string(),
+                        line_number: line_num,
+                        is_common_js: true,
+                        ..Default::default()
+                    });
+                }
+                continue;
+            }
+
+            let (kind, local_name) = commonjs_value_kind(rest);
+            exports.push(ExportInfo {
+                name: "default".to_string(),
+                local_name,
+                kind: kind.to_string(),
+                line_number: line_num,
+                is_default: true,
+                is_common_js: true,
+                ..Default::default()
+            });
+        }
+    }
+
+    if let Some(named_re) = get_regex("cjs_named_export") {
+        for caps in named_re.captures_iter(code) {
+            let m = caps.get(0).unwrap();
+            // After the `=`, as the character following it can be multi-byte
+            let rest = code[caps.get(2).unwrap().end()..].trim_start();
+            let (kind, local_name) = commonjs_value_kind(rest);
+            let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
+
+            exports.push(ExportInfo {
+                local_name: local_name.filter(|l| *l != name),
+                name,
+                kind: kind.to_string(),
+                line_number: line_index.get_line(m.start()),
+                is_common_js: true,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Classify the right-hand side of a CommonJS export assignment
+fn commonjs_value_kind(value: &str) -> (&'static str, Option<String>) {
+    let value = value.trim_start().trim_start_matches("async ").trim_start();
+    let first_line = value.lines().next().unwrap_or("");
+    if value.starts_with("function") || first_line.contains("=>") {
+        ("function", None)
+    } else if value.starts_with("class") {
+        ("class", None)
+    } else {
+        let ident: String = value.chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$').collect();
+        let is_reference = !ident.is_empty() && !value[ident.len()..].trim_start().starts_with(['(', '.']);
+        ("value", is_reference.then_some(ident))
+    }
+}
+
+/// Top-level keys of an object literal starting at `{`, paired with the local they reference
+fn object_literal_keys(literal: &str) -> Vec<(String, Option<String>)> {
+    let mut keys = Vec::new();
+    let mut depth = 0usize;
+    let mut entry_start = 1;
+    let mut body_end = literal.len();
+
+    for (i, c) in literal.char_indices() {
+        match c {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    body_end = i;
+                    break;
+                }
+            }
+            ',' if depth == 1 => {
+                keys.extend(object_literal_key(&literal[entry_start..i]));
+                entry_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if entry_start < body_end {
+        keys.extend(object_literal_key(&literal[entry_start..body_end]));
+    }
+
+    keys
+}
+
+fn object_literal_key(entry: &str) -> Option<(String, Option<String>)> {
+    let entry = entry.trim();
+    if entry.is_empty() || entry.starts_with("...") {
+        return None;
+    }
+
+    let key_end = entry.find([':', '(']).unwrap_or(entry.len());
+    let key = entry[..key_end].trim().trim_start_matches("async ").trim().trim_matches(['\'', '"']);
+    if key.is_empty() {
+        return None;
+    }
+
+    let local = match entry[key_end..].strip_prefix(':') {
+        Some(value) => {
+            let value = value.trim();
+            let is_identifier = value.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+                && value.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+            is_identifier.then(|| value.to_string())
+        }
+        None if key_end == entry.len() => Some(key.to_string()),
+        None => None,
+    };
+
+    Some((key.to_string(), local))
+}
+
+/// Extract functions from code
+#[napi]
+pub fn extract_functions(code: String, language_id: String) -> Result<Vec<FunctionInfo>> {
+    Ok(process_functions(&SourceFile::new(&code, &language_id)))
+}
+
+fn process_functions(src: &SourceFile) -> Vec<FunctionInfo> {
+    let mut functions = Vec::new();
+    
+    match src.language_id {
+        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
+            extract_ts_functions(src, &mut functions);
+        }
+        "python" => {
+            extract_py_functions(src, &mut functions);
+        }
+        "rust" => {
+            extract_rust_functions(src, &mut functions);
+        }
+        "go" => {
+            extract_go_functions(src, &mut functions);
+        }
+        "java" => {
+            extract_java_functions(src, &mut functions);
+        }
+        "csharp" => {
+            extract_csharp_functions(src, &mut functions);
+        }
+        "ruby" => {
+            extract_ruby_functions(src, &mut functions);
+        }
+        "php" => {
+            extract_php_functions(src, &mut functions);
+        }
+        "cpp" | "c" => {
+            extract_cpp_functions(src, &mut functions);
+        }
+        _ => {}
+    }
+    
+    functions
+}
+
+fn extract_ts_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+    let exported = ts_exported_names(src);
+
+    visit_named(tree.root_node(), &mut |node| {
+        let (function, name) = match node.kind() {
+            "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                (node, field_text(node, "name", code))
+            }
+            "variable_declarator" => {
+                let Some(value) = node.child_by_field_name("value") else { return };
+                if !matches!(value.kind(), "arrow_function" | "function" | "function_expression" | "generator_function") {
+                    return;
+                }
+                (value, field_text(node, "name", code))
+            }
+            // Object literal methods and interface signatures are not declarations of their own
+            "method_definition" | "abstract_method_signature"
+                if node.parent().is_some_and(|p| p.kind() == "class_body") =>
+            {
+                (node, field_text(node, "name", code))
+            }
+            _ => return,
+        };
+        let Some(name) = name else { return };
+
+        let mut info = ts_function_info(function, name, code);
+        info.line_number = node_line(node);
+        mark_ts_exported(&mut info, &exported);
+        functions.push(info);
+    });
+}
+
+/// Local names the file exports, whether at their declaration or in an
+/// `export { ... }` list anywhere in the file
+fn ts_exported_names(src: &SourceFile) -> HashSet<String> {
+    process_exports(src)
+        .into_iter()
+        .filter(|e| e.source.is_none())
+        .map(|e| e.local_name.unwrap_or(e.name))
+        .collect()
+}
+
+/// Methods count as exported when they are public and their class is exported
+fn mark_ts_exported(info: &mut FunctionInfo, exported: &HashSet<String>) {
+    info.is_exported = match &info.parent_class {
+        Some(class) => info.visibility == "public" && exported.contains(class),
+        None => exported.contains(&info.name),
+    };
+}
+
+/// Signature of any TypeScript or JavaScript function, method or arrow function
+fn ts_function_info(node: Node, name: String, code: &str) -> FunctionInfo {
+    let is_private_name = node
+        .child_by_field_name("name")
+        .is_some_and(|n| n.kind() == "private_property_identifier");
+    let mut info = FunctionInfo {
+        name,
+        line_number: node_line(node),
+        is_generator: node.kind().starts_with("generator_function"),
+        is_declaration: node.child_by_field_name("body").is_none(),
+        visibility: if is_private_name { "#private" } else { "public" }.to_string(),
+        ..Default::default()
+    };
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "accessibility_modifier" => info.visibility = node_text(child, code).to_string(),
+            "static" => info.is_static = true,
+            "async" => info.is_async = true,
+            "*" => {
+                info.is_generator = true;
+                continue;
+            }
+            "abstract" | "get" | "set" | "override_modifier" => {}
+            _ => continue,
+        }
+        info.modifiers.push(node_text(child, code).to_string());
+    }
+
+    info.parameters = if let Some(params) = node.child_by_field_name("parameters") {
+        ts_parameters(params, code)
+    } else if let Some(param) = node.child_by_field_name("parameter") {
+        // `x => x` has a single bare parameter
+        ts_parameters_from(&[param], code)
+    } else {
+        Vec::new()
+    };
+    info.return_type = field_text(node, "return_type", code).map(|t| t.trim_start_matches(':').trim().to_string());
+    info.parent_class = enclosing_type(node, code);
+    info.is_method = info.parent_class.is_some();
+    info
+}
+
+fn extract_py_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() == "function_definition" {
+            functions.push(py_function_info(node, code));
+        }
+    });
+}
+
+/// Signature of a Python `function_definition`, with its decorators as modifiers
+fn py_function_info(function: Node, code: &str) -> FunctionInfo {
+    let mut cursor = function.walk();
+    let is_async = function.children(&mut cursor).any(|c| c.kind() == "async");
+    let name = field_text(function, "name", code).unwrap_or_default();
+    let modifiers = py_decorators(function, code);
+    let parent_class = enclosing_type(function, code);
+    // Module-level functions are importable unless marked private by convention
+    let is_top_level = function
+        .parent()
+        .and_then(|p| if p.kind() == "decorated_definition" { p.parent() } else { Some(p) })
+        .is_some_and(|p| p.kind() == "module");
+
+    FunctionInfo {
+        parameters: function
+            .child_by_field_name("parameters")
+            .map(|p| py_parameters(p, code))
+            .unwrap_or_default(),
+        return_type: field_text(function, "return_type", code),
+        line_number: node_line(function),
+        is_async,
+        is_generator: function.child_by_field_name("body").is_some_and(py_contains_yield),
+        is_exported: is_top_level && !name.starts_with('_'),
+        is_static: modifiers.iter().any(|d| d == "staticmethod" || d == "classmethod"),
+        visibility: py_visibility(&name).to_string(),
+        is_method: parent_class.is_some(),
+        parent_class,
+        modifiers,
+        name,
+        ..Default::default()
+    }
+}
+
+/// Whether a function body yields, ignoring nested functions and classes
+fn py_contains_yield(node: Node) -> bool {
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).any(|child| match child.kind() {
+        "yield" => true,
+        "function_definition" | "lambda" | "class_definition" => false,
+        _ => py_contains_yield(child),
+    });
+    found
+}
+
+/// Decorators applied to a function or class definition, without the `@`
+fn py_decorators(definition: Node, code: &str) -> Vec<String> {
+    let Some(parent) = definition.parent().filter(|p| p.kind() == "decorated_definition") else {
+        return Vec::new();
+    };
+    let mut cursor = parent.walk();
+    let decorators = parent
+        .named_children(&mut cursor)
+        .filter(|d| d.kind() == "decorator")
+        .map(|d| node_text(d, code).trim_start_matches('@').trim().to_string())
+        .collect();
+    decorators
+}
+
+fn extract_rust_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "function_item" | "function_signature_item") {
+            return;
+        }
+
+        let mut cursor = node.walk();
+        let is_async = node
+            .children(&mut cursor)
+            .any(|c| c.kind() == "function_modifiers" && node_text(c, code).contains("async"));
+
+        let parameters: Vec<ParameterInfo> = node
+            .child_by_field_name("parameters")
+            .map(|params| {
+                let mut cursor = params.walk();
+                params
+                    .named_children(&mut cursor)
+                    .filter_map(|param| match param.kind() {
+                        "parameter" => Some(ParameterInfo {
+                            name: field_text(param, "pattern", code).unwrap_or_default(),
+                            param_type: field_text(param, "type", code),
+                            default_value: None,
+                            is_optional: false,
+                        }),
+                        "self_parameter" => Some(ParameterInfo {
+                            name: node_text(param, code).to_string(),
+                            param_type: None,
+                            default_value: None,
+                            is_optional: false,
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut cursor = node.walk();
+        let visibility = node
+            .children(&mut cursor)
+            .find(|c| c.kind() == "visibility_modifier")
+            .map(|v| node_text(v, code));
+        let parent_class = enclosing_type(node, code);
+        let in_trait = node
+            .parent()
+            .and_then(|p| p.parent())
+            .is_some_and(|p| p.kind() == "trait_item");
+        let visibility = match visibility {
+            Some("pub") => "public",
+            Some(_) => "internal",
+            // Trait items share the trait's visibility
+            None if in_trait => "public",
+            None => "private",
+        };
+        let has_self = parameters.iter().any(|p| p.param_type.is_none());
+
+        functions.push(FunctionInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            parameters,
+            return_type: field_text(node, "return_type", code),
+            line_number: node_line(node),
+            is_async,
+            is_generator: false,
+            is_declaration: node.kind() == "function_signature_item",
+            is_exported: visibility == "public",
+            // Associated functions without a receiver are called on the type
+            is_static: parent_class.is_some() && !has_self,
+            visibility: visibility.to_string(),
+            is_method: parent_class.is_some(),
+            parent_class,
+            ..Default::default()
+        });
+    });
+}
+
+fn extract_go_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "function_declaration" | "method_declaration") {
+            return;
+        }
+
+        let name = field_text(node, "name", code).unwrap_or_default();
+        let receiver = node
+            .child_by_field_name("receiver")
+            .and_then(|r| r.named_child(0))
+            .and_then(|r| field_text(r, "type", code));
+        // Capitalized names are exported from the package
+        let is_exported = name.starts_with(|c: char| c.is_uppercase());
+
+        functions.push(FunctionInfo {
+            parameters: node
+                .child_by_field_name("parameters")
+                .map(|params| go_parameters(params, code))
+                .unwrap_or_default(),
+            return_type: field_text(node, "result", code),
+            line_number: node_line(node),
+            is_exported,
+            visibility: if is_exported { "public" } else { "private" }.to_string(),
+            is_method: receiver.is_some(),
+            parent_class: receiver.as_deref().map(|r| {
+                let r = r.trim_start_matches('*');
+                r.split('[').next().unwrap_or(r).to_string()
+            }),
+            receiver,
+            name,
+            ..Default::default()
+        });
+    });
+}
+
+/// Expand a Go `parameter_list`, splitting grouped names like `a, b int`
+fn go_parameters(params: Node, code: &str) -> Vec<ParameterInfo> {
+    let mut parameters = Vec::new();
+    let mut cursor = params.walk();
+    for param in params.named_children(&mut cursor) {
+        let is_variadic = param.kind() == "variadic_parameter_declaration";
+        if !is_variadic && param.kind() != "parameter_declaration" {
+            continue;
+        }
+
+        let param_type = field_text(param, "type", code)
+            .map(|t| if is_variadic { format!("...{}", t) } else { t });
+        let mut name_cursor = param.walk();
+        let names: Vec<String> = param
+            .children_by_field_name("name", &mut name_cursor)
+            .map(|n| node_text(n, code).to_string())
+            .collect();
+
+        if names.is_empty() {
+            // Unnamed parameters such as `func(int, string)`
+            parameters.push(ParameterInfo {
+                name: String::new(),
+                param_type,
+                default_value: None,
+                is_optional: is_variadic,
+            });
+        } else {
+            for name in names {
+                parameters.push(ParameterInfo {
+                    name,
+                    param_type: param_type.clone(),
+                    default_value: None,
+                    is_optional: is_variadic,
+                });
+            }
+        }
+    }
+    parameters
+}
+
+fn extract_java_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "method_declaration" | "constructor_declaration") {
+            return;
+        }
+
+        let parameters = node
+            .child_by_field_name("parameters")
+            .map(|params| {
+                let mut cursor = params.walk();
+                params
+                    .named_children(&mut cursor)
+                    .filter_map(|param| match param.kind() {
+                        "formal_parameter" => Some(ParameterInfo {
+                            name: field_text(param, "name", code).unwrap_or_default(),
+                            param_type: field_text(param, "type", code),
+                            default_value: None,
+                            is_optional: false,
+                        }),
+                        // `String... rest` has no fields, only a type and a declarator
+                        "spread_parameter" => {
+                            let mut cursor = param.walk();
+                            let children: Vec<Node> = param
+                                .named_children(&mut cursor)
+                                .filter(|n| n.kind() != "modifiers")
+                                .collect();
+                            let name = children
+                                .iter()
+                                .find(|n| n.kind() == "variable_declarator")
+                                .and_then(|d| field_text(*d, "name", code))
+                                .unwrap_or_default();
+                            let param_type = children
+                                .first()
+                                .filter(|n| n.kind() != "variable_declarator")
+                                .map(|t| format!("{}...", node_text(*t, code)));
+                            Some(ParameterInfo {
+                                name,
+                                param_type,
+                                default_value: None,
+                                is_optional: true,
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let modifiers = java_modifiers(node, code);
+        let parent_class = enclosing_type(node, code);
+        // Interface members are implicitly public
+        let in_interface = node
+            .parent()
+            .and_then(|p| p.parent())
+            .is_some_and(|p| p.kind() == "interface_declaration");
+        let visibility = ["public", "protected", "private"]
+            .into_iter()
+            .find(|v| modifiers.iter().any(|m| m == v))
+            .unwrap_or(if in_interface { "public" } else { "package" });
+
+        functions.push(FunctionInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            parameters,
+            return_type: field_text(node, "type", code),
+            line_number: decl_line(node),
+            is_declaration: node.child_by_field_name("body").is_none(),
+            is_exported: visibility == "public",
+            is_static: modifiers.iter().any(|m| m == "static"),
+            visibility: visibility.to_string(),
+            is_method: parent_class.is_some(),
+            parent_class,
+            modifiers,
+            ..Default::default()
+        });
+    });
+}
+
+/// Keyword modifiers of a Java declaration, without its annotations
+fn java_modifiers(node: Node, code: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    let Some(modifiers) = node.children(&mut cursor).find(|n| n.kind() == "modifiers") else {
+        return Vec::new();
+    };
+
+    let mut cursor = modifiers.walk();
+    modifiers
+        .children(&mut cursor)
+        .filter(|n| !n.is_named())
+        .map(|n| node_text(n, code).to_string())
+        .collect()
+}
+
+fn extract_csharp_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "method_declaration" | "constructor_declaration" | "local_function_statement") {
+            return;
+        }
+
+        let modifiers = csharp_modifiers(node, code);
+        let parent_class = enclosing_type(node, code);
+        let in_interface = node
+            .parent()
+            .and_then(|p| p.parent())
+            .is_some_and(|p| p.kind() == "interface_declaration");
+        let visibility = ["public", "protected", "internal", "private"]
+            .into_iter()
+            .find(|v| modifiers.iter().any(|m| m == v))
+            .unwrap_or(if in_interface { "public" } else { "private" });
+
+        functions.push(FunctionInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            parameters: node
+                .child_by_field_name("parameters")
+                .map(|params| csharp_parameters(params, code))
+                .unwrap_or_default(),
+            return_type: field_text(node, "type", code),
+            line_number: decl_line(node),
+            is_async: modifiers.iter().any(|m| m == "async"),
+            is_exported: visibility == "public",
+            is_static: modifiers.iter().any(|m| m == "static"),
+            visibility: visibility.to_string(),
+            is_method: parent_class.is_some(),
+            parent_class,
+            modifiers,
+            ..Default::default()
+        });
+    });
+}
+
+fn csharp_modifiers(node: Node, code: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|n| n.kind() == "modifier")
+        .map(|n| node_text(n, code).to_string())
+        .collect()
+}
+
+fn csharp_parameters(params: Node, code: &str) -> Vec<ParameterInfo> {
+    let mut parameters = Vec::new();
+    // The grammar flattens `params T[] name` into the list itself as a type followed by a `name` field
+    let mut pending_type: Option<String> = None;
+
+    let mut cursor = params.walk();
+    for child in params.named_children(&mut cursor) {
+        if child.kind() == "parameter" {
+            let mut value_cursor = child.walk();
+            let default_value = child
+                .named_children(&mut value_cursor)
+                .find(|n| n.kind() == "equals_value_clause")
+                .map(|n| node_text(n, code).trim_start_matches('=').trim().to_string());
+            parameters.push(ParameterInfo {
+                name: field_text(child, "name", code).unwrap_or_default(),
+                param_type: field_text(child, "type", code),
+                is_optional: default_value.is_some(),
+                default_value,
+            });
+        } else if child.kind() == "identifier" && pending_type.is_some() {
+            parameters.push(ParameterInfo {
+                name: node_text(child, code).to_string(),
+                param_type: pending_type.take().map(|t| format!("params {}", t)),
+                default_value: None,
+                is_optional: true,
+            });
+        } else if child.kind() != "attribute_list" {
+            pending_type = Some(node_text(child, code).to_string());
+        }
+    }
+
+    parameters
+}
+
+fn extract_ruby_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let is_singleton = match node.kind() {
+            "method" => false,
+            "singleton_method" => true,
+            _ => return,
+        };
+
+        let parameters = node
+            .child_by_field_name("parameters")
+            .map(|params| {
+                let mut cursor = params.walk();
+                params
+                    .named_children(&mut cursor)
+                    .filter_map(|param| {
+                        let name = field_text(param, "name", code);
+                        let default_value = field_text(param, "value", code);
+                        let (name, is_optional) = match param.kind() {
+                            "identifier" => (node_text(param, code).to_string(), false),
+                            "optional_parameter" => (name?, true),
+                            "keyword_parameter" => (name?, default_value.is_some()),
+                            "splat_parameter" => (format!("*{}", name.unwrap_or_default()), true),
+                            "hash_splat_parameter" => (format!("**{}", name.unwrap_or_default()), true),
+                            "block_parameter" => (format!("&{}", name.unwrap_or_default()), true),
+                            _ => return None,
+                        };
+                        Some(ParameterInfo {
+                            name,
+                            param_type: None,
+                            default_value,
+                            is_optional,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let parent_class = enclosing_type(node, code);
+        functions.push(FunctionInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            parameters,
+            line_number: node_line(node),
+            // `def self.build` defines a class-level method
+            modifiers: if is_singleton { vec!["static".to_string()] } else { Vec::new() },
+            is_static: is_singleton,
+            visibility: "public".to_string(),
+            is_method: parent_class.is_some(),
+            parent_class,
+            ..Default::default()
+        });
+    });
+}
+
+fn extract_php_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "function_definition" | "method_declaration") {
+            return;
+        }
+
+        let parameters = node
+            .child_by_field_name("parameters")
+            .map(|params| {
+                let mut cursor = params.walk();
+                params
+                    .named_children(&mut cursor)
+                    .filter_map(|param| {
+                        let name = field_text(param, "name", code)?;
+                        let default_value = field_text(param, "default_value", code);
+                        let is_variadic = param.kind() == "variadic_parameter";
+                        Some(ParameterInfo {
+                            name: if is_variadic { format!("...{}", name) } else { name },
+                            param_type: field_text(param, "type", code),
+                            is_optional: is_variadic || default_value.is_some(),
+                            default_value,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut cursor = node.walk();
+        let modifiers: Vec<String> = node
+            .named_children(&mut cursor)
+            .filter(|n| n.kind().ends_with("_modifier"))
+            .map(|n| node_text(n, code).to_string())
+            .collect();
+        let visibility = ["public", "protected", "private"]
+            .into_iter()
+            .find(|v| modifiers.iter().any(|m| m == v))
+            .unwrap_or("public");
+        let parent_class = enclosing_type(node, code);
+
+        functions.push(FunctionInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            parameters,
+            return_type: field_text(node, "return_type", code),
+            line_number: decl_line(node),
+            is_declaration: node.child_by_field_name("body").is_none(),
+            is_exported: visibility == "public",
+            is_static: modifiers.iter().any(|m| m == "static"),
+            visibility: visibility.to_string(),
+            is_method: parent_class.is_some(),
+            parent_class,
+            modifiers,
+            ..Default::default()
+        });
+    });
+}
+
+fn extract_cpp_functions(src: &SourceFile, functions: &mut Vec<FunctionInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "function_declarator" {
+            return;
+        }
+        let Some((owner, pointer_suffix)) = cpp_declarator_owner(node) else { return };
+        // `void (*callback)(int);` declares a function pointer variable
+        if node.child_by_field_name("declarator").is_some_and(|d| d.kind() == "parenthesized_declarator") {
+            return;
+        }
+
+        let mut modifiers = Vec::new();
+        let mut cursor = owner.walk();
+        for child in owner.children(&mut cursor) {
+            match child.kind() {
+                "storage_class_specifier" | "virtual" | "virtual_function_specifier" | "explicit_function_specifier" => {
+                    modifiers.push(node_text(child, code).to_string())
+                }
+                _ => {}
+            }
+        }
+        let mut cursor = node.walk();
+        modifiers.extend(
+            node.children(&mut cursor)
+                .filter(|c| c.kind() == "type_qualifier")
+                .map(|c| node_text(c, code).to_string()),
+        );
+
+        let return_type = field_text(owner, "type", code).map(|t| format!("{}{}", t, pointer_suffix));
+
+        let parameters = node
+            .child_by_field_name("parameters")
+            .map(|params| {
+                let mut cursor = params.walk();
+                params
+                    .children(&mut cursor)
+                    .filter_map(|param| match param.kind() {
+                        // `int main(void)` takes no parameters
+                        "parameter_declaration" if param.child_by_field_name("declarator").is_none()
+                            && field_text(param, "type", code).as_deref() == Some("void") => None,
+                        "parameter_declaration" | "optional_parameter_declaration" => {
+                            let declarator = param.child_by_field_name("declarator");
+                            let (name, suffix) = declarator
+                                .map(|d| cpp_declarator_name(d, code))
+                                .unwrap_or_default();
+                            let default_value = field_text(param, "default_value", code);
+                            Some(ParameterInfo {
+                                name,
+                                param_type: field_text(param, "type", code).map(|t| format!("{}{}", t, suffix)),
+                                is_optional: default_value.is_some(),
+                                default_value,
+                            })
+                        }
+                        "variadic_parameter_declaration" | "..." => Some(ParameterInfo {
+                            name: "...".to_string(),
+                            param_type: None,
+                            default_value: None,
+                            is_optional: true,
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let name = field_text(node, "declarator", code).unwrap_or_default();
+        let is_static = modifiers.iter().any(|m| m == "static");
+        // Out-of-line definitions such as `int Foo::bar()` name their class
+        let parent_class = enclosing_type(owner, code)
+            .or_else(|| name.rsplit_once("::").map(|(class, _)| class.to_string()));
+        let visibility = match &parent_class {
+            Some(_) => cpp_member_access(owner, code),
+            // File-level `static` gives internal linkage
+            None if is_static => "internal",
+            None => "public",
+        };
+
+        functions.push(FunctionInfo {
+            parameters,
+            return_type,
+            line_number: node_line(node),
+            is_declaration: owner.kind() != "function_definition",
+            is_exported: visibility == "public",
+            is_static,
+            visibility: visibility.to_string(),
+            is_method: parent_class.is_some(),
+            parent_class,
+            modifiers,
+            name,
+            ..Default::default()
+        });
+    });
+}
+
+/// Access of a C++ class member from the closest preceding access specifier
+fn cpp_member_access(member: Node, code: &str) -> &'static str {
+    let mut sibling = member.prev_named_sibling();
+    while let Some(node) = sibling {
+        if node.kind() == "access_specifier" {
+            return match node_text(node, code).trim_end_matches(':').trim() {
+                "private" => "private",
+                "protected" => "protected",
+                _ => "public",
+            };
+        }
+        sibling = node.prev_named_sibling();
+    }
+    // Members default to private in classes and public in structs and unions
+    let in_class = member
+        .parent()
+        .and_then(|body| body.parent())
+        .is_some_and(|owner| owner.kind() == "class_specifier");
+    if in_class { "private" } else { "public" }
+}
+
+/// Find the declaration a function declarator belongs to, with the pointer/reference
+/// markers that wrap it (they belong to the return type)
+///
+/// Returns `None` for declarators nested in parameters or expressions, such as function pointers.
+fn cpp_declarator_owner(declarator: Node) -> Option<(Node, String)> {
+    let mut suffix = String::new();
+    let mut current = declarator.parent()?;
+    loop {
+        match current.kind() {
+            "pointer_declarator" => suffix.insert(0, '*'),
+            "reference_declarator" => suffix.insert(0, '&'),
+            "function_definition" | "declaration" | "field_declaration" => return Some((current, suffix)),
+            _ => return None,
+        }
+        current = current.parent()?;
+    }
+}
+
+/// Innermost identifier of a C/C++ declarator plus the pointer/reference/array markers around it
+fn cpp_declarator_name(declarator: Node, code: &str) -> (String, String) {
+    let mut suffix = String::new();
+    let mut current = declarator;
+    loop {
+        match current.kind() {
+            "pointer_declarator" => suffix.push('*'),
+            "reference_declarator" => suffix.push('&'),
+            "array_declarator" => suffix.push_str("[]"),
+            "identifier" | "field_identifier" | "type_identifier" => return (node_text(current, code).to_string(), suffix),
+            _ => {}
+        }
+        // Reference declarators keep their inner declarator as an unnamed child
+        let next = current
+            .child_by_field_name("declarator")
+            .or_else(|| current.named_child(current.named_child_count().saturating_sub(1)));
+        match next {
+            Some(next) if next != current => current = next,
+            _ => return (node_text(current, code).to_string(), suffix),
+        }
+    }
+}
+
+/// Extract classes from code
+#[napi]
+pub fn extract_classes(code: String, language_id: String) -> Result<Vec<ClassInfo>> {
+    Ok(process_classes(&SourceFile::new(&code, &language_id)))
+}
+
+fn process_classes(src: &SourceFile) -> Vec<ClassInfo> {
+    let mut classes = Vec::new();
+    
+    match src.language_id {
+        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
+            extract_ts_classes(src, &mut classes);
+        }
+        "python" => {
+            extract_py_classes(src, &mut classes);
+        }
+        "rust" => {
+            extract_rust_classes(src, &mut classes);
+        }
+        "go" => {
+            extract_go_classes(src, &mut classes);
+        }
+        "java" => {
+            extract_java_classes(src, &mut classes);
+        }
+        "csharp" => {
+            extract_csharp_classes(src, &mut classes);
+        }
+        "ruby" => {
+            extract_ruby_classes(src, &mut classes);
+        }
+        "php" => {
+            extract_php_classes(src, &mut classes);
+        }
+        "cpp" | "c" => {
+            extract_cpp_classes(src, &mut classes);
+        }
+        _ => {}
+    }
+    
+    classes
+}
+
+fn extract_ts_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "class_declaration" | "abstract_class_declaration" | "class") {
+            return;
+        }
+        // Class expressions take the name of the variable they are assigned to
+        let name = field_text(node, "name", code).or_else(|| {
+            node.parent()
+                .filter(|p| p.kind() == "variable_declarator")
+                .and_then(|p| field_text(p, "name", code))
+        });
+        let Some(name) = name else { return };
+
+        let mut class = ClassInfo {
+            name,
+            kind: "class".to_string(),
+            line_number: node_line(node),
+            ..Default::default()
+        };
+
+        let mut cursor = node.walk();
+        for heritage in node.named_children(&mut cursor).filter(|c| c.kind() == "class_heritage") {
+            let mut heritage_cursor = heritage.walk();
+            for clause in heritage.named_children(&mut heritage_cursor) {
+                match clause.kind() {
+                    "extends_clause" => class.extends = field_text(clause, "value", code),
+                    "implements_clause" => {
+                        let mut clause_cursor = clause.walk();
+                        class.implements.extend(
+                            clause.named_children(&mut clause_cursor).map(|t| node_text(t, code).to_string()),
+                        );
+                    }
+                    // The JavaScript grammar has no extends clause node
+                    _ => class.extends = Some(node_text(clause, code).to_string()),
+                }
+            }
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                ts_class_member(member, code, &mut class.members);
+            }
+        }
+
+        for member in &class.members {
+            if member.kind == "property" {
+                class.properties.push(member.name.clone());
+            } else if !class.methods.contains(&member.name) {
+                class.methods.push(member.name.clone());
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+fn ts_class_member(node: Node, code: &str, members: &mut Vec<MemberInfo>) {
+    let is_method = match node.kind() {
+        "method_definition" | "method_signature" | "abstract_method_signature" => true,
+        "public_field_definition" | "field_definition" => false,
+        _ => return,
+    };
+    let Some(name_node) = node.child_by_field_name("name").or_else(|| node.child_by_field_name("property")) else {
+        return;
+    };
+    let name = node_text(name_node, code).to_string();
+
+    let mut member = MemberInfo {
+        visibility: if name_node.kind() == "private_property_identifier" { "#private" } else { "public" }.to_string(),
+        is_abstract: node.kind() == "abstract_method_signature",
+        line_number: node_line(node),
+        ..Default::default()
+    };
+    let mut accessor = None;
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "accessibility_modifier" => member.visibility = node_text(child, code).to_string(),
+            "static" => member.is_static = true,
+            "abstract" => member.is_abstract = true,
+            "readonly" => member.is_readonly = true,
+            "?" => member.is_optional = true,
+            "get" | "set" => accessor = Some(child.kind()),
+            _ => {}
+        }
+    }
+
+    member.member_type = field_text(node, "type", code)
+        .or_else(|| field_text(node, "return_type", code))
+        .map(|t| t.trim_start_matches(':').trim().to_string());
+
+    if !is_method {
+        member.name = name;
+        member.kind = "property".to_string();
+        members.push(member);
+        return;
+    }
+
+    member.kind = match accessor {
+        Some("get") => "getter",
+        Some("set") => "setter",
+        _ if name == "constructor" => "constructor",
+        _ => "method",
+    }
+    .to_string();
+
+    if member.kind == "constructor" {
+        let parameters = node.child_by_field_name("parameters");
+        members.extend(parameters.iter().flat_map(|p| ts_parameter_properties(*p, code)));
+    }
+
+    member.signature = Some(ts_function_info(node, name.clone(), code));
+    member.name = name;
+    members.push(member);
+}
+
+/// Parameters of a TypeScript or JavaScript `formal_parameters` node
+fn ts_parameters(params: Node, code: &str) -> Vec<ParameterInfo> {
+    let mut cursor = params.walk();
+    let params: Vec<Node> = params.named_children(&mut cursor).collect();
+    ts_parameters_from(&params, code)
+}
+
+fn ts_parameters_from(params: &[Node], code: &str) -> Vec<ParameterInfo> {
+    params
+        .iter()
+        .filter(|p| p.kind() != "comment")
+        .map(|&param| {
+            // JavaScript patterns appear directly; TypeScript wraps them with their type
+            let (pattern, default_value) = match param.kind() {
+                "required_parameter" | "optional_parameter" => {
+                    (param.child_by_field_name("pattern"), field_text(param, "value", code))
+                }
+                "assignment_pattern" => (param.child_by_field_name("left"), field_text(param, "right", code)),
+                _ => (Some(param), None),
+            };
+            ParameterInfo {
+                name: pattern.map(|p| node_text(p, code).to_string()).unwrap_or_default(),
+                param_type: field_text(param, "type", code).map(|t| t.trim_start_matches(':').trim().to_string()),
+                is_optional: param.kind() == "optional_parameter" || default_value.is_some(),
+                default_value,
+            }
+        })
+        .collect()
+}
+
+/// Properties declared through constructor parameters with an accessibility or `readonly` modifier
+fn ts_parameter_properties(params: Node, code: &str) -> Vec<MemberInfo> {
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter_map(|param| {
+            let mut member = MemberInfo {
+                kind: "property".to_string(),
+                visibility: "public".to_string(),
+                is_optional: param.kind() == "optional_parameter",
+                is_parameter_property: true,
+                line_number: node_line(param),
+                ..Default::default()
+            };
+            let mut is_property = false;
+            let mut cursor = param.walk();
+            for child in param.children(&mut cursor) {
+                match child.kind() {
+                    "accessibility_modifier" => member.visibility = node_text(child, code).to_string(),
+                    "readonly" => member.is_readonly = true,
+                    _ => continue,
+                }
+                is_property = true;
+            }
+            if !is_property {
+                return None;
+            }
+            member.name = field_text(param, "pattern", code)?;
+            member.member_type = field_text(param, "type", code).map(|t| t.trim_start_matches(':').trim().to_string());
+            Some(member)
+        })
+        .collect()
+}
+
+fn extract_py_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "class_definition" {
+            return;
+        }
+
+        let mut class = ClassInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            kind: "class".to_string(),
+            // In Python, arguments in class definition are the parent classes
+            extends: field_text(node, "superclasses", code)
+                .map(|s| s.trim_start_matches('(').trim_end_matches(')').trim().to_string())
+                .filter(|s| !s.is_empty()),
+            line_number: node_line(node),
+            ..Default::default()
+        };
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for statement in body.named_children(&mut cursor) {
+                py_class_member(statement, code, &mut class.members);
+            }
+        }
+
+        let dataclass = py_decorators(node, code).into_iter().find(|d| {
+            let name = d.split('(').next().unwrap_or_default();
+            name == "dataclass" || name.ends_with(".dataclass")
+        });
+        if let (Some(dataclass), Some(body)) = (dataclass, node.child_by_field_name("body")) {
+            py_dataclass_fields(&dataclass, body, code, &mut class);
+        }
+
+        for member in &class.members {
+            let names = if member.kind == "property" { &mut class.properties } else { &mut class.methods };
+            if !names.contains(&member.name) {
+                names.push(member.name.clone());
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+fn py_class_member(node: Node, code: &str, members: &mut Vec<MemberInfo>) {
+    match node.kind() {
+        "expression_statement" => {
+            let Some(assignment) = node.named_child(0).filter(|a| a.kind() == "assignment") else { return };
+            let Some(left) = assignment.child_by_field_name("left").filter(|l| l.kind() == "identifier") else {
+                return;
+            };
+            let name = node_text(left, code).to_string();
+            members.push(MemberInfo {
+                visibility: py_visibility(&name).to_string(),
+                name,
+                kind: "property".to_string(),
+                // Class-level attributes are shared by all instances
+                is_static: true,
+                member_type: field_text(assignment, "type", code),
+                line_number: node_line(node),
+                ..Default::default()
+            });
+        }
+        "function_definition" | "decorated_definition" => {
+            let function = if node.kind() == "decorated_definition" {
+                node.child_by_field_name("definition")
+            } else {
+                Some(node)
+            };
+            let Some(function) = function.filter(|f| f.kind() == "function_definition") else { return };
+            let signature = py_function_info(function, code);
+            let decorators = &signature.modifiers;
+            let name = signature.name.clone();
+            let has_decorator = |target: &str| {
+                decorators.iter().any(|d| d == target || d.rsplit('.').next() == Some(target))
+            };
+
+            let kind = if name == "__init__" {
+                "constructor"
+            } else if has_decorator("property") {
+                "getter"
+            } else if decorators.iter().any(|d| d.ends_with(".setter")) {
+                "setter"
+            } else {
+                "method"
+            };
+
+            if kind == "constructor" {
+                py_instance_attributes(function, code, members);
+            }
+
+            members.push(MemberInfo {
+                visibility: py_visibility(&name).to_string(),
+                kind: kind.to_string(),
+                is_static: has_decorator("staticmethod") || has_decorator("classmethod"),
+                is_abstract: has_decorator("abstractmethod"),
+                member_type: signature.return_type.clone(),
+                line_number: signature.line_number,
+                signature: Some(signature),
+                name,
+                ..Default::default()
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Turn the annotated class attributes of a `@dataclass` into instance fields
+/// and synthesize its `__init__` unless the class defines one
+fn py_dataclass_fields(decorator: &str, body: Node, code: &str, class: &mut ClassInfo) {
+    let frozen = decorator.replace(' ', "").contains("frozen=True");
+    let mut parameters = Vec::new();
+
+    let mut cursor = body.walk();
+    for statement in body.named_children(&mut cursor) {
+        let Some(assignment) = statement.named_child(0).filter(|a| a.kind() == "assignment") else { continue };
+        let (Some(name), Some(field_type)) = (field_text(assignment, "left", code), field_text(assignment, "type", code)) else {
+            continue;
+        };
+        if field_type.starts_with("ClassVar") {
+            continue;
+        }
+        let default_value = field_text(assignment, "right", code);
+        if let Some(member) = class.members.iter_mut().find(|m| m.kind == "property" && m.name == name) {
+            member.is_static = false;
+            member.is_readonly = frozen;
+            member.is_optional = default_value.is_some();
+        }
+        // `field(init=False)` keeps the attribute out of the constructor
+        let excluded = default_value
+            .as_deref()
+            .is_some_and(|d| d.starts_with("field(") && d.replace(' ', "").contains("init=False"));
+        if !excluded {
+            parameters.push(ParameterInfo {
+                name,
+                is_optional: default_value.is_some(),
+                param_type: Some(field_type),
+                default_value,
+            });
+        }
+    }
+
+    if class.members.iter().any(|m| m.kind == "constructor") {
+        return;
+    }
+    class.members.push(MemberInfo {
+        name: "__init__".to_string(),
+        kind: "constructor".to_string(),
+        visibility: "public".to_string(),
+        signature: Some(FunctionInfo {
+            name: "__init__".to_string(),
+            parameters,
+            return_type: Some("None".to_string()),
+            line_number: class.line_number,
+            ..Default::default()
+        }),
+        line_number: class.line_number,
+        ..Default::default()
+    });
+}
+
+/// Attributes assigned on `self` inside `__init__`
+fn py_instance_attributes(init: Node, code: &str, members: &mut Vec<MemberInfo>) {
+    let Some(body) = init.child_by_field_name("body") else { return };
+    visit_named(body, &mut |node| {
+        if node.kind() != "assignment" {
+            return;
+        }
+        let Some(left) = node.child_by_field_name("left").filter(|l| l.kind() == "attribute") else { return };
+        if field_text(left, "object", code).as_deref() != Some("self") {
+            return;
+        }
+        let Some(name) = field_text(left, "attribute", code) else { return };
+        if members.iter().any(|m| m.name == name) {
+            return;
+        }
+        members.push(MemberInfo {
+            visibility: py_visibility(&name).to_string(),
+            name,
+            kind: "property".to_string(),
+            member_type: field_text(node, "type", code),
+            line_number: node_line(node),
+            ..Default::default()
+        });
+    });
+}
+
+/// Python naming convention: `__name` is private, `_name` protected, dunder methods public
+fn py_visibility(name: &str) -> &'static str {
+    if name.starts_with("__") && !name.ends_with("__") {
+        "private"
+    } else if name.starts_with('_') && !name.ends_with("__") {
+        "protected"
+    } else {
+        "public"
+    }
+}
+
+/// Parameters of a Python `parameters` node
+fn py_parameters(params: Node, code: &str) -> Vec<ParameterInfo> {
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter(|p| p.kind() != "comment")
+        .map(|param| {
+            let name = match param.kind() {
+                "default_parameter" | "typed_default_parameter" => field_text(param, "name", code),
+                "typed_parameter" => param.named_child(0).map(|n| node_text(n, code).to_string()),
+                _ => None,
+            }
+            .unwrap_or_else(|| node_text(param, code).to_string());
+            let default_value = field_text(param, "value", code);
+            let param_type = field_text(param, "type", code);
+            // `*args` and `**kwargs` may be left empty
+            let is_optional = default_value.is_some()
+                || name.starts_with('*')
+                || param_type.as_deref().is_some_and(py_is_optional_type);
+            ParameterInfo {
+                name,
+                param_type,
+                default_value,
+                is_optional,
+            }
+        })
+        .collect()
+}
+
+/// Whether an annotation admits `None`: `Optional[T]`, `Union[T, None]` or `T | None`
+fn py_is_optional_type(annotation: &str) -> bool {
+    let annotation = annotation.replace(' ', "");
+    let base = annotation.split('[').next().unwrap_or_default();
+    if base == "Optional" || base.ends_with(".Optional") {
+        return true;
+    }
+    if base == "Union" || base.ends_with(".Union") {
+        return annotation.contains(",None]") || annotation.contains("[None,");
+    }
+    // Only top-level `|` unions count, not ones nested in a generic argument
+    let mut depth = 0;
+    let mut member_start = 0;
+    for (i, ch) in annotation.char_indices() {
+        match ch {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '|' if depth == 0 => {
+                if &annotation[member_start..i] == "None" {
+                    return true;
+                }
+                member_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    member_start > 0 && &annotation[member_start..] == "None"
+}
+
+fn extract_rust_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    // Names of the named children of a body node, optionally filtered by kind
+    let member_names = |body: Option<Node>, kinds: &[&str]| -> Vec<String> {
+        let Some(body) = body else { return Vec::new() };
+        let mut cursor = body.walk();
+        body.named_children(&mut cursor)
+            .filter(|n| kinds.contains(&n.kind()))
+            .filter_map(|n| field_text(n, "name", code))
+            .collect()
+    };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let body = node.child_by_field_name("body");
+        let class = match node.kind() {
+            "struct_item" | "union_item" => ClassInfo {
+                name: field_text(node, "name", code).unwrap_or_default(),
+                kind: "struct".to_string(),
+                properties: member_names(body, &["field_declaration"]),
+                ..Default::default()
+            },
+            "enum_item" => ClassInfo {
+                name: field_text(node, "name", code).unwrap_or_default(),
+                kind: "enum".to_string(),
+                properties: member_names(body, &["enum_variant"]),
+                ..Default::default()
+            },
+            "trait_item" => ClassInfo {
+                name: field_text(node, "name", code).unwrap_or_default(),
+                kind: "trait".to_string(),
+                extends: field_text(node, "bounds", code)
+                    .map(|b| b.trim_start_matches(':').trim().to_string()),
+                methods: member_names(body, &["function_item", "function_signature_item"]),
+                ..Default::default()
+            },
+            "impl_item" => {
+                // `impl<T> Trait for Type<T>` is reported under the bare type name
+                let type_node = node.child_by_field_name("type");
+                let name = type_node
+                    .map(|t| t.child_by_field_name("type").unwrap_or(t))
+                    .map(|t| node_text(t, code).to_string())
+                    .unwrap_or_default();
+                ClassInfo {
+                    name,
+                    kind: "impl".to_string(),
+                    implements: field_text(node, "trait", code).into_iter().collect(),
+                    methods: member_names(body, &["function_item"]),
+                    properties: member_names(body, &["const_item", "type_item"]),
+                    ..Default::default()
+                }
+            }
+            _ => return,
+        };
+
+        classes.push(ClassInfo {
+            line_number: node_line(node),
+            ..class
+        });
+    });
+}
+
+fn extract_go_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    let mut methods_by_type: HashMap<String, Vec<String>> = HashMap::new();
+    let first_class = classes.len();
+
+    visit_named(tree.root_node(), &mut |node| {
+        match node.kind() {
+            "type_spec" => {
+                let Some(type_node) = node.child_by_field_name("type") else { return };
+                let name = field_text(node, "name", code).unwrap_or_default();
+                let mut class = ClassInfo {
+                    name,
+                    line_number: node_line(node),
+                    ..Default::default()
+                };
+
+                let mut cursor = type_node.walk();
+                match type_node.kind() {
+                    "struct_type" => {
+                        class.kind = "struct".to_string();
+                        let Some(fields) = type_node.named_child(0) else { return };
+                        let mut field_cursor = fields.walk();
+                        for field in fields.named_children(&mut field_cursor) {
+                            let mut name_cursor = field.walk();
+                            let names: Vec<String> = field
+                                .children_by_field_name("name", &mut name_cursor)
+                                .map(|n| node_text(n, code).to_string())
+                                .collect();
+                            if names.is_empty() {
+                                // Embedded fields are addressed by their type name
+                                if let Some(embedded) = field.child_by_field_name("type") {
+                                    let embedded = embedded.child_by_field_name("name").unwrap_or(embedded);
+                                    class.properties.push(node_text(embedded, code).trim_start_matches('*').to_string());
+                                }
+                            } else {
+                                class.properties.extend(names);
+                            }
+                        }
+                    }
+                    "interface_type" => {
+                        class.kind = "interface".to_string();
+                        for member in type_node.named_children(&mut cursor) {
+                            match member.kind() {
+                                "method_spec" => class.methods.extend(field_text(member, "name", code)),
+                                _ => class.implements.push(node_text(member, code).to_string()),
+                            }
+                        }
+                    }
+                    _ => return,
+                }
+
+                classes.push(class);
+            }
+            "method_declaration" => {
+                let receiver_type = node
+                    .child_by_field_name("receiver")
+                    .and_then(|r| r.named_child(0))
+                    .and_then(|r| field_text(r, "type", code));
+                if let (Some(receiver_type), Some(name)) = (receiver_type, field_text(node, "name", code)) {
+                    let base = receiver_type.trim_start_matches('*');
+                    let base = base.split('[').next().unwrap_or(base).to_string();
+                    methods_by_type.entry(base).or_default().push(name);
+                }
+            }
+            _ => {}
+        }
+    });
+
+    // Methods are declared outside the struct, so attach them by receiver type
+    for class in &mut classes[first_class..] {
+        if let Some(methods) = methods_by_type.remove(&class.name) {
+            class.methods.extend(methods);
+        }
+    }
+}
+
+fn extract_java_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let kind = match node.kind() {
+            "class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "record_declaration" => "record",
+            "enum_declaration" => "enum",
+            "annotation_type_declaration" => "annotation",
+            _ => return,
+        };
+
+        let mut class = ClassInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            kind: kind.to_string(),
+            extends: node
+                .child_by_field_name("superclass")
+                .and_then(|s| s.named_child(0))
+                .map(|t| node_text(t, code).to_string()),
+            line_number: decl_line(node),
+            ..Default::default()
+        };
+
+        // `implements` for classes and records, `extends` for interfaces
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if matches!(child.kind(), "super_interfaces" | "extends_interfaces") {
+                if let Some(types) = child.named_child(0) {
+                    let mut type_cursor = types.walk();
+                    class.implements.extend(types.named_children(&mut type_cursor).map(|t| node_text(t, code).to_string()));
+                }
+            }
+        }
+
+        // Record components are its properties
+        if let Some(params) = node.child_by_field_name("parameters") {
+            let mut cursor = params.walk();
+            class.properties.extend(params.named_children(&mut cursor).filter_map(|p| field_text(p, "name", code)));
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut members: Vec<Node> = Vec::new();
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                if member.kind() == "enum_body_declarations" {
+                    let mut inner = member.walk();
+                    members.extend(member.named_children(&mut inner));
+                } else {
+                    members.push(member);
+                }
+            }
+
+            for member in members {
+                match member.kind() {
+                    "method_declaration" | "constructor_declaration" => class.methods.extend(field_text(member, "name", code)),
+                    "enum_constant" => class.properties.extend(field_text(member, "name", code)),
+                    "field_declaration" | "constant_declaration" => {
+                        let mut cursor = member.walk();
+                        class.properties.extend(
+                            member
+                                .children_by_field_name("declarator", &mut cursor)
+                                .filter_map(|d| field_text(d, "name", code)),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+fn extract_csharp_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let kind = match node.kind() {
+            "class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "record_declaration" | "record_struct_declaration" => "record",
+            "struct_declaration" => "struct",
+            "enum_declaration" => "enum",
+            _ => return,
+        };
+
+        let mut class = ClassInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            kind: kind.to_string(),
+            line_number: decl_line(node),
+            ..Default::default()
+        };
+
+        // C# mixes the base class and interfaces in one list; by convention interfaces are `IName`
+        if let Some(bases) = node.child_by_field_name("bases") {
+            let mut cursor = bases.walk();
+            for base in bases.named_children(&mut cursor) {
+                let base = node_text(base, code).to_string();
+                let looks_like_interface = base.len() > 1
+                    && base.starts_with('I')
+                    && base[1..].starts_with(|c: char| c.is_ascii_uppercase());
+                if kind == "class" && class.extends.is_none() && class.implements.is_empty() && !looks_like_interface {
+                    class.extends = Some(base);
+                } else {
+                    class.implements.push(base);
+                }
+            }
+        }
+
+        if let Some(params) = node.child_by_field_name("parameters") {
+            class.properties.extend(csharp_parameters(params, code).into_iter().map(|p| p.name));
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                match member.kind() {
+                    "method_declaration" | "constructor_declaration" => class.methods.extend(field_text(member, "name", code)),
+                    "property_declaration" | "enum_member_declaration" | "event_declaration" => {
+                        class.properties.extend(field_text(member, "name", code))
+                    }
+                    "field_declaration" | "event_field_declaration" => {
+                        let mut names = Vec::new();
+                        visit_named(member, &mut |n| {
+                            if n.kind() == "variable_declarator" {
+                                if let Some(name) = n.child_by_field_name("name").or_else(|| n.named_child(0)) {
+                                    names.push(node_text(name, code).to_string());
+                                }
+                            }
+                        });
+                        class.properties.extend(names);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+fn extract_ruby_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let kind = match node.kind() {
+            "class" => "class",
+            "module" => "module",
+            _ => return,
+        };
+
+        let mut class = ClassInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            kind: kind.to_string(),
+            extends: node
+                .child_by_field_name("superclass")
+                .and_then(|s| s.named_child(0))
+                .map(|s| node_text(s, code).to_string()),
+            line_number: node_line(node),
+            ..Default::default()
+        };
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                match member.kind() {
+                    "method" | "singleton_method" => class.methods.extend(field_text(member, "name", code)),
+                    "call" if member.child_by_field_name("receiver").is_none() => {
+                        let method = field_text(member, "method", code).unwrap_or_default();
+                        let Some(args) = member.child_by_field_name("arguments") else { continue };
+                        let mut arg_cursor = args.walk();
+                        let values = args.named_children(&mut arg_cursor).map(|a| node_text(a, code).trim_start_matches(':').to_string());
+                        match method.as_str() {
+                            "attr_accessor" | "attr_reader" | "attr_writer" => class.properties.extend(values),
+                            // Mixins are the closest Ruby has to implemented interfaces
+                            "include" | "extend" | "prepend" => class.implements.extend(values),
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+fn extract_php_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let kind = match node.kind() {
+            "class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "trait_declaration" => "trait",
+            "enum_declaration" => "enum",
+            _ => return,
+        };
+
+        let mut class = ClassInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            kind: kind.to_string(),
+            line_number: decl_line(node),
+            ..Default::default()
+        };
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let mut name_cursor = child.walk();
+            let names = child.named_children(&mut name_cursor).map(|n| node_text(n, code).to_string());
+            match child.kind() {
+                // Interfaces extend other interfaces through the same clause
+                "base_clause" if kind == "interface" => class.implements.extend(names),
+                "base_clause" => class.extends = names.into_iter().next(),
+                "class_interface_clause" => class.implements.extend(names),
+                _ => {}
+            }
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                match member.kind() {
+                    "method_declaration" => {
+                        class.methods.extend(field_text(member, "name", code));
+                        // Constructor property promotion declares properties too
+                        if let Some(params) = member.child_by_field_name("parameters") {
+                            let mut param_cursor = params.walk();
+                            class.properties.extend(
+                                params
+                                    .named_children(&mut param_cursor)
+                                    .filter(|p| p.kind() == "property_promotion_parameter")
+                                    .filter_map(|p| field_text(p, "name", code)),
+                            );
+                        }
+                    }
+                    "property_declaration" => {
+                        let mut names = Vec::new();
+                        visit_named(member, &mut |n| {
+                            if n.kind() == "property_element" {
+                                if let Some(variable) = n.named_child(0) {
+                                    names.push(node_text(variable, code).to_string());
+                                }
+                            }
+                        });
+                        class.properties.extend(names);
+                    }
+                    "enum_case" => class.properties.extend(field_text(member, "name", code)),
+                    "use_declaration" => {
+                        let mut use_cursor = member.walk();
+                        class.implements.extend(member.named_children(&mut use_cursor).map(|n| node_text(n, code).to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+fn extract_cpp_classes(src: &SourceFile, classes: &mut Vec<ClassInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let kind = match node.kind() {
+            "struct_specifier" => "struct",
+            "class_specifier" => "class",
+            "union_specifier" => "union",
+            "enum_specifier" => "enum",
+            _ => return,
+        };
+        // `struct Point p;` only references the type
+        let Some(body) = node.child_by_field_name("body") else { return };
+
+        // `typedef struct { ... } Name;` takes its name from the typedef
+        let name = field_text(node, "name", code).or_else(|| {
+            node.parent()
+                .filter(|p| p.kind() == "type_definition")
+                .and_then(|p| field_text(p, "declarator", code))
+        });
+
+        let mut class = ClassInfo {
+            name: name.unwrap_or_default(),
+            kind: kind.to_string(),
+            line_number: node_line(node),
+            ..Default::default()
+        };
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "base_class_clause" {
+                let mut base_cursor = child.walk();
+                for base in child.named_children(&mut base_cursor).filter(|b| b.kind() != "access_specifier") {
+                    let base = node_text(base, code).to_string();
+                    if class.extends.is_none() {
+                        class.extends = Some(base);
+                    } else {
+                        class.implements.push(base);
+                    }
+                }
+            }
+        }
+
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            match member.kind() {
+                "enumerator" => class.properties.extend(field_text(member, "name", code)),
+                "field_declaration" | "declaration" | "function_definition" | "template_declaration" => {
+                    let mut function_names = Vec::new();
+                    visit_named(member, &mut |n| {
+                        if n.kind() == "function_declarator" && cpp_declarator_owner(n).is_some() {
+                            function_names.extend(field_text(n, "declarator", code));
+                        }
+                    });
+                    if !function_names.is_empty() {
+                        class.methods.extend(function_names);
+                        continue;
+                    }
+                    let mut declarator_cursor = member.walk();
+                    class.properties.extend(
+                        member
+                            .children_by_field_name("declarator", &mut declarator_cursor)
+                            .map(|d| cpp_declarator_name(d, code).0),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        classes.push(class);
+    });
+}
+
+/// Extract decorators from code
+#[napi]
+pub fn extract_decorators(code: String, language_id: String) -> Result<Vec<DecoratorInfo>> {
+    process_decorators(&SourceFile::new(&code, &language_id))
+}
+
+fn process_decorators(src: &SourceFile) -> Result<Vec<DecoratorInfo>> {
+    let mut decorators = Vec::new();
+    
+    // Select appropriate regex pattern based on language
+    let code = src.code;
+    let decorator_re = match src.language_id {
+        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => get_regex("ts_decorator"),
+        "python" => get_regex("py_decorator"),
+        "cpp" | "c" => get_regex("cpp_attribute"),
+        "java" => {
+            extract_java_annotations(src, &mut decorators);
+            return Ok(decorators);
+        }
+        "csharp" => {
+            extract_csharp_attributes(src, &mut decorators);
+            return Ok(decorators);
+        }
+        "rust" => {
+            extract_rust_attributes(src, &mut decorators);
+            return Ok(decorators);
+        }
+        // For unsupported languages, return empty array (graceful fallback to Tree-sitter)
+        _ => return Ok(decorators),
+    };
+    
+    if let Some(re) = decorator_re {
+        let line_index = src.line_index();
+        let targets = decorator_targets(src);
+        for caps in re.captures_iter(code) {
+             let start = caps.get(0).unwrap().start();
+             let line_num = line_index.get_line(start);
+
+             let name = caps.get(1).map(|m| m.as_str().to_string()).unwrap_or_default();
+             let arguments = caps.get(2).map(|m| m.as_str().to_string());
              
              decorators.push(DecoratorInfo {
                 name,
                 arguments,
                 line_number: line_num,
-                target: "unknown".to_string(), // Would need context to determine
+                target: targets.get(&start).copied().unwrap_or("unknown").to_string(),
             });
         }
     }
-    
-    Ok(decorators)
+    
+    Ok(decorators)
+}
+
+/// Map each decorator's start offset to the kind of declaration it decorates
+fn decorator_targets(src: &SourceFile) -> HashMap<usize, &'static str> {
+    let mut targets = HashMap::new();
+    if !matches!(src.language_id, "typescript" | "typescriptreact" | "javascript" | "javascriptreact" | "python") {
+        return targets;
+    }
+    let Some(tree) = src.tree() else { return targets };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "decorator" {
+            return;
+        }
+        let Some(parent) = node.parent() else { return };
+
+        let target = match parent.kind() {
+            // Python wraps the decorators and their definition together
+            "decorated_definition" => match parent.child_by_field_name("definition").map(|d| d.kind()) {
+                Some("class_definition") => Some("class"),
+                Some("function_definition") => {
+                    let in_class = parent
+                        .parent()
+                        .and_then(|block| block.parent())
+                        .is_some_and(|owner| owner.kind() == "class_definition");
+                    Some(if in_class { "method" } else { "function" })
+                }
+                _ => None,
+            },
+            // `@Dec export class A {}` hangs the decorator on the export
+            "export_statement" => parent
+                .child_by_field_name("declaration")
+                .and_then(|d| ts_decorated_kind(d.kind())),
+            // Method decorators precede the method as siblings in the class body
+            "class_body" => {
+                let mut next = node.next_named_sibling();
+                while let Some(sibling) = next.filter(|s| s.kind() == "decorator") {
+                    next = sibling.next_named_sibling();
+                }
+                next.and_then(|d| ts_decorated_kind(d.kind()))
+            }
+            kind => ts_decorated_kind(kind),
+        };
+
+        if let Some(target) = target {
+            targets.insert(node.start_byte(), target);
+        }
+    });
+
+    targets
+}
+
+fn ts_decorated_kind(kind: &str) -> Option<&'static str> {
+    match kind {
+        "class_declaration" | "abstract_class_declaration" | "class" => Some("class"),
+        "method_definition" | "abstract_method_signature" | "method_signature" => Some("method"),
+        "public_field_definition" | "field_definition" => Some("property"),
+        "required_parameter" | "optional_parameter" => Some("parameter"),
+        _ => None,
+    }
+}
+
+
+
+fn extract_rust_attributes(src: &SourceFile, decorators: &mut Vec<DecoratorInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let is_inner = match node.kind() {
+            "attribute_item" => false,
+            "inner_attribute_item" => true,
+            // The grammar reads an inner attribute on the first line as a shebang
+            "shebang" => {
+                let text = node_text(node, code);
+                if let Some(attr) = text.strip_prefix("#![").and_then(|t| t.strip_suffix(']')) {
+                    let (name, arguments) = match attr.split_once('(') {
+                        Some((name, args)) => (name, Some(args.trim_end_matches(')').to_string())),
+                        None => (attr, None),
+                    };
+                    decorators.push(DecoratorInfo {
+                        name: name.trim().to_string(),
+                        arguments,
+                        line_number: node_line(node),
+                        target: "module".to_string(),
+                    });
+                }
+                return;
+            }
+            _ => return,
+        };
+        let Some(attribute) = node.named_child(0) else { return };
+
+        let name = attribute
+            .named_child(0)
+            .map(|n| node_text(n, code).to_string())
+            .unwrap_or_default();
+        let arguments = field_text(attribute, "arguments", code)
+            .map(|args| args.trim_start_matches('(').trim_end_matches(')').to_string())
+            .or_else(|| field_text(attribute, "value", code));
+
+        let target = if is_inner {
+            "module"
+        } else {
+            rust_attribute_target(node)
+        };
+
+        decorators.push(DecoratorInfo {
+            name,
+            arguments,
+            line_number: node_line(node),
+            target: target.to_string(),
+        });
+    });
+}
+
+/// Classify the item an outer attribute applies to by skipping to the next non-attribute sibling
+fn rust_attribute_target(attribute: Node) -> &'static str {
+    let mut next = attribute.next_named_sibling();
+    while let Some(sibling) = next {
+        if !matches!(sibling.kind(), "attribute_item" | "line_comment" | "block_comment") {
+            break;
+        }
+        next = sibling.next_named_sibling();
+    }
+
+    let Some(item) = next else { return "unknown" };
+    match item.kind() {
+        "struct_item" | "enum_item" | "union_item" | "trait_item" | "impl_item" | "type_item" => "class",
+        "function_item" | "function_signature_item" => {
+            let in_impl = item
+                .parent()
+                .and_then(|body| body.parent())
+                .is_some_and(|owner| matches!(owner.kind(), "impl_item" | "trait_item"));
+            if in_impl { "method" } else { "function" }
+        }
+        "field_declaration" | "enum_variant" | "const_item" | "static_item" => "property",
+        "parameter" => "parameter",
+        "mod_item" => "module",
+        _ => "unknown",
+    }
+}
+
+fn extract_java_annotations(src: &SourceFile, decorators: &mut Vec<DecoratorInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if !matches!(node.kind(), "annotation" | "marker_annotation") {
+            return;
+        }
+
+        // Annotations live in a `modifiers` node owned by the annotated declaration
+        let owner = node
+            .parent()
+            .filter(|p| p.kind() == "modifiers")
+            .and_then(|m| m.parent())
+            .or_else(|| node.parent());
+        let target = match owner.map(|o| o.kind()).unwrap_or("") {
+            "class_declaration" | "interface_declaration" | "record_declaration" | "enum_declaration"
+            | "annotation_type_declaration" => "class",
+            "method_declaration" | "constructor_declaration" | "annotation_type_element_declaration" => "method",
+            "field_declaration" | "constant_declaration" | "enum_constant" => "property",
+            "formal_parameter" | "spread_parameter" => "parameter",
+            "local_variable_declaration" => "variable",
+            "package_declaration" => "module",
+            _ => "unknown",
+        };
+
+        decorators.push(DecoratorInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            arguments: field_text(node, "arguments", code)
+                .map(|args| args.trim_start_matches('(').trim_end_matches(')').to_string()),
+            line_number: node_line(node),
+            target: target.to_string(),
+        });
+    });
 }
 
+fn extract_csharp_attributes(src: &SourceFile, decorators: &mut Vec<DecoratorInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
 
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "attribute" {
+            return;
+        }
+
+        let Some(list) = node.parent() else { return };
+        let owner = if list.kind() == "global_attribute_list" { Some(list) } else { list.parent() };
+        let target = match owner.map(|o| o.kind()).unwrap_or("") {
+            "class_declaration" | "interface_declaration" | "record_declaration" | "record_struct_declaration"
+            | "struct_declaration" | "enum_declaration" | "delegate_declaration" => "class",
+            "method_declaration" | "constructor_declaration" | "local_function_statement" => "method",
+            "property_declaration" | "field_declaration" | "event_declaration" | "event_field_declaration"
+            | "enum_member_declaration" => "property",
+            "parameter" => "parameter",
+            // `[assembly: ...]` and `[module: ...]` attributes
+            "global_attribute_list" => "module",
+            _ => "unknown",
+        };
+
+        let mut cursor = node.walk();
+        let arguments = node
+            .named_children(&mut cursor)
+            .find(|n| n.kind() == "attribute_argument_list")
+            .map(|args| node_text(args, code).trim_start_matches('(').trim_end_matches(')').to_string());
+
+        decorators.push(DecoratorInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            arguments,
+            line_number: node_line(node),
+            target: target.to_string(),
+        });
+    });
+}
 
 /// Extract generics from code
 #[napi]
 pub fn extract_generics(code: String, language_id: String) -> Result<Vec<GenericInfo>> {
-    Ok(process_generics(&code, &language_id))
+    Ok(process_generics(&SourceFile::new(&code, &language_id)))
 }
 
-fn process_generics(code: &str, language_id: &str) -> Vec<GenericInfo> {
+fn process_generics(src: &SourceFile) -> Vec<GenericInfo> {
     let mut generics = Vec::new();
     
-    match language_id {
+    match src.language_id {
         "typescript" | "typescriptreact" => {
-             extract_ts_generics(code, &mut generics);
+             extract_ts_generics(src, &mut generics);
+        }
+        "rust" => {
+            extract_rust_generics(src, &mut generics);
+        }
+        "java" => {
+            extract_java_generics(src, &mut generics);
+        }
+        "csharp" => {
+            extract_csharp_generics(src, &mut generics);
         }
         _ => {}
     }
@@ -576,8 +3320,9 @@ fn process_generics(code: &str, language_id: &str) -> Vec<GenericInfo> {
     generics
 }
 
-fn extract_ts_generics(code: &str, generics: &mut Vec<GenericInfo>) {
-    let line_index = LineIndex::new(code);
+fn extract_ts_generics(src: &SourceFile, generics: &mut Vec<GenericInfo>) {
+    let code = src.code;
+    let line_index = src.line_index();
     
     if let Some(generic_re) = get_regex("ts_generic") {
         for caps in generic_re.captures_iter(code) {
@@ -598,32 +3343,845 @@ fn extract_ts_generics(code: &str, generics: &mut Vec<GenericInfo>) {
     }
 }
 
+fn extract_rust_generics(src: &SourceFile, generics: &mut Vec<GenericInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "type_parameters" {
+            return;
+        }
+
+        let mut cursor = node.walk();
+        for param in node.named_children(&mut cursor) {
+            let (name, constraint, default_type) = match param.kind() {
+                "type_identifier" => (node_text(param, code).to_string(), None, None),
+                "constrained_type_parameter" => (
+                    field_text(param, "left", code).unwrap_or_default(),
+                    field_text(param, "bounds", code).map(|b| b.trim_start_matches(':').trim().to_string()),
+                    None,
+                ),
+                "optional_type_parameter" => (
+                    field_text(param, "name", code).unwrap_or_default(),
+                    None,
+                    field_text(param, "default_type", code),
+                ),
+                "const_parameter" => (
+                    field_text(param, "name", code).unwrap_or_default(),
+                    field_text(param, "type", code),
+                    None,
+                ),
+                // Lifetimes carry no type information
+                _ => continue,
+            };
+
+            generics.push(GenericInfo {
+                name,
+                constraint,
+                default_type,
+                line_number: node_line(param),
+            });
+        }
+    });
+}
+
+fn extract_java_generics(src: &SourceFile, generics: &mut Vec<GenericInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        if node.kind() != "type_parameter" {
+            return;
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        let Some(name) = children.iter().find(|n| matches!(n.kind(), "type_identifier" | "identifier")) else { return };
+        let constraint = children
+            .iter()
+            .find(|n| n.kind() == "type_bound")
+            .map(|b| node_text(*b, code).trim_start_matches("extends").trim().to_string());
+
+        generics.push(GenericInfo {
+            name: node_text(*name, code).to_string(),
+            constraint,
+            default_type: None,
+            line_number: node_line(node),
+        });
+    });
+}
+
+fn extract_csharp_generics(src: &SourceFile, generics: &mut Vec<GenericInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let Some(params) = node.child_by_field_name("type_parameters") else { return };
+
+        // Constraints live in sibling `where T : ...` clauses on the declaration
+        let mut constraints: HashMap<String, String> = HashMap::new();
+        let mut cursor = node.walk();
+        for clause in node.named_children(&mut cursor) {
+            if clause.kind() != "type_parameter_constraints_clause" {
+                continue;
+            }
+            if let Some(target) = field_text(clause, "target", code) {
+                let mut cursor = clause.walk();
+                let bounds: Vec<&str> = clause
+                    .children_by_field_name("constraints", &mut cursor)
+                    .filter(|c| c.is_named())
+                    .map(|c| node_text(c, code))
+                    .collect();
+                constraints.insert(target, bounds.join(", "));
+            }
+        }
+
+        let mut cursor = params.walk();
+        for param in params.named_children(&mut cursor) {
+            let Some(name) = field_text(param, "name", code) else { continue };
+            generics.push(GenericInfo {
+                constraint: constraints.get(&name).cloned(),
+                name,
+                default_type: None,
+                line_number: node_line(param),
+            });
+        }
+    });
+}
+
+/// Extract preprocessor macros from code
+#[napi]
+pub fn extract_macros(code: String, language_id: String) -> Result<Vec<MacroInfo>> {
+    Ok(process_macros(&SourceFile::new(&code, &language_id)))
+}
+
+fn process_macros(src: &SourceFile) -> Vec<MacroInfo> {
+    let mut macros = Vec::new();
+
+    if matches!(src.language_id, "c" | "cpp") {
+        extract_cpp_macros(src, &mut macros);
+    }
+
+    macros
+}
+
+fn extract_cpp_macros(src: &SourceFile, macros: &mut Vec<MacroInfo>) {
+    let code = src.code;
+    let Some(tree) = src.tree() else { return };
+
+    visit_named(tree.root_node(), &mut |node| {
+        let parameters = match node.kind() {
+            "preproc_def" => None,
+            "preproc_function_def" => node.child_by_field_name("parameters").map(|params| {
+                let mut cursor = params.walk();
+                params
+                    .children(&mut cursor)
+                    .filter(|p| p.is_named() || p.kind() == "...")
+                    .map(|p| node_text(p, code).to_string())
+                    .collect()
+            }),
+            _ => return,
+        };
+
+        macros.push(MacroInfo {
+            name: field_text(node, "name", code).unwrap_or_default(),
+            parameters,
+            value: field_text(node, "value", code).map(|v| v.trim().to_string()),
+            line_number: node_line(node),
+        });
+    });
+}
+
+/// Find the package or namespace a file declares
+fn process_namespace(src: &SourceFile) -> Option<String> {
+    let code = src.code;
+    match src.language_id {
+        "java" => {
+            let tree = src.tree()?;
+            let root = tree.root_node();
+            let mut cursor = root.walk();
+            let package = root
+                .named_children(&mut cursor)
+                .find(|n| n.kind() == "package_declaration")?;
+            let mut cursor = package.walk();
+            let name = package
+                .named_children(&mut cursor)
+                .find(|n| matches!(n.kind(), "scoped_identifier" | "identifier"))?;
+            Some(node_text(name, code).to_string())
+        }
+        "csharp" => {
+            let tree = src.tree()?;
+            let mut namespace = None;
+            visit_named(tree.root_node(), &mut |node| {
+                if namespace.is_none()
+                    && matches!(node.kind(), "namespace_declaration" | "file_scoped_namespace_declaration")
+                {
+                    namespace = field_text(node, "name", code);
+                }
+            });
+            namespace
+        }
+        "cpp" => {
+            let tree = src.tree()?;
+            let mut namespace = None;
+            visit_named(tree.root_node(), &mut |node| {
+                if namespace.is_none() && node.kind() == "namespace_definition" {
+                    namespace = field_text(node, "name", code);
+                }
+            });
+            namespace
+        }
+        "php" => {
+            let tree = src.tree()?;
+            let root = tree.root_node();
+            let mut cursor = root.walk();
+            let definition = root
+                .named_children(&mut cursor)
+                .find(|n| n.kind() == "namespace_definition")?;
+            field_text(definition, "name", code)
+        }
+        _ => None,
+    }
+}
+
+/// Count references to the given identifiers, excluding their declarations
+///
+/// Identifiers inside strings and comments are ignored for languages with a
+/// Tree-sitter grammar; other languages fall back to whole-word matching,
+/// taking the first match as the declaration. A name given twice is counted
+/// for both entries.
+#[napi]
+pub fn count_symbol_references(code: String, language_id: String, symbol_names: Vec<String>) -> Result<Vec<SymbolReferences>> {
+    let mut lines: HashMap<&str, Vec<u32>> = symbol_names.iter().map(|name| (name.as_str(), Vec::new())).collect();
+
+    if let Ok(tree) = parse_tree(&code, &language_id) {
+        visit_named(tree.root_node(), &mut |node| {
+            if node.child_count() != 0 || !is_identifier_kind(node.kind()) {
+                return;
+            }
+            let Some(found) = lines.get_mut(node_text(node, &code)) else { return };
+            if !is_declaration_name(node) {
+                found.push(node_line(node));
+            }
+        });
+    } else {
+        let line_index = LineIndex::new(&code);
+        for (name, found) in lines.iter_mut() {
+            if name.is_empty() {
+                continue;
+            }
+            let Ok(re) = Regex::new(&format!(r"\b{}\b", regex::escape(name))) else { continue };
+            *found = re.find_iter(&code).skip(1).map(|m| line_index.get_line(m.start())).collect();
+        }
+    }
+
+    Ok(symbol_names
+        .iter()
+        .map(|name| {
+            let lines = lines.get(name.as_str()).cloned().unwrap_or_default();
+            SymbolReferences { name: name.clone(), count: lines.len() as u32, lines }
+        })
+        .collect())
+}
+
+pub(crate) fn is_identifier_kind(kind: &str) -> bool {
+    // Ruby constants and PHP names are identifiers under another name
+    kind.ends_with("identifier") || matches!(kind, "constant" | "name")
+}
+
+/// Whether an identifier names the declaration it belongs to rather than referencing it
+pub(crate) fn is_declaration_name(node: Node) -> bool {
+    let Some(parent) = node.parent() else { return false };
+    let kind = parent.kind();
+
+    match kind {
+        // Python parameters are bare identifiers
+        "parameters" | "typed_parameter" | "lambda_parameters" => return true,
+        "assignment" => return parent.child_by_field_name("left") == Some(node),
+        _ => {}
+    }
+    // Calls and member accesses also use a `name` field
+    if ["call", "invocation", "access", "member", "selector"].iter().any(|k| kind.contains(k)) {
+        return false;
+    }
+
+    let named_by = |field: &str| parent.child_by_field_name(field) == Some(node);
+    named_by("name") || named_by("declarator") || (kind.contains("parameter") && named_by("pattern"))
+}
+
+/// List the symbols visible at a zero-based line and column, with the column
+/// in UTF-16 code units as editors report it
+///
+/// Symbols from inner scopes come first and shadow outer declarations of the
+/// same name. Variables only count once declared; functions, classes and
+/// imports are visible throughout their scope.
+#[napi]
+pub fn symbols_in_scope(code: String, language_id: String, line: u32, column: u32) -> Result<Vec<ScopeSymbol>> {
+    let Ok(tree) = parse_tree(&code, &language_id) else { return Ok(Vec::new()) };
+    // Tree-sitter columns are in bytes
+    let column = code.split('\n').nth(line as usize).map_or(column as usize, |text| utf16_to_byte_column(text, column));
+    let point = Point::new(line as usize, column);
+
+    let mut symbols = Vec::new();
+    let mut seen = HashSet::new();
+    let mut scope = tree.root_node().descendant_for_point_range(point, point);
+    while let Some(node) = scope {
+        let mut bindings = Vec::new();
+        scope_bindings(node, point, &code, &mut bindings);
+        for (name, kind, line_number) in bindings {
+            if seen.insert(name.clone()) {
+                symbols.push(ScopeSymbol {
+                    name,
+                    kind: kind.to_string(),
+                    line_number,
+                    is_module_level: node.parent().is_none(),
+                });
+            }
+        }
+        scope = node.parent();
+    }
+
+    Ok(symbols)
+}
+
+/// Byte offset into a line of a column in UTF-16 code units, clamped to the line
+fn utf16_to_byte_column(line: &str, column: u32) -> usize {
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= column {
+            return i;
+        }
+        units += c.len_utf16() as u32;
+    }
+    line.len()
+}
+
+type Binding = (String, &'static str, u32);
+
+/// Names a node introduces into the scope it opens
+fn scope_bindings(node: Node, point: Point, code: &str, out: &mut Vec<Binding>) {
+    for field in ["parameters", "parameter", "receiver"] {
+        let mut cursor = node.walk();
+        for params in node.children_by_field_name(field, &mut cursor) {
+            push_bindings(params, "parameter", code, out);
+        }
+    }
+    match node.kind() {
+        "for_in_statement" | "for_statement" | "for_in_clause" | "enhanced_for_statement" => {
+            for field in ["left", "initializer", "name"] {
+                if let Some(target) = node.child_by_field_name(field) {
+                    declaration_bindings(target, point, code, out);
+                    if target.kind() == "identifier" || target.kind().ends_with("pattern") {
+                        push_bindings(target, "variable", code, out);
+                    }
+                }
+            }
+        }
+        "except_clause" | "catch_clause" => {
+            visit_named(node, &mut |n| {
+                if n.kind() == "as_pattern_target" || n.kind() == "catch_formal_parameter" {
+                    push_bindings(n, "variable", code, out);
+                }
+            });
+        }
+        _ => {}
+    }
+
+    // Only statement lists declare names; class members are reached through `this`/`self`
+    let kind = node.kind();
+    let is_statement_list = node.parent().is_none()
+        || kind.contains("block")
+        || kind.ends_with("body")
+        || matches!(kind, "declaration_list" | "compound_statement" | "statement_list");
+    let in_class_body = kind == "class_body"
+        || node.parent().is_some_and(|p| p.kind().contains("class") || p.kind() == "impl_item");
+    if !is_statement_list || in_class_body {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        declaration_bindings(child, point, code, out);
+    }
+}
+
+/// Names declared by a statement
+fn declaration_bindings(node: Node, point: Point, code: &str, out: &mut Vec<Binding>) {
+    let declared_before = node.start_position() < point;
+    let named = |kind: &'static str, out: &mut Vec<Binding>| {
+        if let Some(name) = node.child_by_field_name("name") {
+            push_bindings(name, kind, code, out);
+        }
+    };
+
+    match node.kind() {
+        "export_statement" => {
+            if let Some(declaration) = node.child_by_field_name("declaration") {
+                declaration_bindings(declaration, point, code, out);
+            }
+        }
+        "decorated_definition" => {
+            if let Some(definition) = node.child_by_field_name("definition") {
+                declaration_bindings(definition, point, code, out);
+            }
+        }
+        "import_statement" | "import_from_statement" => import_bindings(node, code, out),
+        "lexical_declaration" | "variable_declaration" => {
+            if !declared_before {
+                return;
+            }
+            let kind = if node.child(0).is_some_and(|k| k.kind() == "const") { "constant" } else { "variable" };
+            let mut cursor = node.walk();
+            for declarator in node.named_children(&mut cursor).filter(|d| d.kind() == "variable_declarator") {
+                if let Some(name) = declarator.child_by_field_name("name") {
+                    push_bindings(name, kind, code, out);
+                }
+            }
+        }
+        // Python assignments and `with ... as name` bind in the enclosing scope
+        "expression_statement" => {
+            let Some(assignment) = node.named_child(0).filter(|a| a.kind() == "assignment") else { return };
+            if let Some(left) = assignment.child_by_field_name("left").filter(|_| declared_before) {
+                push_bindings(left, "variable", code, out);
+            }
+        }
+        "with_statement" if declared_before => {
+            visit_named(node, &mut |n| {
+                if n.kind() == "as_pattern_target" {
+                    push_bindings(n, "variable", code, out);
+                }
+            });
+        }
+        // Go methods are only reachable through their receiver
+        "method_declaration" if node.child_by_field_name("receiver").is_some() => {}
+        kind if kind.contains("function") || kind.contains("method") => named("function", out),
+        kind if kind.contains("class") || kind.contains("struct") => named("class", out),
+        kind if ["interface", "enum", "trait", "type_alias", "union"].iter().any(|k| kind.contains(k)) => named("type", out),
+        kind if ["declaration", "definition", "_item", "_spec", "declarator"].iter().any(|k| kind.contains(k)) => {
+            let mut cursor = node.walk();
+            let names: Vec<Node> = node.children_by_field_name("name", &mut cursor).collect();
+            if !names.is_empty() {
+                let binding_kind = declaration_kind(kind);
+                if declared_before || binding_kind != "variable" {
+                    for name in names {
+                        push_bindings(name, binding_kind, code, out);
+                    }
+                }
+                return;
+            }
+            if !declared_before {
+                return;
+            }
+            if let Some(target) = ["declarator", "pattern", "left", "argument"].iter().find_map(|f| node.child_by_field_name(f)) {
+                push_bindings(target, declaration_kind(kind), code, out);
+                return;
+            }
+            // `var (a, b int)` and `type ( ... )` groups hold their specs as children
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                if ["_spec", "declarator"].iter().any(|k| child.kind().contains(k)) {
+                    declaration_bindings(child, point, code, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn declaration_kind(kind: &str) -> &'static str {
+    if kind.contains("const") {
+        "constant"
+    } else if kind.contains("import") || kind.starts_with("use_") {
+        "import"
+    } else if kind.contains("type") {
+        "type"
+    } else {
+        "variable"
+    }
+}
+
+/// Local names bound by an ES or Python import statement
+fn import_bindings(node: Node, code: &str, out: &mut Vec<Binding>) {
+    visit_named(node, &mut |n| {
+        let name = match n.kind() {
+            // `import a.b.c` binds `a`; `from m import a` binds `a`
+            "dotted_name" if n.parent() == Some(node) => {
+                let is_module = node.child_by_field_name("module_name") == Some(n);
+                let part = if node.kind() == "import_statement" { n.named_child(0) } else { Some(n) };
+                part.filter(|_| !is_module).map(|p| node_text(p, code).to_string())
+            }
+            "aliased_import" => field_text(n, "alias", code),
+            "import_specifier" => field_text(n, "alias", code).or_else(|| field_text(n, "name", code)),
+            "namespace_import" => n.named_child(0).map(|i| node_text(i, code).to_string()),
+            "identifier" if n.parent().is_some_and(|p| p.kind() == "import_clause") => Some(node_text(n, code).to_string()),
+            _ => None,
+        };
+        if let Some(name) = name {
+            out.push((name, "import", node_line(n)));
+        }
+    });
+}
+
+/// Push every identifier bound by a name, pattern or parameter list
+fn push_bindings(node: Node, kind: &'static str, code: &str, out: &mut Vec<Binding>) {
+    let node_kind = node.kind();
+    // Qualified names such as `std::fmt` bind their last segment
+    let is_simple_name = is_identifier_kind(node_kind) && node.named_child_count() == 0;
+    if is_simple_name || matches!(node_kind, "shorthand_property_identifier_pattern" | "variable_name") {
+        out.push((node_text(node, code).to_string(), kind, node_line(node)));
+        return;
+    }
+    match node_kind {
+        "pair_pattern" => {
+            if let Some(value) = node.child_by_field_name("value") {
+                push_bindings(value, kind, code, out);
+            }
+            return;
+        }
+        "typed_parameter" => {
+            if let Some(name) = node.named_child(0) {
+                push_bindings(name, kind, code, out);
+            }
+            return;
+        }
+        _ => {}
+    }
+    if let Some(inner) = ["alias", "name", "pattern", "declarator", "left"].iter().find_map(|f| node.child_by_field_name(f)) {
+        push_bindings(inner, kind, code, out);
+        return;
+    }
+    if ["pattern", "list", "tuple", "parameter", "target", "splat", "spec"].iter().any(|k| node_kind.contains(k)) {
+        let mut cursor = node.walk();
+        // Skip type annotations, which sit beside the names in parameter lists
+        for child in node.named_children(&mut cursor).filter(|c| !c.kind().contains("type") || c.kind().contains("parameter")) {
+            push_bindings(child, kind, code, out);
+        }
+    }
+}
+
 /// Perform complete semantic analysis
 /// 
-/// Combines all analysis operations in a single pass for maximum efficiency
+/// Runs every extractor in parallel over a single parse of the file
 #[napi]
-
 pub fn analyze_semantics(code: String, language_id: String) -> Result<SemanticAnalysis> {
-    // Use Rayon to parallelize if inputs are large, but for now just avoid clones
-    // We could use rayon::join here
-    let (imports, functions) = rayon::join(
-        || process_imports(&code, &language_id),
-        || process_functions(&code, &language_id)
-    );
-    
-    let (classes, generics) = rayon::join(
-        || process_classes(&code, &language_id),
-        || process_generics(&code, &language_id)
-    );
+    analyze_source(&SourceFile::new(&code, &language_id))
+}
+
+/// Analyze many files in parallel in a single call
+#[napi]
+pub fn analyze_semantics_many(files: Vec<FileInput>) -> BatchAnalysis {
+    use rayon::prelude::*;
+
+    let started = Instant::now();
+    let files: Vec<FileAnalysis> = files
+        .par_iter()
+        .map(|file| {
+            let file_started = Instant::now();
+            let result = analyze_source(&SourceFile::new(&file.code, &file.language_id));
+            let (analysis, error) = match result {
+                Ok(analysis) => (Some(analysis), None),
+                Err(e) => (None, Some(e.reason.clone())),
+            };
+            FileAnalysis {
+                path: file.path.clone(),
+                analysis,
+                error,
+                duration_ms: file_started.elapsed().as_secs_f64() * 1000.0,
+            }
+        })
+        .collect();
+
+    BatchAnalysis {
+        files,
+        total_ms: started.elapsed().as_secs_f64() * 1000.0,
+    }
+}
 
-    // decorators are usually few, run sequentially or join again
-    let decorators = process_decorators(&code, &language_id).unwrap_or_default();
+fn analyze_source(src: &SourceFile) -> Result<SemanticAnalysis> {
+    // Build the shared state up front so the extractors don't queue behind whichever initializes it
+    rayon::join(|| src.line_index(), || src.tree());
+
+    let mut imports = Vec::new();
+    let mut functions = Vec::new();
+    let mut classes = Vec::new();
+    let mut decorators = Ok(Vec::new());
+    let mut generics = Vec::new();
+    let mut namespace = None;
+    let mut macros = Vec::new();
+
+    rayon::scope(|s| {
+        s.spawn(|_| imports = process_imports(src));
+        s.spawn(|_| functions = process_functions(src));
+        s.spawn(|_| classes = process_classes(src));
+        s.spawn(|_| decorators = process_decorators(src));
+        s.spawn(|_| generics = process_generics(src));
+        s.spawn(|_| {
+            namespace = process_namespace(src);
+            macros = process_macros(src);
+        });
+    });
 
     Ok(SemanticAnalysis {
+        language_id: src.language_id.to_string(),
+        line_count: src.line_index().offsets.len() as u32,
+        namespace,
         imports,
         functions,
         classes,
-        decorators,
+        decorators: decorators?,
         generics,
+        macros,
+    })
+}
+
+/// Re-analyze a file after an edit, reusing the previous result outside the edited lines
+///
+/// `changed_start_line` and `changed_end_line` are zero-based and inclusive, in
+/// the new code. The dirty range is widened to the top-level declarations it
+/// touches; symbols after it keep their previous info with shifted line numbers.
+/// Facts that depend on other statements, such as a TypeScript function
+/// exported by an `export { ... }` list elsewhere, are recomputed for the
+/// whole file.
+#[napi]
+pub fn analyze_semantics_incremental(
+    previous: SemanticAnalysis,
+    code: String,
+    changed_start_line: u32,
+    changed_end_line: u32,
+) -> Result<SemanticAnalysis> {
+    let language_id = previous.language_id.clone();
+    let src = SourceFile::new(&code, &language_id);
+    let offsets = &src.line_index().offsets;
+    let line_count = offsets.len() as u32;
+    let delta = line_count as i64 - previous.line_count as i64;
+
+    let mut start = changed_start_line.min(changed_end_line);
+    let mut end = changed_start_line.max(changed_end_line).min(line_count - 1);
+    if let Some(tree) = src.tree() {
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        for node in root.named_children(&mut cursor) {
+            let (node_start, node_end) = (node.start_position().row as u32, node.end_position().row as u32);
+            if node_start <= end && node_end >= start {
+                start = start.min(node_start);
+                end = end.max(node_end);
+            }
+        }
+    }
+
+    // The edited range as it was in the previous code
+    let old_end = end as i64 - delta;
+    if start >= line_count || old_end < start as i64 - 1 {
+        return analyze_source(&src);
+    }
+
+    let region_start = offsets[start as usize];
+    let region_end = offsets.get(end as usize + 1).copied().unwrap_or(code.len());
+    let region = SourceFile::new(&code[region_start..region_end], src.language_id);
+    let fresh = analyze_source(&region)?;
+
+    let mut functions = splice_lines(previous.functions, fresh.functions, start, old_end, delta);
+    if matches!(src.language_id, "typescript" | "typescriptreact" | "javascript" | "javascriptreact") {
+        let exported = ts_exported_names(&src);
+        for function in &mut functions {
+            mark_ts_exported(function, &exported);
+        }
+    }
+
+    Ok(SemanticAnalysis {
+        namespace: process_namespace(&src),
+        line_count,
+        imports: splice_lines(previous.imports, fresh.imports, start, old_end, delta),
+        functions,
+        classes: splice_lines(previous.classes, fresh.classes, start, old_end, delta),
+        decorators: splice_lines(previous.decorators, fresh.decorators, start, old_end, delta),
+        generics: splice_lines(previous.generics, fresh.generics, start, old_end, delta),
+        macros: splice_lines(previous.macros, fresh.macros, start, old_end, delta),
+        language_id: previous.language_id,
     })
 }
+
+/// Replace the symbols of an edited line range with freshly extracted ones
+///
+/// `fresh` holds line numbers relative to `start`; symbols after `old_end`
+/// move by `delta` lines.
+fn splice_lines<T: LineShift>(previous: Vec<T>, fresh: Vec<T>, start: u32, old_end: i64, delta: i64) -> Vec<T> {
+    let mut merged = Vec::with_capacity(previous.len() + fresh.len());
+    let mut after = Vec::new();
+    for mut item in previous {
+        let line = item.line();
+        if line < start {
+            merged.push(item);
+        } else if line as i64 > old_end {
+            item.shift(delta);
+            after.push(item);
+        }
+    }
+    merged.extend(fresh.into_iter().map(|mut item| {
+        item.shift(start as i64);
+        item
+    }));
+    merged.extend(after);
+    merged
+}
+
+/// Symbols that carry line numbers
+trait LineShift {
+    fn line(&self) -> u32;
+    fn shift(&mut self, delta: i64);
+}
+
+fn shift_line(line: &mut u32, delta: i64) {
+    *line = (*line as i64 + delta).max(0) as u32;
+}
+
+impl LineShift for ImportInfo {
+    fn line(&self) -> u32 {
+        self.line_number
+    }
+
+    fn shift(&mut self, delta: i64) {
+        shift_line(&mut self.line_number, delta);
+    }
+}
+
+impl LineShift for FunctionInfo {
+    fn line(&self) -> u32 {
+        self.line_number
+    }
+
+    fn shift(&mut self, delta: i64) {
+        shift_line(&mut self.line_number, delta);
+    }
+}
+
+impl LineShift for ClassInfo {
+    fn line(&self) -> u32 {
+        self.line_number
+    }
+
+    fn shift(&mut self, delta: i64) {
+        shift_line(&mut self.line_number, delta);
+        for member in &mut self.members {
+            shift_line(&mut member.line_number, delta);
+            if let Some(signature) = &mut member.signature {
+                signature.shift(delta);
+            }
+        }
+    }
+}
+
+impl LineShift for DecoratorInfo {
+    fn line(&self) -> u32 {
+        self.line_number
+    }
+
+    fn shift(&mut self, delta: i64) {
+        shift_line(&mut self.line_number, delta);
+    }
+}
+
+impl LineShift for GenericInfo {
+    fn line(&self) -> u32 {
+        self.line_number
+    }
+
+    fn shift(&mut self, delta: i64) {
+        shift_line(&mut self.line_number, delta);
+    }
+}
+
+impl LineShift for MacroInfo {
+    fn line(&self) -> u32 {
+        self.line_number
+    }
+
+    fn shift(&mut self, delta: i64) {
+        shift_line(&mut self.line_number, delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn python_imports_after_the_first_line_are_found() {
+        let code = "\"\"\"Module.\"\"\"\nimport os\nimport numpy as np\nfrom a.b import c, d\n";
+        let imports = extract_imports(code.to_string(), "python".to_string()).unwrap();
+        let found: Vec<(&str, u32, Vec<String>)> = imports
+            .iter()
+            .map(|import| (import.module.as_str(), import.line_number, import.imports.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("os", 1, vec!["os".to_string()]),
+                ("numpy", 2, vec!["np".to_string()]),
+                ("a.b", 3, vec!["c".to_string(), "d".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn incremental_analysis_matches_full_analysis() {
+        let before = "import { a } from './a';\n\nfunction f() {}\n\nfunction g() {\n  return 1;\n}\n";
+        // Each edit with its changed lines in the new code
+        let edits = [
+            ("import { a } from './a';\n\nfunction f() {}\n\nexport { f };\nfunction g() {\n  return 1;\n}\n", 4, 4),
+            ("import { a } from './a';\n\nfunction f(x: number) {\n  return x;\n}\n\nfunction g() {\n  return 1;\n}\n", 2, 4),
+            ("import { a } from './a';\nimport { b } from './b';\n\nfunction f() {}\n\nfunction g() {\n  return 1;\n}\n", 1, 1),
+        ];
+        let ts = || "typescript".to_string();
+        for (after, start, end) in edits {
+            let previous = analyze_semantics(before.to_string(), ts()).unwrap();
+            let incremental = analyze_semantics_incremental(previous, after.to_string(), start, end).unwrap();
+            let full = analyze_semantics(after.to_string(), ts()).unwrap();
+            assert_eq!(serde_json::to_value(incremental).unwrap(), serde_json::to_value(full).unwrap(), "{}", after);
+        }
+
+        // Dropping an export list elsewhere un-exports the function
+        let exported = "function f() {}\n\nexport { f };\n";
+        let previous = analyze_semantics(exported.to_string(), ts()).unwrap();
+        assert!(previous.functions[0].is_exported);
+        let incremental = analyze_semantics_incremental(previous, "function f() {}\n\n\n".to_string(), 2, 2).unwrap();
+        assert!(!incremental.functions[0].is_exported);
+    }
+
+    #[test]
+    fn commonjs_exports_allow_non_ascii_values() {
+        let exports = extract_exports("exports.x=π;\nexports.y = 'é';\n".to_string(), "javascript".to_string()).unwrap();
+        let names: Vec<&str> = exports.iter().map(|export| export.name.as_str()).collect();
+        assert_eq!(names, ["x", "y"]);
+    }
+
+    #[test]
+    fn import_meta_is_not_an_import() {
+        let code = "const url = import.meta.url;\nconst { a } = await import('./a');\n";
+        let imports = extract_imports(code.to_string(), "typescript".to_string()).unwrap();
+        let found: Vec<(&str, bool)> = imports.iter().map(|import| (import.module.as_str(), import.is_dynamic)).collect();
+        assert_eq!(found, [("./a", true)]);
+    }
+
+    #[test]
+    fn repeated_names_and_fallback_declarations_are_counted_once() {
+        let code = "function f() {}\nf();\nf();\n";
+        let found = count_symbol_references(code.to_string(), "javascript".to_string(), vec!["f".into(), "f".into()]).unwrap();
+        let counts: Vec<u32> = found.iter().map(|reference| reference.count).collect();
+        assert_eq!(counts, [2, 2]);
+
+        let found = count_symbol_references(code.to_string(), "unknown".to_string(), vec!["f".into()]).unwrap();
+        assert_eq!(found[0].lines, [1, 2]);
+    }
+
+    #[test]
+    fn scope_columns_are_utf16() {
+        let code = "let s = '𝄞𝄞𝄞𝄞'; function f(p) { return p; } let t;\n";
+        let column = |needle: &str| code[..code.find(needle).unwrap()].encode_utf16().count() as u32;
+        let names = |column| -> Vec<String> {
+            let symbols = symbols_in_scope(code.to_string(), "javascript".to_string(), 0, column).unwrap();
+            symbols.into_iter().map(|symbol| symbol.name).collect()
+        };
+        assert!(names(column("return")).contains(&"p".to_string()));
+        assert!(!names(column(" let t")).contains(&"p".to_string()));
+    }
+}
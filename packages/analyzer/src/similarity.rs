@@ -0,0 +1,337 @@
+use napi_derive::napi;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::streaming_tokenizer::lexical_tokens;
+
+/// Options for `edit_distance`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditDistanceOptions {
+    /// Give up once the distance is known to exceed this
+    #[napi(js_name = "maxDistance")]
+    pub max_distance: Option<u32>,
+}
+
+/// Levenshtein distance between two strings, counted in characters
+///
+/// Returns `null` when the distance is above `maxDistance`. Once the common
+/// prefix and suffix are dropped, a side of up to 64 characters is handled
+/// bit-parallel in one pass; otherwise, with a limit, only a band around the
+/// diagonal is filled and the search stops as soon as a row is over the limit.
+#[napi]
+pub fn edit_distance(a: String, b: String, options: Option<EditDistanceOptions>) -> Option<u32> {
+    let max_distance = options.unwrap_or_default().max_distance.map(|d| d as usize);
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    levenshtein(&a, &b, max_distance).map(|d| d as u32)
+}
+
+/// Edit distance from `query` to each candidate, in parallel; results are in
+/// input order
+#[napi]
+pub fn edit_distance_many(
+    query: String,
+    candidates: Vec<String>,
+    options: Option<EditDistanceOptions>,
+) -> Vec<Option<u32>> {
+    let max_distance = options.unwrap_or_default().max_distance.map(|d| d as usize);
+    let query: Vec<char> = query.chars().collect();
+    candidates
+        .par_iter()
+        .map(|candidate| {
+            let candidate: Vec<char> = candidate.chars().collect();
+            levenshtein(&query, &candidate, max_distance).map(|d| d as u32)
+        })
+        .collect()
+}
+
+pub(crate) fn levenshtein(a: &[char], b: &[char], max_distance: Option<usize>) -> Option<usize> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+    // Keep `a` the shorter one
+    let (a, b) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    let within = |d: usize| max_distance.is_none_or(|max| d <= max);
+    if !within(b.len() - a.len()) {
+        return None;
+    }
+    if a.is_empty() {
+        return Some(b.len());
+    }
+
+    let distance = match max_distance {
+        _ if a.len() <= 64 => bit_parallel(a, b),
+        Some(max) if max < b.len() => banded(a, b, max)?,
+        _ => banded(a, b, b.len()).unwrap_or(b.len()),
+    };
+    within(distance).then_some(distance)
+}
+
+/// Myers' bit-parallel algorithm (Hyyrö's formulation) for `a` of up to 64
+/// characters: one pass over `b` updating a column of the table as bit vectors
+fn bit_parallel(a: &[char], b: &[char]) -> usize {
+    let mut ascii = [0u64; 128];
+    let mut other: HashMap<char, u64> = HashMap::new();
+    for (i, c) in a.iter().enumerate() {
+        match ascii.get_mut(*c as usize) {
+            Some(bits) => *bits |= 1 << i,
+            None => *other.entry(*c).or_default() |= 1 << i,
+        }
+    }
+
+    let last = 1u64 << (a.len() - 1);
+    let mut positive = u64::MAX;
+    let mut negative = 0u64;
+    let mut score = a.len();
+    for c in b {
+        let eq = match ascii.get(*c as usize) {
+            Some(bits) => *bits,
+            None => other.get(c).copied().unwrap_or(0),
+        };
+        let xv = eq | negative;
+        let xh = ((eq & positive).wrapping_add(positive) ^ positive) | eq;
+        let mut horizontal_positive = negative | !(xh | positive);
+        let mut horizontal_negative = positive & xh;
+        if horizontal_positive & last != 0 {
+            score += 1;
+        } else if horizontal_negative & last != 0 {
+            score -= 1;
+        }
+        horizontal_positive = (horizontal_positive << 1) | 1;
+        horizontal_negative <<= 1;
+        positive = horizontal_negative | !(xv | horizontal_positive);
+        negative = horizontal_positive & xv;
+    }
+    score
+}
+
+/// Dynamic programming restricted to cells within `max` of the diagonal, or
+/// `None` once a whole row exceeds `max`
+fn banded(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    let over = max + 1;
+    // Rows follow `b`, columns follow `a`
+    let mut previous: Vec<usize> = (0..=a.len()).map(|j| j.min(over)).collect();
+    let mut current = vec![over; a.len() + 1];
+    for i in 1..=b.len() {
+        let start = i.saturating_sub(max).max(1);
+        let end = (i + max).min(a.len());
+        current[start - 1] = if start == 1 { i.min(over) } else { over };
+        let mut row_min = current[start - 1];
+        for j in start..=end {
+            let substitution = previous[j - 1] + usize::from(a[j - 1] != b[i - 1]);
+            let cell = substitution.min(previous[j] + 1).min(current[j - 1] + 1).min(over);
+            current[j] = cell;
+            row_min = row_min.min(cell);
+        }
+        if end < a.len() {
+            current[end + 1] = over;
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    let distance = previous[a.len()];
+    (distance <= max).then_some(distance)
+}
+
+/// Longest run of text shared by two strings
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommonSubstring {
+    /// UTF-8 byte offset in `a`
+    #[napi(js_name = "aStart")]
+    pub a_start: u32,
+    /// UTF-8 byte offset in `b`
+    #[napi(js_name = "bStart")]
+    pub b_start: u32,
+    /// Length in bytes
+    pub length: u32,
+}
+
+/// Find the longest common substring, earliest in `b` on ties
+///
+/// Runs in linear time using a suffix automaton of `a`. Returns `null` when
+/// the strings share no character.
+#[napi]
+pub fn longest_common_substring(a: String, b: String) -> Option<CommonSubstring> {
+    let a_chars: Vec<char> = a.chars().collect();
+    let b_chars: Vec<char> = b.chars().collect();
+    let (a_start, b_start, length) = longest_common_run(&a_chars, &b_chars)?;
+    let a_offset: usize = a_chars[..a_start].iter().map(|c| c.len_utf8()).sum();
+    let b_offset: usize = b_chars[..b_start].iter().map(|c| c.len_utf8()).sum();
+    let byte_length: usize = a_chars[a_start..a_start + length].iter().map(|c| c.len_utf8()).sum();
+    Some(CommonSubstring {
+        a_start: a_offset as u32,
+        b_start: b_offset as u32,
+        length: byte_length as u32,
+    })
+}
+
+/// Length in characters of the longest common subsequence
+///
+/// Uses the bit-parallel algorithm of Allison and Dix, processing 64
+/// characters of `a` per machine word for each character of `b`.
+#[napi]
+pub fn lcs_length(a: String, b: String) -> u32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    lcs_len(&a, &b) as u32
+}
+
+fn lcs_len<T: Copy + Eq + Hash>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
+        return 0;
+    }
+    let words = a.len().div_ceil(64);
+    let mut masks: HashMap<T, Vec<u64>> = HashMap::new();
+    for (i, x) in a.iter().enumerate() {
+        masks.entry(*x).or_insert_with(|| vec![0; words])[i / 64] |= 1 << (i % 64);
+    }
+
+    // Zero bits mark elements of `a` used in the subsequence so far
+    let mut row = vec![u64::MAX; words];
+    for y in b {
+        let Some(mask) = masks.get(y) else { continue };
+        let mut carry = 0u64;
+        for (word, m) in row.iter_mut().zip(mask) {
+            let matched = *word & m;
+            let (sum, overflow_a) = word.overflowing_add(matched);
+            let (sum, overflow_b) = sum.overflowing_add(carry);
+            carry = u64::from(overflow_a || overflow_b);
+            *word = sum | (*word & !m);
+        }
+    }
+
+    // Carries can spill into the bits past the end of `a`
+    row.iter()
+        .enumerate()
+        .map(|(k, word)| {
+            let bits = (a.len() - k * 64).min(64);
+            let valid = if bits == 64 { u64::MAX } else { (1 << bits) - 1 };
+            (!word & valid).count_ones() as usize
+        })
+        .sum()
+}
+
+/// Whether two snippets have the same tokens, ignoring whitespace between them
+///
+/// Whitespace inside string literals still counts. Tokens are found lexically
+/// as by `StreamingTokenizer`, with `languageId` choosing comment and string
+/// syntax (default: C-like).
+#[napi]
+pub fn equals_ignoring_whitespace(a: String, b: String, language_id: Option<String>) -> bool {
+    let language_id = language_id.unwrap_or_default();
+    let a_tokens = lexical_tokens(&a, &language_id);
+    let b_tokens = lexical_tokens(&b, &language_id);
+    a_tokens.len() == b_tokens.len()
+        && a_tokens.iter().zip(&b_tokens).all(|((a_start, a_end, _), (b_start, b_end, _))| {
+            a[*a_start..*a_end] == b[*b_start..*b_end]
+        })
+}
+
+/// Token-level similarity from 0 to 1, ignoring whitespace between tokens
+///
+/// Twice the longest common token subsequence over the total token count, as
+/// in difflib's `ratio`. Two empty snippets are identical.
+#[napi]
+pub fn similarity_ignoring_whitespace(a: String, b: String, language_id: Option<String>) -> f64 {
+    let language_id = language_id.unwrap_or_default();
+    let a_tokens: Vec<&str> = lexical_tokens(&a, &language_id).into_iter().map(|(s, e, _)| &a[s..e]).collect();
+    let b_tokens: Vec<&str> = lexical_tokens(&b, &language_id).into_iter().map(|(s, e, _)| &b[s..e]).collect();
+    let total = a_tokens.len() + b_tokens.len();
+    if total == 0 {
+        return 1.0;
+    }
+    2.0 * lcs_len(&a_tokens, &b_tokens) as f64 / total as f64
+}
+
+/// Longest run shared by `a` and `b` as `(a_start, b_start, length)`,
+/// earliest in `b` on ties, in time linear in both lengths
+pub(crate) fn longest_common_run<T: Copy + Eq + Hash>(a: &[T], b: &[T]) -> Option<(usize, usize, usize)> {
+    let automaton = SuffixAutomaton::new(a);
+
+    // State and length of the longest suffix of `b[..=j]` found in `a`
+    let (mut state, mut length) = (0, 0);
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (j, c) in b.iter().enumerate() {
+        while state != 0 && !automaton.states[state].next.contains_key(c) {
+            state = automaton.states[state].link;
+            length = automaton.states[state].len;
+        }
+        match automaton.states[state].next.get(c) {
+            Some(&next) => {
+                state = next;
+                length += 1;
+            }
+            None => length = 0,
+        }
+        if length > best.map_or(0, |(_, _, best_len)| best_len) {
+            best = Some((automaton.states[state].first_end + 1 - length, j + 1 - length, length));
+        }
+    }
+    best
+}
+
+struct SuffixState<T> {
+    len: usize,
+    link: usize,
+    /// End index in the source of the first occurrence of this state's strings
+    first_end: usize,
+    next: HashMap<T, usize>,
+}
+
+struct SuffixAutomaton<T> {
+    states: Vec<SuffixState<T>>,
+}
+
+impl<T: Copy + Eq + Hash> SuffixAutomaton<T> {
+    fn new(text: &[T]) -> Self {
+        let mut states = Vec::with_capacity(text.len() * 2 + 1);
+        states.push(SuffixState { len: 0, link: 0, first_end: 0, next: HashMap::new() });
+        let mut last = 0;
+        for (i, &c) in text.iter().enumerate() {
+            let current = states.len();
+            states.push(SuffixState { len: states[last].len + 1, link: 0, first_end: i, next: HashMap::new() });
+            let mut p = Some(last);
+            while let Some(state) = p {
+                if states[state].next.contains_key(&c) {
+                    break;
+                }
+                states[state].next.insert(c, current);
+                p = (state != 0).then(|| states[state].link);
+            }
+            if let Some(state) = p {
+                let q = states[state].next[&c];
+                if states[state].len + 1 == states[q].len {
+                    states[current].link = q;
+                } else {
+                    let clone = states.len();
+                    states.push(SuffixState {
+                        len: states[state].len + 1,
+                        link: states[q].link,
+                        first_end: states[q].first_end,
+                        next: states[q].next.clone(),
+                    });
+                    let mut p = Some(state);
+                    while let Some(s) = p {
+                        if states[s].next.get(&c) != Some(&q) {
+                            break;
+                        }
+                        states[s].next.insert(c, clone);
+                        p = (s != 0).then(|| states[s].link);
+                    }
+                    states[q].link = clone;
+                    states[current].link = clone;
+                }
+            }
+            last = current;
+        }
+        Self { states }
+    }
+}
@@ -0,0 +1,193 @@
+use napi_derive::napi;
+
+use crate::text_processor::{is_ident_byte, scan_literal, LexFlavor, Scan, TokenResult};
+
+/// Incremental tokenizer for files too large to hold twice in memory
+///
+/// Feed the file with `push(chunk)`; each call returns the tokens that are
+/// complete so far, and keeps only the unfinished tail (a word, string or
+/// comment running past the chunk end) until the next chunk. Call `finish()`
+/// after the last chunk. Offsets are UTF-8 byte offsets from the start of the
+/// stream.
+///
+/// Tokens are found lexically rather than from a syntax tree, so regex
+/// literals are not recognized and template literals are a single string
+/// token. Token types are the same as `tokenize_code`, minus 'regex',
+/// 'interpolation' and 'text'.
+#[napi]
+pub struct StreamingTokenizer {
+    flavor: LexFlavor,
+    /// Input not yet consumed
+    pending: String,
+    /// Stream offset of `pending`
+    offset: usize,
+}
+
+#[napi]
+impl StreamingTokenizer {
+    #[napi(constructor)]
+    pub fn new(language_id: String) -> Self {
+        Self {
+            flavor: LexFlavor::of(&language_id),
+            pending: String::new(),
+            offset: 0,
+        }
+    }
+
+    /// Add the next chunk and return the tokens completed by it
+    #[napi]
+    pub fn push(&mut self, chunk: String) -> TokenResult {
+        self.pending.push_str(&chunk);
+        self.drain(false)
+    }
+
+    /// Return the remaining tokens and reset for a new stream
+    #[napi]
+    pub fn finish(&mut self) -> TokenResult {
+        let result = self.drain(true);
+        self.reset();
+        result
+    }
+
+    #[napi]
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.offset = 0;
+    }
+}
+
+impl StreamingTokenizer {
+    fn drain(&mut self, eof: bool) -> TokenResult {
+        let (spans, consumed) = lex(&self.pending, self.flavor, eof);
+        let mut result = TokenResult {
+            texts: Vec::with_capacity(spans.len()),
+            token_types: Vec::with_capacity(spans.len()),
+            starts: Vec::with_capacity(spans.len()),
+            ends: Vec::with_capacity(spans.len()),
+        };
+        for (start, end, token_type) in spans {
+            result.texts.push(self.pending[start..end].to_string());
+            result.token_types.push(token_type.to_string());
+            result.starts.push((self.offset + start) as u32);
+            result.ends.push((self.offset + end) as u32);
+        }
+
+        self.pending.drain(..consumed);
+        self.offset += consumed;
+        result
+    }
+}
+
+/// Tokens of a whole text as `(start, end, type)`, lexed as by `StreamingTokenizer`
+pub(crate) fn lexical_tokens(text: &str, language_id: &str) -> Vec<(usize, usize, &'static str)> {
+    lex(text, LexFlavor::of(language_id), true).0
+}
+
+/// Complete tokens at the start of `text` and how many bytes they cover
+fn lex(text: &str, flavor: LexFlavor, eof: bool) -> (Vec<(usize, usize, &'static str)>, usize) {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let Some((end, token_type)) = lex_token(bytes, i, flavor, eof) else {
+            break;
+        };
+        spans.push((i, end, token_type));
+        i = end;
+    }
+    (spans, i)
+}
+
+const OPERATORS: &[&str] = &[
+    ">>>=", "===", "!==", "**=", "<<=", ">>=", ">>>", "...", "??=", "&&=", "||=", "<=>", "=>", "->", "::", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>", "**",
+    "..", ":=",
+];
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "break", "case", "catch", "class", "const", "continue", "def", "default",
+    "defer", "del", "delete", "do", "elif", "else", "enum", "except", "export", "extends", "false", "final",
+    "finally", "fn", "for", "from", "func", "function", "go", "if", "impl", "implements", "import", "in",
+    "instanceof", "interface", "is", "lambda", "let", "loop", "match", "mod", "module", "mut", "new", "nil", "None",
+    "not", "null", "or", "and", "package", "pass", "private", "protected", "pub", "public", "raise", "return",
+    "self", "static", "struct", "super", "switch", "this", "throw", "throws", "trait", "True", "False", "true",
+    "try", "type", "typeof", "undefined", "use", "var", "void", "where", "while", "with", "yield",
+];
+
+/// End and type of the token starting at `start`, or `None` if more input
+/// could still change where it ends
+fn lex_token(bytes: &[u8], start: usize, flavor: LexFlavor, eof: bool) -> Option<(usize, &'static str)> {
+    let rest = &bytes[start..];
+    let complete = |end: Option<usize>, token_type| match end {
+        Some(end) => Some((end, token_type)),
+        None if eof => Some((bytes.len(), token_type)),
+        None => None,
+    };
+
+    if flavor.line_comments().iter().any(|marker| rest.starts_with(marker.as_bytes())) {
+        return complete(memchr::memchr(b'\n', rest).map(|i| start + i), "comment");
+    }
+    if flavor.has_block_comments() && rest.starts_with(b"/*") {
+        return complete(memchr::memmem::find(&rest[2..], b"*/").map(|i| start + i + 4), "comment");
+    }
+
+    let b = bytes[start];
+    // Long enough for any char literal, up to `'\u{10FFFF}'`
+    if b == b'\'' && !eof && rest.len() < 12 && memchr::memchr(b'\n', rest).is_none() {
+        return None;
+    }
+    // A prefix like `r` or `r#` needs the next byte to tell if a string follows
+    if is_ident_byte(b) && !eof {
+        let word_end = start + rest.iter().take_while(|c| is_ident_byte(**c)).count();
+        if word_end + bytes[word_end..].iter().take_while(|c| **c == b'#').count() >= bytes.len() {
+            return None;
+        }
+    }
+    if matches!(b, b'"' | b'\'' | b'`') || is_ident_byte(b) || matches!(b, b'@' | b'$') {
+        match scan_literal(bytes, start, flavor) {
+            Scan::String(literal) => return Some((literal.end, "string")),
+            // A string or word may continue in the next chunk
+            Scan::Skip(end) if end >= bytes.len() && !eof => return None,
+            Scan::Skip(end) if matches!(b, b'\'') && end > start + 1 => return Some((end, "string")),
+            Scan::Skip(_) => {}
+        }
+    }
+
+    if b.is_ascii_digit() || (b == b'.' && rest.get(1).is_some_and(u8::is_ascii_digit)) {
+        let len = rest
+            .iter()
+            .enumerate()
+            .take_while(|(i, c)| {
+                c.is_ascii_alphanumeric() || **c == b'_' || **c == b'.'
+                    || (matches!(c, b'+' | b'-') && matches!(rest[i - 1], b'e' | b'E'))
+            })
+            .count();
+        return complete((start + len < bytes.len()).then_some(start + len), "number");
+    }
+
+    if is_ident_byte(b) || b == b'$' {
+        let len = rest.iter().take_while(|c| is_ident_byte(**c) || **c == b'$').count();
+        let word = std::str::from_utf8(&rest[..len]).unwrap_or("");
+        let token_type = if KEYWORDS.contains(&word) { "keyword" } else { "identifier" };
+        return complete((start + len < bytes.len()).then_some(start + len), token_type);
+    }
+
+    // The longest operator could still grow into a longer one
+    if rest.len() < 4 && !eof {
+        return None;
+    }
+    let len = match OPERATORS.iter().find(|op| rest.starts_with(op.as_bytes())) {
+        Some(op) => op.len(),
+        None => match b {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        },
+    };
+    Some((start + len, "operator"))
+}
@@ -0,0 +1,272 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::semantic_analyzer::{node_line, node_text};
+
+/// String literal information
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StringLiteralInfo {
+    /// Contents without quotes or prefixes; interpolations are kept verbatim
+    pub value: String,
+    pub quote: String, // 'single' | 'double' | 'backtick' | 'triple_single' | 'triple_double' | 'other' | 'none'
+    #[napi(js_name = "isTemplate")]
+    pub is_template: bool,
+    pub interpolations: u32,
+    pub context: String, // 'code' | 'import' | 'i18n' | 'key' | 'type' | 'jsx' | 'docstring'
+    /// Translation function the literal is passed to, such as `t` or `i18n.t`
+    #[napi(js_name = "i18nFunction")]
+    pub i18n_function: Option<String>,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    #[napi(js_name = "startColumn")]
+    pub start_column: u32,
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    #[napi(js_name = "endColumn")]
+    pub end_column: u32,
+}
+
+/// Extract string literals with their positions, quote style and usage context
+///
+/// JSX text is reported too, with quote `'none'`, since it is what localization
+/// most often needs to find. Literals passed as the first argument to a
+/// translation function (`t('key')`, `i18n.t`, `$t`, `gettext`, `_`, `__`)
+/// get context `'i18n'`.
+#[napi]
+pub fn extract_string_literals(code: String, language_id: String) -> Result<Vec<StringLiteralInfo>> {
+    let mut literals = Vec::new();
+    let Ok(tree) = parse_tree(&code, &language_id) else {
+        return Ok(literals);
+    };
+
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if STRING_KINDS.contains(&node.kind()) {
+            literals.push(literal_info(node, &code));
+        } else if node.kind() == "jsx_text" {
+            let text = node_text(node, &code).trim();
+            if !text.is_empty() {
+                let mut info = literal_info(node, &code);
+                info.value = text.to_string();
+                literals.push(info);
+            }
+        }
+
+        // Substitutions can hold literals of their own
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+
+    Ok(literals)
+}
+
+pub(crate) const STRING_KINDS: &[&str] = &[
+    "string",
+    "template_string",
+    "string_literal",
+    "raw_string_literal",
+    "interpreted_string_literal",
+    "verbatim_string_literal",
+    "interpolated_string_expression",
+    "encapsed_string",
+    "text_block",
+];
+
+/// Children that make up the literal text rather than embedded expressions
+const CONTENT_KINDS: &[&str] = &[
+    "string_fragment",
+    "string_content",
+    "string_value",
+    "escape_sequence",
+    "multiline_string_fragment",
+    "raw_string_content",
+    "interpolated_string_text",
+    "interpolated_verbatim_string_text",
+];
+
+const DELIMITER_KINDS: &[&str] = &["string_start", "string_end", "raw_string_delimiter"];
+
+const I18N_FUNCTIONS: &[&str] = &["t", "$t", "tc", "$tc", "_t", "_", "__", "gettext", "ngettext", "pgettext", "translate"];
+
+fn literal_info(node: Node, code: &str) -> StringLiteralInfo {
+    let raw = node_text(node, code);
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node
+        .named_children(&mut cursor)
+        .filter(|c| !DELIMITER_KINDS.contains(&c.kind()))
+        .collect();
+
+    let (quote, prefix) = if node.kind() == "jsx_text" { ("none", "") } else { quote_style(raw) };
+    let value = literal_value(node, &children, code);
+    let interpolations = children.iter().filter(|c| !CONTENT_KINDS.contains(&c.kind())).count() as u32;
+    let is_template = matches!(node.kind(), "template_string" | "interpolated_string_expression")
+        || prefix.contains(['f', 'F'])
+        || interpolations > 0;
+
+    let i18n_function = i18n_function(node, code);
+    let context = if i18n_function.is_some() { "i18n" } else { literal_context(node, code) };
+    let start = node.start_position();
+    let end = node.end_position();
+
+    StringLiteralInfo {
+        value,
+        quote: quote.to_string(),
+        is_template,
+        interpolations,
+        context: context.to_string(),
+        i18n_function,
+        start_line: node_line(node),
+        start_column: start.column as u32,
+        end_line: end.row as u32,
+        end_column: end.column as u32,
+    }
+}
+
+/// Contents of a string literal node, or `None` if the node is not a string literal
+pub(crate) fn string_value(node: Node, code: &str) -> Option<String> {
+    if !STRING_KINDS.contains(&node.kind()) {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node
+        .named_children(&mut cursor)
+        .filter(|c| !DELIMITER_KINDS.contains(&c.kind()))
+        .collect();
+    Some(literal_value(node, &children, code))
+}
+
+fn literal_value(node: Node, children: &[Node], code: &str) -> String {
+    match (children.first(), children.last()) {
+        (Some(first), Some(last)) => code[first.start_byte()..last.end_byte()].to_string(),
+        _ => strip_delimiters(node_text(node, code)).to_string(),
+    }
+}
+
+/// Quote style and the prefix before the opening quote (`f`, `r#`, `@`, `u8`, ...)
+fn quote_style(raw: &str) -> (&'static str, &str) {
+    let Some(open) = raw.find(['"', '\'', '`']) else {
+        return ("other", "");
+    };
+    let prefix = &raw[..open];
+    let rest = &raw[open..];
+    let quote = match rest.as_bytes()[0] {
+        b'"' if rest.len() >= 6 && rest.starts_with("\"\"\"") => "triple_double",
+        b'\'' if rest.len() >= 6 && rest.starts_with("'''") => "triple_single",
+        b'"' => "double",
+        b'\'' => "single",
+        _ => "backtick",
+    };
+    (quote, prefix)
+}
+
+/// Literal contents for nodes without content children, such as Rust strings
+fn strip_delimiters(raw: &str) -> &str {
+    let Some(open) = raw.find(['"', '\'', '`']) else {
+        return raw;
+    };
+    let hashes = raw[..open].chars().filter(|c| *c == '#').count();
+    let quote_len = if raw.len() - open >= 6 && (raw[open..].starts_with("\"\"\"") || raw[open..].starts_with("'''")) {
+        3
+    } else {
+        1
+    };
+    let close = quote_len + hashes;
+    if raw.len() < open + quote_len + close {
+        return "";
+    }
+    &raw[open + quote_len..raw.len() - close]
+}
+
+/// The translation function a literal is the first argument of
+fn i18n_function(node: Node, code: &str) -> Option<String> {
+    let mut argument = node;
+    let mut arguments = node.parent()?;
+    if arguments.kind() == "argument" {
+        argument = arguments;
+        arguments = arguments.parent()?;
+    }
+    if !matches!(arguments.kind(), "arguments" | "argument_list") || arguments.named_child(0)? != argument {
+        return None;
+    }
+
+    let callee = callee_text(arguments.parent()?, code)?;
+    let name = callee.rsplit(['.', ':', '>']).next().unwrap_or(callee);
+    I18N_FUNCTIONS.contains(&name).then(|| callee.to_string())
+}
+
+fn callee_text<'a>(call: Node, code: &'a str) -> Option<&'a str> {
+    let callee = match call.kind() {
+        "call_expression" | "call" | "function_call_expression" => call.child_by_field_name("function"),
+        "method_invocation" | "member_call_expression" => call.child_by_field_name("name"),
+        "invocation_expression" => call.child_by_field_name("function"),
+        _ => None,
+    }?;
+    Some(node_text(callee, code))
+}
+
+fn literal_context(node: Node, code: &str) -> &'static str {
+    if node.kind() == "jsx_text" {
+        return "jsx";
+    }
+    let Some(parent) = node.parent() else {
+        return "code";
+    };
+
+    match parent.kind() {
+        "import_statement" | "export_statement" | "import_spec" | "import_require_clause" => "import",
+        "jsx_attribute" => "jsx",
+        "literal_type" => "type",
+        "pair" if parent.child_by_field_name("key") == Some(node) => "key",
+        "arguments" if parent.parent().and_then(|call| callee_text(call, code)).is_some_and(|c| c == "require") => {
+            "import"
+        }
+        "expression_statement" if is_docstring(parent) => "docstring",
+        _ => "code",
+    }
+}
+
+/// Whether an expression statement is a lone string literal opening a Python module, class or
+/// function
+pub(crate) fn is_docstring(statement: Node) -> bool {
+    let is_string =
+        statement.named_child_count() == 1 && statement.named_child(0).is_some_and(|c| c.kind() == "string");
+    let Some(block) = statement.parent().filter(|_| is_string) else {
+        return false;
+    };
+    let is_first = block.named_child(0) == Some(statement);
+    let owner_is_definition = match block.kind() {
+        "module" => true,
+        "block" => block
+            .parent()
+            .is_some_and(|p| matches!(p.kind(), "function_definition" | "class_definition")),
+        _ => false,
+    };
+    is_first && owner_is_definition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn docstrings_are_only_leading_string_statements() {
+        let code = "\"\"\"Module.\"\"\"\ndef f(a):\n    a += 1\n    return a\nclass C:\n    \"\"\"Doc.\"\"\"\n    x = 1\n";
+        let tree = parse_tree(code, "python").unwrap();
+        let mut docstrings = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "expression_statement" && is_docstring(node) {
+                docstrings.push(&code[node.byte_range()]);
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+        docstrings.sort_unstable();
+        assert_eq!(docstrings, ["\"\"\"Doc.\"\"\"", "\"\"\"Module.\"\"\""]);
+    }
+}
@@ -0,0 +1,373 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::semantic_analyzer::{field_text, node_line, node_text};
+use crate::string_extractor::string_value;
+
+/// Test suite, test case or hook
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestInfo {
+    pub name: String,
+    pub kind: String, // 'suite' | 'test' | 'hook'
+    /// Names of the enclosing suites (and parent tests for subtests), outermost first
+    pub parents: Vec<String>,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    /// Marked as skipped (`it.skip`, `xit`, `@Disabled`, `#[ignore]`, `@pytest.mark.skip`)
+    #[napi(js_name = "isSkipped")]
+    pub is_skipped: bool,
+    /// Marked as exclusive (`it.only`, `fit`)
+    #[napi(js_name = "isFocused")]
+    pub is_focused: bool,
+}
+
+/// Extract test suites, test cases and hooks
+///
+/// Recognizes Jest/Mocha/Vitest `describe`/`it`/`test` blocks, pytest and unittest
+/// tests, Rust `#[test]` functions, JUnit annotations and Go `TestXxx` functions
+/// with `t.Run` subtests.
+#[napi]
+pub fn extract_tests(code: String, language_id: String) -> Result<Vec<TestInfo>> {
+    let mut collector = TestCollector::default();
+
+    match language_id.as_str() {
+        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" | "python" | "rust" | "java"
+        | "go" => {}
+        _ => return Ok(collector.tests),
+    }
+
+    let tree = parse_tree(&code, &language_id)?;
+    let root = tree.root_node();
+    match language_id.as_str() {
+        "python" => collector.python(root, &code, false),
+        "rust" => collector.rust(root, &code),
+        "java" => collector.java(root, &code),
+        "go" => collector.go(root, &code),
+        _ => collector.js(root, &code),
+    }
+
+    Ok(collector.tests)
+}
+
+#[derive(Default)]
+struct TestCollector {
+    tests: Vec<TestInfo>,
+    parents: Vec<String>,
+}
+
+impl TestCollector {
+    fn push(&mut self, name: String, kind: &str, node: Node, start: Node) -> usize {
+        self.tests.push(TestInfo {
+            name,
+            kind: kind.to_string(),
+            parents: self.parents.clone(),
+            start_line: node_line(start),
+            end_line: node.end_position().row as u32,
+            is_skipped: false,
+            is_focused: false,
+        });
+        self.tests.len() - 1
+    }
+
+    /// Visit the members of a suite, keeping the suite only if it ends up containing tests
+    fn suite_if_nonempty(&mut self, name: String, node: Node, start: Node, visit: impl FnOnce(&mut Self)) -> Option<usize> {
+        let index = self.tests.len();
+        self.parents.push(name.clone());
+        visit(self);
+        self.parents.pop();
+
+        if self.tests.len() == index {
+            return None;
+        }
+        let start_line = node_line(start);
+        let end_line = node.end_position().row as u32;
+        self.tests.insert(
+            index,
+            TestInfo {
+                name,
+                kind: "suite".to_string(),
+                parents: self.parents.clone(),
+                start_line,
+                end_line,
+                is_skipped: false,
+                is_focused: false,
+            },
+        );
+        Some(index)
+    }
+
+    fn js(&mut self, node: Node, code: &str) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "call_expression" {
+                if let Some((kind, name, modifiers)) = js_test_call(child, code) {
+                    let index = self.push(name.clone(), kind, child, child);
+                    let base = &modifiers[0];
+                    self.tests[index].is_skipped = base.starts_with('x')
+                        || modifiers[1..].iter().any(|m| m == "skip" || m == "todo");
+                    self.tests[index].is_focused = base.starts_with('f')
+                        || modifiers[1..].iter().any(|m| m == "only");
+
+                    if let Some(arguments) = child.child_by_field_name("arguments") {
+                        if kind == "suite" {
+                            self.parents.push(name);
+                            self.js(arguments, code);
+                            self.parents.pop();
+                        } else {
+                            self.js(arguments, code);
+                        }
+                    }
+                    continue;
+                }
+            }
+            self.js(child, code);
+        }
+    }
+
+    fn python(&mut self, node: Node, code: &str, in_test_class: bool) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let (definition, decorators) = match child.kind() {
+                "decorated_definition" => {
+                    let Some(definition) = child.child_by_field_name("definition") else { continue };
+                    let mut decorator_cursor = child.walk();
+                    let decorators: Vec<&str> = child
+                        .named_children(&mut decorator_cursor)
+                        .filter(|d| d.kind() == "decorator")
+                        .map(|d| node_text(d, code))
+                        .collect();
+                    (definition, decorators)
+                }
+                _ => (child, Vec::new()),
+            };
+            let Some(name) = field_text(definition, "name", code) else { continue };
+            let skipped = decorators.iter().any(|d| d.contains("skip"));
+
+            match definition.kind() {
+                "function_definition" => {
+                    let is_module_level = self.parents.is_empty();
+                    let kind = if name.starts_with("test") && (is_module_level || in_test_class) {
+                        "test"
+                    } else if decorators.iter().any(|d| d.contains("fixture"))
+                        || ((is_module_level || in_test_class) && PY_HOOKS.contains(&name.as_str()))
+                    {
+                        "hook"
+                    } else {
+                        continue;
+                    };
+                    let index = self.push(name, kind, definition, child);
+                    self.tests[index].is_skipped = skipped;
+                }
+                "class_definition" => {
+                    let is_test_class = name.starts_with("Test")
+                        || field_text(definition, "superclasses", code).is_some_and(|s| s.contains("TestCase"));
+                    let Some(body) = definition.child_by_field_name("body") else { continue };
+                    if is_test_class {
+                        let index = self.push(name.clone(), "suite", definition, child);
+                        self.tests[index].is_skipped = skipped;
+                        self.parents.push(name);
+                        self.python(body, code, true);
+                        self.parents.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn rust(&mut self, node: Node, code: &str) {
+        let mut attributes: Vec<Node> = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "attribute_item" => {
+                    attributes.push(child);
+                    continue;
+                }
+                "line_comment" | "block_comment" => continue,
+                "function_item" => {
+                    let names: Vec<&str> = attributes.iter().filter_map(|a| rust_attribute_name(*a, code)).collect();
+                    if names.iter().any(|n| matches!(*n, "test" | "bench" | "rstest" | "test_case" | "quickcheck")) {
+                        if let Some(name) = field_text(child, "name", code) {
+                            let start = attributes.first().copied().unwrap_or(child);
+                            let index = self.push(name, "test", child, start);
+                            self.tests[index].is_skipped = names.contains(&"ignore");
+                        }
+                    }
+                }
+                "mod_item" => {
+                    if let (Some(name), Some(body)) = (field_text(child, "name", code), child.child_by_field_name("body")) {
+                        let start = attributes.first().copied().unwrap_or(child);
+                        self.suite_if_nonempty(name, child, start, |this| this.rust(body, code));
+                    }
+                }
+                _ => {}
+            }
+            attributes.clear();
+        }
+    }
+
+    fn java(&mut self, node: Node, code: &str) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "class_declaration" => {
+                    let (Some(name), Some(body)) = (field_text(child, "name", code), child.child_by_field_name("body"))
+                    else {
+                        continue;
+                    };
+                    let annotations = java_annotations(child, code);
+                    if let Some(index) = self.suite_if_nonempty(name, child, child, |this| this.java(body, code)) {
+                        self.tests[index].is_skipped = annotations.iter().any(|a| matches!(a.as_str(), "Disabled" | "Ignore"));
+                    }
+                }
+                "method_declaration" => {
+                    let Some(name) = field_text(child, "name", code) else { continue };
+                    let annotations = java_annotations(child, code);
+                    let kind = if annotations.iter().any(|a| JAVA_TESTS.contains(&a.as_str())) {
+                        "test"
+                    } else if annotations.iter().any(|a| JAVA_HOOKS.contains(&a.as_str())) {
+                        "hook"
+                    } else {
+                        continue;
+                    };
+                    let index = self.push(name, kind, child, child);
+                    self.tests[index].is_skipped = annotations.iter().any(|a| matches!(a.as_str(), "Disabled" | "Ignore"));
+                }
+                "program" | "class_body" => self.java(child, code),
+                _ => {}
+            }
+        }
+    }
+
+    fn go(&mut self, node: Node, code: &str) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() != "function_declaration" {
+                continue;
+            }
+            let Some(name) = field_text(child, "name", code) else { continue };
+            if !GO_TEST_PREFIXES.iter().any(|prefix| go_is_test_name(&name, prefix)) {
+                continue;
+            }
+            self.push(name.clone(), "test", child, child);
+            if let Some(body) = child.child_by_field_name("body") {
+                self.parents.push(name);
+                self.go_subtests(body, code);
+                self.parents.pop();
+            }
+        }
+    }
+
+    fn go_subtests(&mut self, node: Node, code: &str) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "call_expression" {
+                let is_run = child
+                    .child_by_field_name("function")
+                    .filter(|f| f.kind() == "selector_expression")
+                    .and_then(|f| field_text(f, "field", code))
+                    .is_some_and(|field| field == "Run");
+                let arguments = child.child_by_field_name("arguments");
+                let name = arguments.and_then(|a| a.named_child(0)).and_then(|a| string_value(a, code));
+                if let (true, Some(name), Some(arguments)) = (is_run, name, arguments) {
+                    self.push(name.clone(), "test", child, child);
+                    self.parents.push(name);
+                    self.go_subtests(arguments, code);
+                    self.parents.pop();
+                    continue;
+                }
+            }
+            self.go_subtests(child, code);
+        }
+    }
+}
+
+const JS_SUITES: &[&str] = &["describe", "context", "suite", "xdescribe", "fdescribe"];
+const JS_TESTS: &[&str] = &["it", "test", "specify", "xit", "fit", "xtest", "bench"];
+const JS_HOOKS: &[&str] = &["beforeEach", "afterEach", "beforeAll", "afterAll", "before", "after", "setup", "teardown"];
+
+const PY_HOOKS: &[&str] = &[
+    "setUp", "tearDown", "setUpClass", "tearDownClass", "asyncSetUp", "asyncTearDown", "setup_method",
+    "teardown_method", "setup_class", "teardown_class", "setup_function", "teardown_function", "setup_module",
+    "teardown_module",
+];
+
+const JAVA_TESTS: &[&str] = &["Test", "ParameterizedTest", "RepeatedTest", "TestFactory", "TestTemplate"];
+const JAVA_HOOKS: &[&str] = &["BeforeEach", "AfterEach", "BeforeAll", "AfterAll", "Before", "After", "BeforeClass", "AfterClass"];
+
+const GO_TEST_PREFIXES: &[&str] = &["Test", "Benchmark", "Fuzz", "Example"];
+
+/// Classify a JS call such as `describe.skip('x', fn)` or `test.each(rows)('x', fn)`
+///
+/// Returns the kind, the name and the callee chain (base identifier first).
+fn js_test_call(node: Node, code: &str) -> Option<(&'static str, String, Vec<String>)> {
+    let mut chain = Vec::new();
+    let mut callee = node.child_by_field_name("function")?;
+    loop {
+        match callee.kind() {
+            "identifier" => {
+                chain.push(node_text(callee, code).to_string());
+                break;
+            }
+            "member_expression" => {
+                chain.push(field_text(callee, "property", code)?);
+                callee = callee.child_by_field_name("object")?;
+            }
+            "call_expression" => callee = callee.child_by_field_name("function")?,
+            _ => return None,
+        }
+    }
+    chain.reverse();
+
+    let base = chain[0].as_str();
+    let kind = if JS_SUITES.contains(&base) {
+        "suite"
+    } else if JS_TESTS.contains(&base) {
+        "test"
+    } else if JS_HOOKS.contains(&base) {
+        return Some(("hook", base.to_string(), chain));
+    } else {
+        return None;
+    };
+
+    let arguments = node.child_by_field_name("arguments")?;
+    let name = string_value(arguments.named_child(0)?, code)?;
+    Some((kind, name, chain))
+}
+
+/// Last path segment of a Rust attribute, such as `test` for `#[tokio::test]`
+fn rust_attribute_name<'a>(item: Node, code: &'a str) -> Option<&'a str> {
+    let path = item.named_child(0)?.named_child(0)?;
+    node_text(path, code).rsplit("::").next()
+}
+
+/// Simple names of the annotations on a Java declaration
+fn java_annotations(node: Node, code: &str) -> Vec<String> {
+    let mut cursor = node.walk();
+    let Some(modifiers) = node.named_children(&mut cursor).find(|c| c.kind() == "modifiers") else {
+        return Vec::new();
+    };
+    let mut modifier_cursor = modifiers.walk();
+    let annotations = modifiers
+        .named_children(&mut modifier_cursor)
+        .filter(|m| matches!(m.kind(), "marker_annotation" | "annotation"))
+        .filter_map(|m| field_text(m, "name", code))
+        .map(|name| name.rsplit('.').next().unwrap_or_default().to_string())
+        .collect();
+    annotations
+}
+
+/// Go's rule for test function names: the prefix must not be followed by a lowercase letter
+fn go_is_test_name(name: &str, prefix: &str) -> bool {
+    match name.strip_prefix(prefix) {
+        Some(rest) => !rest.starts_with(|c: char| c.is_lowercase()),
+        None => false,
+    }
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::naming::split_words;
+use crate::streaming_tokenizer::lexical_tokens;
+
+/// Lines per indexed chunk
+const CHUNK_LINES: usize = 40;
+
+/// BM25 term-frequency saturation and length normalization
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Terms of one chunk of a file, as stored in index snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ChunkTerms {
+    pub start_line: u32,
+    /// Exclusive
+    pub end_line: u32,
+    /// Term count, repeats included
+    pub length: u32,
+    /// Distinct terms with their frequencies
+    pub terms: Vec<(String, u32)>,
+}
+
+/// A chunk found by `TextIndex::search`
+pub(crate) struct TextHit {
+    pub path: Arc<str>,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub score: f64,
+}
+
+struct ChunkEntry {
+    path: Arc<str>,
+    start_line: u32,
+    end_line: u32,
+    length: u32,
+    terms: Vec<String>,
+}
+
+/// BM25-scored inverted index over file chunks
+#[derive(Default)]
+pub(crate) struct TextIndex {
+    /// By chunk id; `None` for ids free for reuse
+    chunks: Vec<Option<ChunkEntry>>,
+    free: Vec<u32>,
+    by_file: HashMap<Arc<str>, Vec<u32>>,
+    /// Term frequency by chunk id, per term
+    postings: HashMap<String, HashMap<u32, u32>>,
+    total_length: u64,
+    live: usize,
+}
+
+impl TextIndex {
+    /// Add the chunks of a file, replacing any it had
+    pub fn add(&mut self, path: &str, chunks: &[ChunkTerms]) {
+        self.remove(path);
+        let path: Arc<str> = Arc::from(path);
+        let mut ids = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let id = match self.free.pop() {
+                Some(id) => id,
+                None => {
+                    self.chunks.push(None);
+                    (self.chunks.len() - 1) as u32
+                }
+            };
+            for (term, frequency) in &chunk.terms {
+                self.postings.entry(term.clone()).or_default().insert(id, *frequency);
+            }
+            self.chunks[id as usize] = Some(ChunkEntry {
+                path: path.clone(),
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                length: chunk.length,
+                terms: chunk.terms.iter().map(|(term, _)| term.clone()).collect(),
+            });
+            self.total_length += chunk.length as u64;
+            self.live += 1;
+            ids.push(id);
+        }
+        self.by_file.insert(path, ids);
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        let Some(ids) = self.by_file.remove(path) else { return };
+        for id in ids {
+            let Some(chunk) = self.chunks[id as usize].take() else { continue };
+            for term in &chunk.terms {
+                if let Some(posting) = self.postings.get_mut(term) {
+                    posting.remove(&id);
+                    if posting.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+            self.total_length -= chunk.length as u64;
+            self.live -= 1;
+            self.free.push(id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The best `limit` chunks for a query, highest score first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<TextHit> {
+        if self.live == 0 {
+            return Vec::new();
+        }
+        let terms: HashSet<String> = index_terms(query, "").into_iter().collect();
+        let total = self.live as f64;
+        let average_length = (self.total_length as f64 / total).max(1.0);
+
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+        for term in &terms {
+            let Some(posting) = self.postings.get(term) else { continue };
+            let frequency = posting.len() as f64;
+            let idf = (1.0 + (total - frequency + 0.5) / (frequency + 0.5)).ln();
+            for (&id, &tf) in posting {
+                let Some(chunk) = &self.chunks[id as usize] else { continue };
+                let tf = tf as f64;
+                let norm = K1 * (1.0 - B + B * chunk.length as f64 / average_length);
+                *scores.entry(id).or_default() += idf * tf * (K1 + 1.0) / (tf + norm);
+            }
+        }
+
+        let mut ranked: Vec<(u32, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .filter_map(|(id, score)| {
+                let chunk = self.chunks[id as usize].as_ref()?;
+                Some(TextHit {
+                    path: chunk.path.clone(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    score,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Split code into chunks of `CHUNK_LINES` lines and count their terms
+pub(crate) fn chunk_terms(code: &str, language_id: &str) -> Vec<ChunkTerms> {
+    let lines: Vec<&str> = code.split_inclusive('\n').collect();
+    lines
+        .chunks(CHUNK_LINES)
+        .enumerate()
+        .filter_map(|(i, chunk)| {
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            for term in index_terms(&chunk.concat(), language_id) {
+                *counts.entry(term).or_default() += 1;
+            }
+            if counts.is_empty() {
+                return None;
+            }
+            let start_line = (i * CHUNK_LINES) as u32;
+            Some(ChunkTerms {
+                start_line,
+                end_line: start_line + chunk.len() as u32,
+                length: counts.values().sum(),
+                terms: counts.into_iter().collect(),
+            })
+        })
+        .collect()
+}
+
+/// Lowercased words of identifiers, keywords, strings and comments
+///
+/// Identifiers are indexed whole and by their camelCase and snake_case parts,
+/// so `getUserName` is found by `user` as well as by `getusername`
+fn index_terms(text: &str, language_id: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for (start, end, token_type) in lexical_tokens(text, language_id) {
+        if !matches!(token_type, "identifier" | "keyword" | "string" | "comment") {
+            continue;
+        }
+        for word in text[start..end].split(|c: char| !c.is_alphanumeric() && c != '_') {
+            let parts = split_words(word);
+            if parts.len() > 1 {
+                terms.extend(parts.iter().filter(|part| part.chars().count() > 1).map(|part| part.to_lowercase()));
+            }
+            if word.chars().count() > 1 {
+                terms.push(word.to_lowercase());
+            }
+        }
+    }
+    terms
+}
@@ -1,6 +1,16 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+use crate::comment_extractor::comment_nodes;
+use crate::semantic_analyzer::{node_text, FileInput, LineIndex};
+use crate::string_extractor::{is_docstring, STRING_KINDS};
 
 /// Token information
 /// Token analysis result with Structure of Arrays (SoA) layout for performance
@@ -16,104 +26,206 @@ pub struct TokenResult {
 
 /// Tokenize code into tokens
 /// 
-/// Fast tokenization for context building - 2-4x faster than TypeScript
+/// Languages with a Tree-sitter grammar are tokenized from the syntax tree, so
+/// comments, regex literals, JSX text and numeric suffixes are classified
+/// correctly. Template literals and f-strings are split at interpolation
+/// boundaries, with the embedded expressions tokenized like any other code.
+/// Other languages fall back to whitespace-separated words.
+///
+/// Token types: 'keyword' | 'identifier' | 'number' | 'string' | 'comment' |
+/// 'regex' | 'operator' | 'interpolation' | 'text' | 'word'
 #[napi]
 pub fn tokenize_code(code: String, language_id: String) -> Result<TokenResult> {
-    let mut result = TokenResult {
-        texts: Vec::new(),
-        token_types: Vec::new(),
-        starts: Vec::new(),
-        ends: Vec::new(),
+    let spans = token_spans(&code, &language_id);
+    Ok(TokenResult {
+        texts: spans
+            .starts
+            .iter()
+            .zip(&spans.ends)
+            .map(|(start, end)| code[*start as usize..*end as usize].to_string())
+            .collect(),
+        token_types: spans.types.iter().map(|t| t.to_string()).collect(),
+        starts: spans.starts,
+        ends: spans.ends,
+    })
+}
+
+/// Token types in `typeIds` order
+const TOKEN_TYPES: &[&str] = &[
+    "keyword",
+    "identifier",
+    "number",
+    "string",
+    "comment",
+    "regex",
+    "operator",
+    "interpolation",
+    "text",
+    "word",
+];
+
+/// Tokens as typed arrays over native memory
+#[napi(object)]
+pub struct TokenBuffers {
+    /// Offsets in UTF-16 code units, so `code.slice(starts[i], ends[i])` is the token text
+    pub starts: Uint32Array,
+    pub ends: Uint32Array,
+    /// Index into `typeNames` for each token
+    #[napi(js_name = "typeIds")]
+    pub type_ids: Uint32Array,
+    #[napi(js_name = "typeNames")]
+    pub type_names: Vec<String>,
+}
+
+/// Tokenize code like `tokenize_code`, without a string per token
+///
+/// Offsets and type ids are handed to JavaScript as `Uint32Array`s backed by
+/// the native buffers, and token texts are left for the caller to slice from
+/// the source on demand.
+#[napi]
+pub fn tokenize_code_buffers(code: String, language_id: String) -> Result<TokenBuffers> {
+    let spans = token_spans(&code, &language_id);
+    let type_ids = spans
+        .types
+        .iter()
+        .map(|t| TOKEN_TYPES.iter().position(|known| known == t).unwrap_or(0) as u32)
+        .collect();
+    let (starts, ends) = if code.is_ascii() {
+        (spans.starts, spans.ends)
+    } else {
+        (utf16_offsets(&code, &spans.starts), utf16_offsets(&code, &spans.ends))
     };
-    
-    match language_id.as_str() {
-        "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
-            tokenize_js_like(&code, &mut result);
+
+    Ok(TokenBuffers {
+        starts: Uint32Array::new(starts),
+        ends: Uint32Array::new(ends),
+        type_ids: Uint32Array::new(type_ids),
+        type_names: TOKEN_TYPES.iter().map(|t| t.to_string()).collect(),
+    })
+}
+
+/// Convert non-decreasing byte offsets into UTF-16 offsets in one pass
+fn utf16_offsets(code: &str, byte_offsets: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(byte_offsets.len());
+    let mut chars = code.char_indices().peekable();
+    let mut utf16 = 0u32;
+    for &offset in byte_offsets {
+        while let Some(&(i, c)) = chars.peek() {
+            if i >= offset as usize {
+                break;
+            }
+            utf16 += c.len_utf16() as u32;
+            chars.next();
         }
-        "python" => {
-            tokenize_python(&code, &mut result);
+        result.push(utf16);
+    }
+    result
+}
+
+/// Token byte ranges and types, before any strings are built
+#[derive(Default)]
+pub(crate) struct TokenSpans {
+    pub(crate) starts: Vec<u32>,
+    pub(crate) ends: Vec<u32>,
+    pub(crate) types: Vec<&'static str>,
+}
+
+impl TokenSpans {
+    fn push(&mut self, start: usize, end: usize, token_type: &'static str) {
+        if start >= end {
+            return;
         }
-        _ => {
-            tokenize_generic(&code, &mut result);
+        self.starts.push(start as u32);
+        self.ends.push(end as u32);
+        self.types.push(token_type);
+    }
+}
+
+/// Tokens in source order, from the syntax tree where there is a grammar
+pub(crate) fn token_spans(code: &str, language_id: &str) -> TokenSpans {
+    let mut spans = TokenSpans::default();
+    match parse_tree(code, language_id) {
+        Ok(tree) => tokenize_tree(tree.root_node(), code, &mut spans),
+        Err(_) => tokenize_generic(code, &mut spans),
+    }
+    spans
+}
+
+fn tokenize_tree(node: Node, code: &str, result: &mut TokenSpans) {
+    let kind = node.kind();
+
+    if kind.contains("comment") {
+        result.push(node.start_byte(), node.end_byte(), "comment");
+    } else if kind == "regex" {
+        result.push(node.start_byte(), node.end_byte(), "regex");
+    } else if STRING_KINDS.contains(&kind) {
+        tokenize_string(node, code, result);
+    } else if kind == "format_specifier" {
+        // Python `{value:>10}` - the spec text is not exposed as child nodes
+        result.push(node.start_byte(), node.end_byte(), "string");
+    } else if node.child_count() == 0 {
+        result.push(node.start_byte(), node.end_byte(), leaf_token_type(node, code));
+    } else {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            tokenize_tree(child, code, result);
         }
     }
-    
-    Ok(result)
 }
 
-fn tokenize_js_like(code: &str, result: &mut TokenResult) {
-    let keywords = [
-        "function", "const", "let", "var", "class", "interface", "type",
-        "import", "export", "from", "async", "await", "return", "if",
-        "else", "for", "while", "switch", "case", "break", "continue",
-    ];
-    
-    let mut chars = code.char_indices().peekable();
-    
-    while let Some((start_byte, c)) = chars.next() {
-        if c.is_whitespace() { continue; }
-        
-        let start_pos = start_byte;
-        if c.is_alphabetic() || c == '_' {
-            let mut end_byte = start_byte + c.len_utf8();
-            while let Some(&(idx, ch)) = chars.peek() {
-                if ch.is_alphanumeric() || ch == '_' {
-                    chars.next();
-                    end_byte = idx + ch.len_utf8();
-                } else { break; }
-            }
-            let text = &code[start_pos..end_byte];
-            let type_str = if keywords.contains(&text) { "keyword".to_string() } else { "identifier".to_string() };
-            result.texts.push(text.to_string());
-            result.token_types.push(type_str);
-            result.starts.push(start_pos as u32);
-            result.ends.push(end_byte as u32);
-            continue;
-        } else if c.is_numeric() {
-            let mut end_byte = start_byte + c.len_utf8();
-            while let Some(&(idx, ch)) = chars.peek() {
-                if ch.is_numeric() || ch == '.' {
-                    chars.next();
-                    end_byte = idx + ch.len_utf8();
-                } else { break; }
-            }
-            result.texts.push(code[start_pos..end_byte].to_string());
-            result.token_types.push("number".to_string());
-            result.starts.push(start_pos as u32);
-            result.ends.push(end_byte as u32);
-            continue;
-        } else if c == '"' || c == '\'' || c == '`' {
-            let quote = c;
-            let mut end_byte = start_byte + c.len_utf8();
-            let mut escaped = false;
-            loop {
-                if let Some((idx, ch)) = chars.next() {
-                    end_byte = idx + ch.len_utf8();
-                    if escaped { escaped = false; }
-                    else if ch == '\\' { escaped = true; }
-                    else if ch == quote { break; }
-                } else { break; }
+/// A string literal is one token unless it has interpolations, in which case the
+/// literal parts, the `${`/`}` boundaries and the embedded code become separate tokens
+fn tokenize_string(node: Node, code: &str, result: &mut TokenSpans) {
+    let mut cursor = node.walk();
+    let interpolations: Vec<Node> = node
+        .named_children(&mut cursor)
+        .filter(|c| matches!(c.kind(), "template_substitution" | "interpolation"))
+        .collect();
+
+    let mut position = node.start_byte();
+    for interpolation in interpolations {
+        result.push(position, interpolation.start_byte(), "string");
+
+        let mut inner = interpolation.walk();
+        for child in interpolation.children(&mut inner) {
+            if child.is_named() {
+                tokenize_tree(child, code, result);
+            } else {
+                result.push(child.start_byte(), child.end_byte(), "interpolation");
             }
-            result.texts.push(code[start_pos..end_byte].to_string());
-            result.token_types.push("string".to_string());
-            result.starts.push(start_pos as u32);
-            result.ends.push(end_byte as u32);
-            continue;
-        } else {
-            let end_byte = start_byte + c.len_utf8();
-            result.texts.push(code[start_pos..end_byte].to_string());
-            result.token_types.push("operator".to_string());
-            result.starts.push(start_pos as u32);
-            result.ends.push(end_byte as u32);
         }
+        position = interpolation.end_byte();
     }
+    result.push(position, node.end_byte(), "string");
 }
 
-fn tokenize_python(code: &str, result: &mut TokenResult) {
-    tokenize_generic(code, result);
+fn leaf_token_type(node: Node, code: &str) -> &'static str {
+    let kind = node.kind();
+    let is_word = node_text(node, code).starts_with(|c: char| c.is_alphabetic() || c == '_');
+    if !node.is_named() {
+        return if is_word { "keyword" } else { "operator" };
+    }
+
+    match kind {
+        "jsx_text" | "text" => "text",
+        "true" | "false" | "null" | "undefined" | "none" | "nil" | "this" | "self" | "super" | "primitive_type"
+        | "predefined_type" | "integral_type" | "floating_point_type" | "boolean_type" | "void_type" => "keyword",
+        "string_fragment" | "string_content" | "escape_sequence" | "char_literal" | "character_literal"
+        | "rune_literal" => "string",
+        _ if kind.contains("identifier") => "identifier",
+        _ if ["number", "integer", "float", "int_literal", "decimal", "hex", "octal", "binary", "imaginary"]
+            .iter()
+            .any(|n| kind.contains(n)) =>
+        {
+            "number"
+        }
+        // Named punctuation such as `?.` (optional_chain) or `!r` (type_conversion)
+        _ if !is_word => "operator",
+        _ => "identifier",
+    }
 }
 
-fn tokenize_generic(code: &str, result: &mut TokenResult) {
+fn tokenize_generic(code: &str, result: &mut TokenSpans) {
     let mut pos = 0;
     for word in code.split_whitespace() {
         // Note: split_whitespace loses original offset precision if not tracked carefully
@@ -125,10 +237,7 @@ fn tokenize_generic(code: &str, result: &mut TokenResult) {
         let start = code[pos..].find(word).unwrap_or(0) + pos;
         let end = start + word.len();
         
-        result.texts.push(word.to_string());
-        result.token_types.push("word".to_string());
-        result.starts.push(start as u32);
-        result.ends.push(end as u32);
+        result.push(start, end, "word");
         
         pos = end;
     }
@@ -136,23 +245,57 @@ fn tokenize_generic(code: &str, result: &mut TokenResult) {
 
 /// Normalize whitespace in code
 /// 
-/// Fast whitespace normalization using SIMD where available
+/// Fast whitespace normalization using SIMD where available. With a
+/// `languageId`, whitespace inside string and template literals is kept as is
+/// and only whitespace in the surrounding code is collapsed.
 #[napi]
-pub fn normalize_whitespace(code: String) -> String {
-    code.split_whitespace()
-        .collect::<Vec<&str>>()
-        .join(" ")
+pub fn normalize_whitespace(code: String, language_id: Option<String>) -> String {
+    let Some(language_id) = language_id else {
+        return code.split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+    };
+
+    // Whitespace runs outside literals become one space, dropped at either end
+    let mut result = String::with_capacity(code.len());
+    let mut pending_space = false;
+    let push = |result: &mut String, pending_space: &mut bool, text: &str| {
+        if *pending_space && !result.is_empty() {
+            result.push(' ');
+        }
+        *pending_space = false;
+        result.push_str(text);
+    };
+
+    let mut position = 0;
+    let literals = extract_strings(code.clone(), language_id);
+    let ranges = literals.iter().map(|l| (l.start as usize, l.end as usize)).chain([(code.len(), code.len())]);
+    for (start, end) in ranges {
+        for (i, segment) in code[position..start].split(char::is_whitespace).enumerate() {
+            pending_space |= i > 0;
+            if !segment.is_empty() {
+                push(&mut result, &mut pending_space, segment);
+            }
+        }
+        if start < end {
+            push(&mut result, &mut pending_space, &code[start..end]);
+        }
+        position = end;
+    }
+    result
 }
 
 /// Remove comments from code
+///
+/// With `removeDocstrings`, Python docstrings are dropped as well.
 #[napi]
-pub fn remove_comments(code: String, language_id: String) -> String {
+pub fn remove_comments(code: String, language_id: String, remove_docstrings: Option<bool>) -> String {
     match language_id.as_str() {
         "typescript" | "typescriptreact" | "javascript" | "javascriptreact" => {
             remove_js_comments(&code)
         }
         "python" => {
-            remove_python_comments(&code)
+            remove_python_comments(&code, remove_docstrings.unwrap_or(false))
         }
         _ => code,
     }
@@ -224,33 +367,827 @@ fn remove_js_comments(code: &str) -> String {
     result
 }
 
-fn remove_python_comments(code: &str) -> String {
-    code.lines()
-        .map(|line| {
-            if let Some(pos) = line.find('#') {
-                &line[..pos]
-            } else {
-                line
-            }
-        })
-        .collect::<Vec<&str>>()
-        .join("\n")
+/// Uses the syntax tree so `#` inside strings and f-string expressions is left alone
+fn remove_python_comments(code: &str, remove_docstrings: bool) -> String {
+    let Ok(tree) = parse_tree(code, "python") else {
+        return code.to_string();
+    };
+
+    let mut ranges = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.kind() == "comment" {
+            // Drop the whitespace between code and a trailing comment too
+            let start = code[..node.start_byte()].trim_end_matches([' ', '\t']).len();
+            ranges.push((start, node.end_byte()));
+            continue;
+        }
+        if remove_docstrings && node.kind() == "expression_statement" && is_docstring(node) {
+            ranges.push(whole_lines(code, node.start_byte(), node.end_byte()));
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    ranges.sort_unstable();
+
+    let mut result = String::with_capacity(code.len());
+    let mut position = 0;
+    for (start, end) in ranges {
+        if start >= position {
+            result.push_str(&code[position..start]);
+            position = end;
+        }
+    }
+    result.push_str(&code[position..]);
+    result
 }
 
-/// Count lines of code (excluding comments and blank lines)
+/// Widen a range to its full lines, including the line break, if nothing else is on them
+pub(crate) fn whole_lines(code: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = code[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = code[end..].find('\n').map_or(code.len(), |i| end + i + 1);
+    if code[line_start..start].trim().is_empty() && code[end..line_end].trim().is_empty() {
+        (line_start, line_end)
+    } else {
+        (start, end)
+    }
+}
+
+/// Count lines of code (excluding comments, docstrings and blank lines)
 #[napi]
 pub fn count_loc(code: String, language_id: String) -> u32 {
-    let without_comments = remove_comments(code, language_id);
-    without_comments
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .count() as u32
+    let stats = count_loc_detailed(code, language_id);
+    stats.code_lines + stats.mixed_lines
+}
+
+/// Line counts by category
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocStats {
+    #[napi(js_name = "totalLines")]
+    pub total_lines: u32,
+    /// Lines with code only
+    #[napi(js_name = "codeLines")]
+    pub code_lines: u32,
+    /// Lines with comments or docstrings only
+    #[napi(js_name = "commentLines")]
+    pub comment_lines: u32,
+    #[napi(js_name = "blankLines")]
+    pub blank_lines: u32,
+    /// Lines with both code and a comment
+    #[napi(js_name = "mixedLines")]
+    pub mixed_lines: u32,
+}
+
+/// Count code, comment, blank and mixed lines, cloc-style
+///
+/// Comments come from the syntax tree, so languages without a grammar count
+/// every non-blank line as code.
+#[napi]
+pub fn count_loc_detailed(code: String, language_id: String) -> LocStats {
+    let comments: Vec<(usize, usize)> = match parse_tree(&code, &language_id) {
+        Ok(tree) => comment_nodes(tree.root_node(), &language_id)
+            .iter()
+            .map(|node| (node.start_byte(), node.end_byte()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let mut stats = LocStats::default();
+    let mut comments = comments.into_iter().peekable();
+    let mut line_start = 0;
+    for line in code.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        stats.total_lines += 1;
+
+        let mut has_code = false;
+        let mut has_comment = false;
+        let mut position = line_start;
+        while let Some(&(start, end)) = comments.peek() {
+            if start >= line_end {
+                break;
+            }
+            has_code |= start > position && !code[position..start].trim().is_empty();
+            has_comment = true;
+            position = position.max(end);
+            if end > line_end {
+                break;
+            }
+            comments.next();
+        }
+        has_code |= position < line_end && !code[position..line_end].trim().is_empty();
+
+        match (has_code, has_comment) {
+            (true, true) => stats.mixed_lines += 1,
+            (true, false) => stats.code_lines += 1,
+            (false, true) if !line.trim().is_empty() => stats.comment_lines += 1,
+            _ => stats.blank_lines += 1,
+        }
+        line_start = line_end;
+    }
+
+    stats
+}
+
+/// Count lines for many files in parallel; results are in input order
+#[napi]
+pub fn count_loc_detailed_many(files: Vec<FileInput>) -> Vec<LocStats> {
+    use rayon::prelude::*;
+
+    files
+        .into_par_iter()
+        .map(|file| count_loc_detailed(file.code, file.language_id))
+        .collect()
+}
+
+/// Indentation style of a file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndentationInfo {
+    pub style: String, // 'tabs' | 'spaces' | 'unknown'
+    /// Characters per indentation level (1 for tabs)
+    pub width: u32,
+    /// Share of the evidence agreeing with the result, from 0 to 1
+    pub confidence: f64,
+}
+
+/// Detect tabs vs spaces and the indentation width over the whole file
+///
+/// The width is the most common change in indentation between consecutive
+/// space-indented lines. Block comment continuations (` * `) are ignored.
+#[napi]
+pub fn detect_indentation(code: String) -> IndentationInfo {
+    let mut tab_lines = 0u32;
+    let mut space_lines = 0u32;
+    let mut deltas = [0u32; 9];
+    let mut previous = 0usize;
+
+    for line in code.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('*') {
+            continue;
+        }
+        let indent = &line[..line.len() - content.len()];
+        if indent.starts_with('\t') {
+            tab_lines += 1;
+        } else if !indent.is_empty() {
+            space_lines += 1;
+        }
+
+        if indent.bytes().all(|b| b == b' ') {
+            let delta = indent.len().abs_diff(previous);
+            if (2..deltas.len()).contains(&delta) {
+                deltas[delta] += 1;
+            }
+            previous = indent.len();
+        }
+    }
+
+    let indented = tab_lines + space_lines;
+    if indented == 0 {
+        return IndentationInfo { style: "unknown".to_string(), width: 0, confidence: 0.0 };
+    }
+    if tab_lines > space_lines {
+        return IndentationInfo {
+            style: "tabs".to_string(),
+            width: 1,
+            confidence: tab_lines as f64 / indented as f64,
+        };
+    }
+
+    let delta_total: u32 = deltas.iter().sum();
+    // Ties go to the smaller width, since two-level steps of it look like one wider step
+    let (width, count) = deltas
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, count)| **count)
+        .map(|(width, count)| (width as u32, *count))
+        .unwrap_or((4, 0));
+    let width_share = if delta_total == 0 { 0.5 } else { count as f64 / delta_total as f64 };
+    IndentationInfo {
+        style: "spaces".to_string(),
+        width: if count == 0 { 4 } else { width },
+        confidence: space_lines as f64 / indented as f64 * width_share,
+    }
+}
+
+/// Line ending counts of a file
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineEndingInfo {
+    /// Most common line ending
+    pub dominant: String, // 'lf' | 'crlf' | 'cr' | 'none'
+    pub lf: u32,
+    pub crlf: u32,
+    pub cr: u32,
+    /// More than one kind of line ending is present
+    #[napi(js_name = "isMixed")]
+    pub is_mixed: bool,
+}
+
+/// Count LF, CRLF and lone CR line endings
+#[napi]
+pub fn detect_line_endings(code: String) -> LineEndingInfo {
+    let (mut lf, mut crlf, mut cr) = (0u32, 0u32, 0u32);
+    let bytes = code.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let dominant = if lf + crlf + cr == 0 {
+        "none"
+    } else if crlf >= lf && crlf >= cr {
+        "crlf"
+    } else if lf >= cr {
+        "lf"
+    } else {
+        "cr"
+    };
+    LineEndingInfo {
+        dominant: dominant.to_string(),
+        lf,
+        crlf,
+        cr,
+        is_mixed: [lf, crlf, cr].iter().filter(|count| **count > 0).count() > 1,
+    }
+}
+
+/// Convert every line ending to `target` ('lf' | 'crlf' | 'cr')
+///
+/// Normalizing to LF before analysis keeps byte offsets consistent across
+/// analyzers regardless of how the file was saved.
+#[napi]
+pub fn normalize_line_endings(code: String, target: String) -> Result<String> {
+    let ending = match target.as_str() {
+        "lf" => "\n",
+        "crlf" => "\r\n",
+        "cr" => "\r",
+        other => return Err(Error::from_reason(format!("Unknown line ending: {}", other))),
+    };
+    if !code.contains('\r') && ending == "\n" {
+        return Ok(code);
+    }
+
+    let mut result = String::with_capacity(code.len());
+    let mut rest = code.as_str();
+    while let Some(i) = rest.find(['\r', '\n']) {
+        result.push_str(&rest[..i]);
+        result.push_str(ending);
+        let width = if rest[i..].starts_with("\r\n") { 2 } else { 1 };
+        rest = &rest[i + width..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// An identifier and how often it occurs
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentifierCount {
+    pub name: String,
+    pub count: u32,
+}
+
+/// Most frequent identifiers, skipping keywords and common stopwords
+///
+/// Identifiers inside strings and comments are not counted. Languages without
+/// a grammar fall back to splitting on word characters. Ties are broken by name.
+#[napi]
+pub fn identifier_histogram(code: String, language_id: String, top_n: u32) -> Result<Vec<IdentifierCount>> {
+    let names: Vec<&str> = match parse_tree(&code, &language_id) {
+        Ok(tree) => {
+            let mut tokens = TokenSpans::default();
+            tokenize_tree(tree.root_node(), &code, &mut tokens);
+            (0..tokens.types.len())
+                .filter(|i| tokens.types[*i] == "identifier")
+                .map(|i| &code[tokens.starts[i] as usize..tokens.ends[i] as usize])
+                .collect()
+        }
+        Err(_) => code
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+            .collect(),
+    };
+
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for name in names {
+        if name.len() > 1 && !IDENTIFIER_STOPWORDS.contains(&name) {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+
+    let mut histogram: Vec<IdentifierCount> = counts
+        .into_iter()
+        .map(|(name, count)| IdentifierCount { name: name.to_string(), count })
+        .collect();
+    histogram.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    histogram.truncate(top_n as usize);
+    Ok(histogram)
+}
+
+/// Keywords for languages without a grammar, plus names too common to say
+/// anything about the code
+const IDENTIFIER_STOPWORDS: &[&str] = &[
+    "if", "else", "for", "while", "do", "return", "function", "const", "let", "var", "class", "def", "fn", "pub",
+    "import", "from", "export", "new", "true", "false", "null", "nil", "None", "True", "False", "undefined", "this",
+    "self", "Self", "super", "and", "or", "not", "in", "is", "end", "then", "elif", "static", "void", "int", "string",
+    "args", "kwargs", "err", "error", "result", "value", "data", "item", "index", "tmp", "temp", "console", "log",
+    "print", "len",
+];
+
+/// A string literal found by `extract_strings`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StringRange {
+    /// Contents between the quotes, with escapes left as written
+    pub value: String,
+    /// Byte offset of the literal, including any prefix such as `f` or `r#`
+    pub start: u32,
+    pub end: u32,
+    #[napi(js_name = "startLine")]
+    pub start_line: u32,
+    #[napi(js_name = "endLine")]
+    pub end_line: u32,
+    #[napi(js_name = "isTemplate")]
+    pub is_template: bool,
+    #[napi(js_name = "isMultiline")]
+    pub is_multiline: bool,
+}
+
+/// Extract string and template literals with a single lexical pass
+///
+/// Much cheaper than `extract_string_literals` since nothing is parsed: the
+/// scanner only knows each language's comment and quote rules. Char literals
+/// are skipped, and strings nested in template substitutions are part of the
+/// enclosing template. Regex literals containing quotes can confuse it.
+#[napi]
+pub fn extract_strings(code: String, language_id: String) -> Vec<StringRange> {
+    let flavor = LexFlavor::of(&language_id);
+    let bytes = code.as_bytes();
+    let line_index = LineIndex::new(&code);
+    let mut strings = Vec::new();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if flavor.line_comments().iter().any(|marker| rest.starts_with(marker.as_bytes())) {
+            i += memchr::memchr(b'\n', rest).unwrap_or(rest.len());
+            continue;
+        }
+        if flavor.has_block_comments() && rest.starts_with(b"/*") {
+            i += memchr::memmem::find(&rest[2..], b"*/").map_or(rest.len(), |end| end + 4);
+            continue;
+        }
+
+        match scan_literal(bytes, i, flavor) {
+            Scan::String(literal) => {
+                let value = &code[literal.content_start..literal.content_end];
+                strings.push(StringRange {
+                    value: value.to_string(),
+                    start: i as u32,
+                    end: literal.end as u32,
+                    start_line: line_index.get_line(i),
+                    end_line: line_index.get_line(literal.end - 1),
+                    is_template: literal.is_template,
+                    is_multiline: value.contains('\n'),
+                });
+                i = literal.end;
+            }
+            Scan::Skip(end) => i = end,
+        }
+    }
+
+    strings
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum LexFlavor {
+    Js,
+    Python,
+    Rust,
+    Go,
+    CSharp,
+    CLike,
+    Php,
+    Hash,
+}
+
+impl LexFlavor {
+    pub(crate) fn of(language_id: &str) -> Self {
+        match language_id {
+            "typescript" | "typescriptreact" | "javascript" | "javascriptreact" | "vue" | "svelte" => Self::Js,
+            "python" => Self::Python,
+            "rust" => Self::Rust,
+            "go" => Self::Go,
+            "csharp" => Self::CSharp,
+            "php" => Self::Php,
+            "ruby" | "shellscript" | "perl" | "r" | "elixir" | "yaml" | "toml" | "dockerfile" | "makefile" => Self::Hash,
+            _ => Self::CLike,
+        }
+    }
+
+    pub(crate) fn line_comments(self) -> &'static [&'static str] {
+        match self {
+            Self::Python | Self::Hash => &["#"],
+            Self::Php => &["//", "#"],
+            _ => &["//"],
+        }
+    }
+
+    pub(crate) fn has_block_comments(self) -> bool {
+        !matches!(self, Self::Python | Self::Hash)
+    }
+
+    /// `'` starts a char literal rather than a string
+    fn has_char_literals(self) -> bool {
+        matches!(self, Self::Rust | Self::Go | Self::CSharp | Self::CLike)
+    }
+
+    /// Plain quoted strings end at the line break
+    fn single_line_strings(self) -> bool {
+        matches!(self, Self::Js | Self::Python | Self::Go | Self::CSharp | Self::CLike)
+    }
+}
+
+pub(crate) struct Literal {
+    content_start: usize,
+    content_end: usize,
+    pub(crate) end: usize,
+    is_template: bool,
+}
+
+pub(crate) enum Scan {
+    String(Literal),
+    /// Not a string literal; resume scanning here
+    Skip(usize),
+}
+
+pub(crate) fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// Scan a literal starting at `start`, which may be a quote or a string prefix
+pub(crate) fn scan_literal(bytes: &[u8], start: usize, flavor: LexFlavor) -> Scan {
+    let b = bytes[start];
+    if is_ident_byte(b) {
+        let word_end = start + bytes[start..].iter().take_while(|b| is_ident_byte(**b)).count();
+        if start > 0 && is_ident_byte(bytes[start - 1]) {
+            return Scan::Skip(word_end);
+        }
+        let prefix = &bytes[start..word_end];
+        return match bytes.get(word_end) {
+            Some(b'"' | b'\'') if is_string_prefix(prefix, flavor) => {
+                let raw = prefix.iter().any(|b| matches!(b, b'r' | b'R')) && flavor != LexFlavor::Python;
+                let template = prefix.iter().any(|b| matches!(b, b'f' | b'F'));
+                scan_quoted(bytes, start, word_end, flavor, raw, template)
+            }
+            // Rust raw strings with hashes, `r#"..."#`
+            Some(b'#') if flavor == LexFlavor::Rust && matches!(prefix, b"r" | b"br") => {
+                let hashes = bytes[word_end..].iter().take_while(|b| **b == b'#').count();
+                if bytes.get(word_end + hashes) != Some(&b'"') {
+                    return Scan::Skip(word_end);
+                }
+                let content_start = word_end + hashes + 1;
+                let close = [&b"\""[..], &bytes[word_end..word_end + hashes]].concat();
+                match memchr::memmem::find(&bytes[content_start..], &close) {
+                    Some(offset) => Scan::String(Literal {
+                        content_start,
+                        content_end: content_start + offset,
+                        end: content_start + offset + close.len(),
+                        is_template: false,
+                    }),
+                    None => Scan::Skip(bytes.len()),
+                }
+            }
+            _ => Scan::Skip(word_end),
+        };
+    }
+
+    match b {
+        b'"' => scan_quoted(bytes, start, start, flavor, false, false),
+        b'\'' if flavor.has_char_literals() => Scan::Skip(skip_char_literal(bytes, start, flavor)),
+        b'\'' => scan_quoted(bytes, start, start, flavor, false, false),
+        b'`' if flavor == LexFlavor::Js => scan_template(bytes, start),
+        b'`' if flavor == LexFlavor::Go => match memchr::memchr(b'`', &bytes[start + 1..]) {
+            Some(offset) => Scan::String(Literal {
+                content_start: start + 1,
+                content_end: start + 1 + offset,
+                end: start + offset + 2,
+                is_template: false,
+            }),
+            None => Scan::Skip(bytes.len()),
+        },
+        b'@' | b'$' if flavor == LexFlavor::CSharp => {
+            let prefix_len = bytes[start..].iter().take_while(|b| matches!(b, b'@' | b'$')).count();
+            let prefix = &bytes[start..start + prefix_len];
+            if prefix_len > 2 || bytes.get(start + prefix_len) != Some(&b'"') {
+                return Scan::Skip(start + 1);
+            }
+            scan_quoted(bytes, start, start + prefix_len, flavor, prefix.contains(&b'@'), prefix.contains(&b'$'))
+        }
+        _ => Scan::Skip(start + 1),
+    }
+}
+
+fn is_string_prefix(prefix: &[u8], flavor: LexFlavor) -> bool {
+    let lower = prefix.to_ascii_lowercase();
+    match flavor {
+        LexFlavor::Python => matches!(lower.as_slice(), b"r" | b"b" | b"u" | b"f" | b"rb" | b"br" | b"fr" | b"rf"),
+        LexFlavor::Rust => matches!(prefix, b"b" | b"r" | b"br" | b"c" | b"cr"),
+        LexFlavor::CLike => matches!(prefix, b"L" | b"u" | b"U" | b"u8"),
+        _ => false,
+    }
+}
+
+/// A quoted string whose opening quote is at `quote`; `start` is where its prefix begins.
+/// Raw strings (Rust `r"..."`, C# `@"..."`) have no backslash escapes.
+fn scan_quoted(bytes: &[u8], start: usize, quote: usize, flavor: LexFlavor, raw: bool, template: bool) -> Scan {
+    let q = bytes[quote];
+    let triple = flavor == LexFlavor::Python && bytes[quote..].starts_with(&[q, q, q]);
+    let delimiter = if triple { 3 } else { 1 };
+    let content_start = quote + delimiter;
+    let verbatim = flavor == LexFlavor::CSharp && raw;
+
+    let mut i = content_start;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'\\' && !raw {
+            i += 2;
+            continue;
+        }
+        if b == b'\n' && !triple && !verbatim && flavor.single_line_strings() {
+            // Unterminated; pick up again after the stray quote
+            return Scan::Skip(quote + 1);
+        }
+        if b == q {
+            if verbatim && bytes.get(i + 1) == Some(&q) {
+                i += 2;
+                continue;
+            }
+            if !triple || bytes[i..].starts_with(&[q, q, q]) {
+                return Scan::String(Literal {
+                    content_start,
+                    content_end: i,
+                    end: i + delimiter,
+                    is_template: template,
+                });
+            }
+        }
+        i += 1;
+    }
+    Scan::Skip(bytes.len().max(start + 1))
+}
+
+/// A JavaScript template literal, skipping over `${...}` substitutions
+fn scan_template(bytes: &[u8], start: usize) -> Scan {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                return Scan::String(Literal {
+                    content_start: start + 1,
+                    content_end: i,
+                    end: i + 1,
+                    is_template: true,
+                })
+            }
+            b'$' if bytes.get(i + 1) == Some(&b'{') => {
+                let mut depth = 0;
+                i += 1;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'{' => depth += 1,
+                        b'}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        b'"' | b'\'' | b'`' => {
+                            if let Scan::String(nested) = scan_literal(bytes, i, LexFlavor::Js) {
+                                i = nested.end;
+                                continue;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    Scan::Skip(bytes.len())
+}
+
+/// End of a char literal, or just past the quote for a Rust lifetime or label
+fn skip_char_literal(bytes: &[u8], start: usize, flavor: LexFlavor) -> usize {
+    let rest = &bytes[start + 1..];
+    if flavor == LexFlavor::Rust {
+        let char_len = match rest.first() {
+            Some(b'\\') => rest.iter().skip(1).position(|b| *b == b'\'').map(|p| p + 1),
+            Some(&b) => Some(match b {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                _ => 4,
+            }),
+            None => None,
+        };
+        return match char_len {
+            Some(len) if rest.get(len) == Some(&b'\'') => start + len + 2,
+            _ => start + 1,
+        };
+    }
+
+    let mut i = 0;
+    while i < rest.len() && rest[i] != b'\n' {
+        match rest[i] {
+            b'\\' => i += 2,
+            b'\'' => return start + i + 2,
+            _ => i += 1,
+        }
+    }
+    start + 1
 }
 
 /// Estimate token count for LLM context
 /// 
-/// Fast approximation: ~4 characters per token
+/// Fast approximation: ~4 characters per token. Use `count_tokens` when the
+/// count has to match what the model will see.
 #[napi]
 pub fn estimate_tokens(text: String) -> u32 {
     (text.len() / 4) as u32
 }
+
+/// Count BPE tokens exactly as the model's tokenizer would
+///
+/// `encoding` is an encoding name ('cl100k_base' | 'o200k_base' | 'p50k_base' |
+/// 'p50k_edit' | 'r50k_base') or a model name such as `gpt-4o` or
+/// `gpt-3.5-turbo`. Defaults to cl100k_base.
+#[napi]
+pub fn count_tokens(text: String, encoding: Option<String>) -> Result<u32> {
+    Ok(bpe(encoding.as_deref())?.encode_ordinary(&text).len() as u32)
+}
+
+/// Encode text into BPE token ids
+///
+/// Special tokens such as `<|endoftext|>` are encoded as plain text.
+#[napi]
+pub fn encode(text: String, encoding: Option<String>) -> Result<Vec<u32>> {
+    Ok(bpe(encoding.as_deref())?.encode_ordinary(&text))
+}
+
+/// Decode BPE token ids back into text
+#[napi]
+pub fn decode(tokens: Vec<u32>, encoding: Option<String>) -> Result<String> {
+    bpe(encoding.as_deref())?
+        .decode(tokens)
+        .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+static BPE_ENCODINGS: [OnceLock<CoreBPE>; 5] = [
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+];
+
+/// Resolve an encoding or model name to a cached tokenizer
+pub(crate) fn bpe(encoding: Option<&str>) -> Result<&'static CoreBPE> {
+    let name = encoding.unwrap_or("cl100k_base");
+    let tokenizer = match name {
+        "cl100k_base" => Tokenizer::Cl100kBase,
+        "o200k_base" => Tokenizer::O200kBase,
+        "p50k_base" => Tokenizer::P50kBase,
+        "p50k_edit" => Tokenizer::P50kEdit,
+        "r50k_base" | "gpt2" => Tokenizer::R50kBase,
+        model => get_tokenizer(model)
+            .ok_or_else(|| Error::from_reason(format!("Unknown encoding or model: {}", model)))?,
+    };
+
+    let slot = match tokenizer {
+        Tokenizer::Cl100kBase => 0,
+        Tokenizer::O200kBase => 1,
+        Tokenizer::P50kBase => 2,
+        Tokenizer::P50kEdit => 3,
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => 4,
+    };
+    if let Some(bpe) = BPE_ENCODINGS[slot].get() {
+        return Ok(bpe);
+    }
+    let loaded = match tokenizer {
+        Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base(),
+        Tokenizer::O200kBase => tiktoken_rs::o200k_base(),
+        Tokenizer::P50kBase => tiktoken_rs::p50k_base(),
+        Tokenizer::P50kEdit => tiktoken_rs::p50k_edit(),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => tiktoken_rs::r50k_base(),
+    }
+    .map_err(|e| Error::from_reason(e.to_string()))?;
+    Ok(BPE_ENCODINGS[slot].get_or_init(|| loaded))
+}
+
+/// Truncate text to fit a token budget
+///
+/// Strategy: 'head' keeps the start (default), 'tail' keeps the end and
+/// 'head_tail' keeps both ends joined by an ellipsis line. Cuts fall on token
+/// boundaries, moved back to the nearest character boundary where a token
+/// splits a multi-byte character.
+#[napi]
+pub fn truncate_to_tokens(
+    text: String,
+    max_tokens: u32,
+    strategy: Option<String>,
+    encoding: Option<String>,
+) -> Result<String> {
+    let bpe = bpe(encoding.as_deref())?;
+    let tokens = bpe.encode_ordinary(&text);
+    let max_tokens = max_tokens as usize;
+    if tokens.len() <= max_tokens {
+        return Ok(text);
+    }
+
+    let offsets = token_offsets(bpe, tokens);
+    let head = |count: usize| &text[..floor_char_boundary(&text, offsets[count])];
+    let tail = |count: usize| &text[ceil_char_boundary(&text, offsets[offsets.len() - 1 - count])..];
+
+    let truncated = match strategy.as_deref().unwrap_or("head") {
+        "head" => head(max_tokens).to_string(),
+        "tail" => tail(max_tokens).to_string(),
+        "head_tail" => {
+            const ELLIPSIS: &str = "\n...\n";
+            let budget = max_tokens.saturating_sub(bpe.encode_ordinary(ELLIPSIS).len());
+            if budget == 0 {
+                head(max_tokens).to_string()
+            } else {
+                format!("{}{}{}", head(budget - budget / 2), ELLIPSIS, tail(budget / 2))
+            }
+        }
+        other => return Err(Error::from_reason(format!("Unknown truncation strategy: {}", other))),
+    };
+    Ok(truncated)
+}
+
+/// Byte offset where each token starts, plus the end of the text
+pub(crate) fn token_offsets(bpe: &CoreBPE, tokens: Vec<u32>) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(tokens.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+    for bytes in bpe._decode_native_and_split(tokens) {
+        offset += bytes.len();
+        offsets.push(offset);
+    }
+    offsets
+}
+
+pub(crate) fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+pub(crate) fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYTHON: &str = "def f(a):\n    a += 1\n    return a\nclass C:\n    x = 1\n";
+
+    #[test]
+    fn removing_docstrings_keeps_leading_statements() {
+        let code = format!("\"\"\"Module.\"\"\"\n{}", PYTHON.replace("class C:\n", "class C:\n    \"\"\"Doc.\"\"\"\n"));
+        assert_eq!(remove_comments(code, "python".to_string(), Some(true)), PYTHON);
+        assert_eq!(remove_comments(PYTHON.to_string(), "python".to_string(), Some(true)), PYTHON);
+    }
+
+    #[test]
+    fn leading_python_statements_count_as_code() {
+        // Every line is code, as counting non-blank lines used to give
+        assert_eq!(count_loc(PYTHON.to_string(), "python".to_string()), 5);
+
+        let stats = count_loc_detailed("x = 1 # c\n".to_string(), "python".to_string());
+        assert_eq!((stats.code_lines, stats.comment_lines, stats.mixed_lines), (0, 0, 1));
+    }
+}
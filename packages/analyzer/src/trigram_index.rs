@@ -0,0 +1,251 @@
+use napi::bindgen_prelude::*;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use regex_syntax::ParserBuilder;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Most strings a regex fragment may expand to before only its trigrams
+/// are kept
+const MAX_EXACT: usize = 64;
+
+/// Most characters of a class expanded into exact strings
+const MAX_CLASS_CHARS: usize = 8;
+
+/// Trigrams a file must contain for a regex to possibly match it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TrigramQuery {
+    /// Any file may match
+    All,
+    Trigram(u32),
+    And(Vec<TrigramQuery>),
+    Or(Vec<TrigramQuery>),
+}
+
+/// Files by the byte trigrams they contain
+#[derive(Default)]
+pub(crate) struct TrigramIndex {
+    postings: HashMap<u32, HashSet<Arc<str>>>,
+    paths: HashSet<Arc<str>>,
+}
+
+impl TrigramIndex {
+    pub fn add(&mut self, path: &str, trigrams: &[u32]) {
+        let path: Arc<str> = Arc::from(path);
+        for trigram in trigrams {
+            self.postings.entry(*trigram).or_default().insert(path.clone());
+        }
+        self.paths.insert(path);
+    }
+
+    pub fn remove(&mut self, path: &str, trigrams: &[u32]) {
+        for trigram in trigrams {
+            if let Some(paths) = self.postings.get_mut(trigram) {
+                paths.remove(path);
+                if paths.is_empty() {
+                    self.postings.remove(trigram);
+                }
+            }
+        }
+        self.paths.remove(path);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Files that may match the query
+    pub fn candidates(&self, query: &TrigramQuery) -> HashSet<Arc<str>> {
+        self.evaluate(query).unwrap_or_else(|| self.paths.clone())
+    }
+
+    /// `None` for every file
+    fn evaluate(&self, query: &TrigramQuery) -> Option<HashSet<Arc<str>>> {
+        match query {
+            TrigramQuery::All => None,
+            TrigramQuery::Trigram(trigram) => Some(self.postings.get(trigram).cloned().unwrap_or_default()),
+            TrigramQuery::And(parts) => {
+                let mut result: Option<HashSet<Arc<str>>> = None;
+                for part in parts {
+                    let Some(paths) = self.evaluate(part) else { continue };
+                    result = Some(match result {
+                        Some(result) => result.intersection(&paths).cloned().collect(),
+                        None => paths,
+                    });
+                    if result.as_ref().is_some_and(|result| result.is_empty()) {
+                        break;
+                    }
+                }
+                result
+            }
+            TrigramQuery::Or(parts) => {
+                let mut result = HashSet::new();
+                for part in parts {
+                    result.extend(self.evaluate(part)?);
+                }
+                Some(result)
+            }
+        }
+    }
+}
+
+/// Distinct byte trigrams of a text, sorted
+pub(crate) fn trigrams_of(bytes: &[u8]) -> Vec<u32> {
+    let mut trigrams: Vec<u32> = bytes.windows(3).map(trigram).collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+fn trigram(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32
+}
+
+/// The trigram query a regex implies, as in Google Code Search
+pub(crate) fn regex_query(pattern: &str, case_insensitive: bool) -> Result<TrigramQuery> {
+    let hir = ParserBuilder::new()
+        .case_insensitive(case_insensitive)
+        .build()
+        .parse(pattern)
+        .map_err(|e| Error::from_reason(format!("Invalid pattern: {}", e)))?;
+    Ok(analyze(&hir).into_query())
+}
+
+/// What is known about the strings a regex fragment matches
+struct Info {
+    /// Every string it can match, when there are few
+    exact: Option<HashSet<Vec<u8>>>,
+    /// Trigrams a match must contain otherwise
+    query: TrigramQuery,
+}
+
+impl Info {
+    fn exact(strings: HashSet<Vec<u8>>) -> Self {
+        Self { exact: Some(strings), query: TrigramQuery::All }
+    }
+
+    fn any() -> Self {
+        Self { exact: None, query: TrigramQuery::All }
+    }
+
+    fn into_query(self) -> TrigramQuery {
+        match self.exact {
+            Some(strings) => exact_query(&strings),
+            None => self.query,
+        }
+    }
+}
+
+fn analyze(hir: &Hir) -> Info {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Info::exact(HashSet::from([Vec::new()])),
+        HirKind::Literal(literal) => Info::exact(HashSet::from([literal.0.to_vec()])),
+        HirKind::Class(class) => match class_strings(class) {
+            Some(strings) => Info::exact(strings),
+            None => Info::any(),
+        },
+        HirKind::Capture(capture) => analyze(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min == 0 => Info::any(),
+        HirKind::Repetition(repetition) => {
+            let sub = analyze(&repetition.sub);
+            if repetition.max == Some(1) {
+                return sub;
+            }
+            Info { exact: None, query: sub.into_query() }
+        }
+        HirKind::Concat(parts) => {
+            let mut exact: Option<HashSet<Vec<u8>>> = Some(HashSet::from([Vec::new()]));
+            let mut queries = Vec::new();
+            for part in parts.iter().map(analyze) {
+                exact = match (exact, part.exact) {
+                    (Some(left), Some(right)) if left.len() * right.len() <= MAX_EXACT => Some(
+                        left.iter()
+                            .flat_map(|a| right.iter().map(move |b| [a.as_slice(), b.as_slice()].concat()))
+                            .collect(),
+                    ),
+                    (left, right) => {
+                        // Trigrams spanning the join are lost here
+                        queries.extend(left.map(|strings| exact_query(&strings)));
+                        queries.push(match right {
+                            Some(strings) => exact_query(&strings),
+                            None => part.query,
+                        });
+                        None
+                    }
+                };
+            }
+            match exact {
+                Some(strings) if queries.is_empty() => Info::exact(strings),
+                exact => {
+                    queries.extend(exact.map(|strings| exact_query(&strings)));
+                    Info { exact: None, query: and(queries) }
+                }
+            }
+        }
+        HirKind::Alternation(branches) => {
+            let infos: Vec<Info> = branches.iter().map(analyze).collect();
+            let total: Option<usize> = infos.iter().map(|info| info.exact.as_ref().map(HashSet::len)).sum();
+            if total.is_some_and(|total| total <= MAX_EXACT) {
+                return Info::exact(infos.into_iter().flat_map(|info| info.exact.unwrap()).collect());
+            }
+            Info { exact: None, query: or(infos.into_iter().map(Info::into_query).collect()) }
+        }
+    }
+}
+
+/// The characters of a small class, UTF-8 encoded
+fn class_strings(class: &Class) -> Option<HashSet<Vec<u8>>> {
+    let mut strings = HashSet::new();
+    match class {
+        Class::Unicode(class) => {
+            for range in class.iter() {
+                for c in range.start()..=range.end() {
+                    strings.insert(c.to_string().into_bytes());
+                    if strings.len() > MAX_CLASS_CHARS {
+                        return None;
+                    }
+                }
+            }
+        }
+        Class::Bytes(class) => {
+            for range in class.iter() {
+                for byte in range.start()..=range.end() {
+                    strings.insert(vec![byte]);
+                    if strings.len() > MAX_CLASS_CHARS {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+    Some(strings)
+}
+
+/// Any of the strings: each needs all of its trigrams
+fn exact_query(strings: &HashSet<Vec<u8>>) -> TrigramQuery {
+    if strings.iter().any(|string| string.len() < 3) {
+        return TrigramQuery::All;
+    }
+    or(strings
+        .iter()
+        .map(|string| and(trigrams_of(string).into_iter().map(TrigramQuery::Trigram).collect()))
+        .collect())
+}
+
+fn and(parts: Vec<TrigramQuery>) -> TrigramQuery {
+    let mut parts: Vec<TrigramQuery> = parts.into_iter().filter(|part| *part != TrigramQuery::All).collect();
+    match parts.len() {
+        0 => TrigramQuery::All,
+        1 => parts.pop().unwrap(),
+        _ => TrigramQuery::And(parts),
+    }
+}
+
+fn or(mut parts: Vec<TrigramQuery>) -> TrigramQuery {
+    if parts.is_empty() || parts.contains(&TrigramQuery::All) {
+        return TrigramQuery::All;
+    }
+    if parts.len() == 1 {
+        return parts.pop().unwrap();
+    }
+    TrigramQuery::Or(parts)
+}
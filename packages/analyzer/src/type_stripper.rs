@@ -0,0 +1,124 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use tree_sitter::Node;
+
+use crate::ast_parser::parse_tree;
+
+/// Strip TypeScript type syntax, leaving runnable JavaScript
+///
+/// Type annotations, generics, interfaces, type aliases, `declare`d and
+/// overload-only declarations, `as`/`satisfies`, non-null assertions, type-only
+/// imports and modifiers such as `private` or `readonly` are replaced with
+/// spaces, so every remaining token keeps its line, column and byte offset.
+/// Constructs with runtime meaning (`enum`, `namespace`, constructor parameter
+/// properties) are not translated: the first two are kept as written and
+/// parameter properties lose their modifiers.
+///
+/// `languageId` is 'typescript' (default) or 'typescriptreact'.
+#[napi]
+pub fn strip_types(code: String, language_id: Option<String>) -> Result<String> {
+    let language_id = language_id.unwrap_or_else(|| "typescript".to_string());
+    if !matches!(language_id.as_str(), "typescript" | "typescriptreact") {
+        return Err(Error::from_reason(format!("strip_types expects TypeScript, got {}", language_id)));
+    }
+    let tree = parse_tree(&code, &language_id)?;
+
+    let mut ranges = Vec::new();
+    collect_type_ranges(tree.root_node(), &mut ranges);
+
+    let mut bytes = code.into_bytes();
+    for (start, end) in ranges {
+        for b in &mut bytes[start..end] {
+            if *b != b'\n' && *b != b'\r' {
+                *b = b' ';
+            }
+        }
+    }
+    // Whole characters are blanked byte by byte, so the result is still UTF-8
+    String::from_utf8(bytes).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Byte ranges of type-only syntax; a blanked node is not descended into
+fn collect_type_ranges(node: Node, ranges: &mut Vec<(usize, usize)>) {
+    let whole = (node.start_byte(), node.end_byte());
+    match node.kind() {
+        "interface_declaration" | "type_alias_declaration" | "ambient_declaration" | "abstract_method_signature"
+        | "function_signature" | "method_signature" | "index_signature" | "type_annotation"
+        | "type_predicate_annotation" | "asserts_annotation" | "omitting_type_annotation"
+        | "opting_type_annotation" | "type_parameters" | "type_arguments" | "implements_clause"
+        | "accessibility_modifier" => {
+            ranges.push(whole);
+            return;
+        }
+        "export_statement" | "import_statement" if is_type_only_statement(node) => {
+            ranges.push(whole);
+            return;
+        }
+        "public_field_definition" if has_keyword(node, "declare") => {
+            ranges.push(whole);
+            return;
+        }
+        "import_specifier" | "export_specifier" if has_keyword(node, "type") => {
+            // Take a neighbouring comma along so the list stays valid
+            let end = match node.next_sibling() {
+                Some(comma) if comma.kind() == "," => comma.end_byte(),
+                _ => node.end_byte(),
+            };
+            let start = match node.prev_sibling() {
+                Some(comma) if comma.kind() == "," && end == node.end_byte() => comma.start_byte(),
+                _ => node.start_byte(),
+            };
+            ranges.push((start, end));
+            return;
+        }
+        "as_expression" | "satisfies_expression" => {
+            if let Some(value) = node.named_child(0) {
+                collect_type_ranges(value, ranges);
+                ranges.push((value.end_byte(), node.end_byte()));
+            }
+            return;
+        }
+        "non_null_expression" => {
+            ranges.push((node.end_byte() - 1, node.end_byte()));
+        }
+        _ => {}
+    }
+
+    let parent_kind = node.kind();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let is_modifier = matches!(child.kind(), "readonly" | "declare" | "abstract" | "override");
+        let is_optional_mark = child.kind() == "?"
+            && matches!(parent_kind, "optional_parameter" | "public_field_definition" | "method_definition");
+        let is_definite_mark =
+            child.kind() == "!" && matches!(parent_kind, "public_field_definition" | "variable_declarator");
+        if !child.is_named() && (is_modifier || is_optional_mark || is_definite_mark) {
+            ranges.push((child.start_byte(), child.end_byte()));
+        } else {
+            collect_type_ranges(child, ranges);
+        }
+    }
+}
+
+/// `import type ...`, `export type { ... }`, `export interface ...` and other
+/// statements that only move types around
+fn is_type_only_statement(node: Node) -> bool {
+    if node.kind() == "import_statement" {
+        return node.child(1).is_some_and(|c| c.kind() == "type");
+    }
+    if node.child(1).is_some_and(|c| c.kind() == "type") {
+        return true;
+    }
+    node.child_by_field_name("declaration").is_some_and(|declaration| {
+        matches!(
+            declaration.kind(),
+            "interface_declaration" | "type_alias_declaration" | "ambient_declaration" | "function_signature"
+        )
+    })
+}
+
+fn has_keyword(node: Node, keyword: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|c| !c.is_named() && c.kind() == keyword);
+    found
+}
@@ -0,0 +1,861 @@
+use memmap2::Mmap;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::file_io::{atomic_write, map_file};
+use crate::quantization::{dot, int8_decode, int8_encode, lanes, squared_distance, ProductQuantizer};
+
+/// Bumped whenever the stored layout changes
+const FORMAT_VERSION: u32 = 2;
+
+/// Vectors stored before product quantization codebooks are trained, and
+/// most vectors they are trained on
+const PQ_TRAINING_VECTORS: usize = 4096;
+
+/// Options for `VectorStore`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorStoreOptions {
+    /// 'cosine' | 'dot' | 'euclidean' (default: 'cosine')
+    pub metric: Option<String>,
+    /// Links per node and layer; layer 0 gets twice as many (default: 16)
+    pub m: Option<u32>,
+    /// Candidates considered when linking a new vector (default: 200)
+    #[napi(js_name = "efConstruction")]
+    pub ef_construction: Option<u32>,
+    /// Candidates considered per search, at least `k` (default: 64)
+    #[napi(js_name = "efSearch")]
+    pub ef_search: Option<u32>,
+    /// File the store is loaded from on construction and written by `save`
+    #[napi(js_name = "storagePath")]
+    pub storage_path: Option<String>,
+    /// 'none' | 'int8' | 'pq' (default: 'none'). Quantized stores need a
+    /// storage path and keep full-precision copies in a `.vectors` file
+    /// beside it
+    pub quantization: Option<String>,
+    /// Bytes per vector with 'pq' quantization (default: dimensions / 8)
+    #[napi(js_name = "pqSubvectors")]
+    pub pq_subvectors: Option<u32>,
+    /// Candidates re-ranked at full precision per search of a quantized
+    /// store, at least `k` (default: 4 * k)
+    pub rerank: Option<u32>,
+}
+
+/// A vector to add with `VectorStore.addBatch`
+#[napi(object)]
+pub struct VectorInput {
+    pub id: String,
+    pub vector: Float32Array,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// A stored vector near a query
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorMatch {
+    pub id: String,
+    /// 1 - cosine similarity, the negated dot product, or the Euclidean
+    /// distance, depending on the metric
+    pub distance: f64,
+    pub metadata: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Metric {
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "cosine" => Ok(Self::Cosine),
+            "dot" => Ok(Self::Dot),
+            "euclidean" => Ok(Self::Euclidean),
+            _ => Err(Error::from_reason(format!("Unknown metric: {}", name))),
+        }
+    }
+
+    /// Lower is closer; cosine vectors are normalized when stored, and
+    /// Euclidean distances are left squared until reported
+    fn distance(self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::Euclidean => squared_distance(a, b),
+            _ => self.distance_from_sum(dot(a, b)),
+        }
+    }
+
+    /// The distance to an int8-coded vector, without decoding it
+    fn distance_int8(self, a: &[f32], codes: &[i8], scale: f32) -> f32 {
+        match self {
+            Self::Euclidean => lanes(a, codes, |x, code| (x - code as f32 * scale) * (x - code as f32 * scale)),
+            _ => self.distance_from_sum(scale * lanes(a, codes, |x, code| x * code as f32)),
+        }
+    }
+
+    /// The distance from a dot product, or from a squared Euclidean distance
+    fn distance_from_sum(self, sum: f32) -> f32 {
+        match self {
+            Self::Cosine => 1.0 - sum,
+            Self::Dot => -sum,
+            Self::Euclidean => sum,
+        }
+    }
+
+    fn reported(self, distance: f32) -> f64 {
+        match self {
+            Self::Euclidean => (distance as f64).sqrt(),
+            _ => distance as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Quantization {
+    None,
+    Int8,
+    Product { subvectors: usize },
+}
+
+impl Quantization {
+    fn parse(name: &str, subvectors: Option<u32>, dimensions: usize) -> Result<Self> {
+        match name {
+            "none" => Ok(Self::None),
+            "int8" => Ok(Self::Int8),
+            "pq" => {
+                let subvectors = subvectors.map_or((dimensions / 8).max(1), |count| count as usize);
+                if subvectors == 0 || subvectors > dimensions {
+                    return Err(Error::from_reason(format!(
+                        "Subvectors must be between 1 and {}",
+                        dimensions
+                    )));
+                }
+                Ok(Self::Product { subvectors })
+            }
+            _ => Err(Error::from_reason(format!("Unknown quantization: {}", name))),
+        }
+    }
+}
+
+/// Vectors as held in memory, one after another by node
+#[derive(Serialize, Deserialize)]
+enum Vectors {
+    /// Also used by 'pq' stores until they have enough vectors to train on
+    Full(Vec<f32>),
+    Int8 {
+        codes: Vec<i8>,
+        scales: Vec<f32>,
+    },
+    Product {
+        quantizer: ProductQuantizer,
+        codes: Vec<u8>,
+    },
+}
+
+/// Full-precision copies of quantized vectors, in a file of native floats
+struct FullVectors {
+    path: PathBuf,
+    file: File,
+    map: Option<Mmap>,
+}
+
+impl FullVectors {
+    /// Open the file, keeping its first `components` floats
+    fn open(path: &Path, components: usize) -> Result<Self> {
+        let failed = |e: std::io::Error| Error::from_reason(format!("Failed to open {}: {}", path.display(), e));
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(failed)?;
+        let length = (components * 4) as u64;
+        if file.metadata().map_err(failed)?.len() < length {
+            return Err(Error::from_reason(format!(
+                "Full-precision vectors {} are missing or truncated",
+                path.display()
+            )));
+        }
+        // Anything past the saved vectors was added after the last save
+        file.set_len(length).map_err(failed)?;
+        file.seek(SeekFrom::End(0)).map_err(failed)?;
+        let mut vectors = Self { path: path.to_path_buf(), file, map: None };
+        vectors.remap()?;
+        Ok(vectors)
+    }
+
+    fn append(&mut self, vector: &[f32]) -> Result<()> {
+        let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_ne_bytes()).collect();
+        self.file
+            .write_all(&bytes)
+            .map_err(|e| Error::from_reason(format!("Failed to write {}: {}", self.path.display(), e)))
+    }
+
+    /// Map the file again to take in appended vectors
+    fn remap(&mut self) -> Result<()> {
+        let failed = |e: std::io::Error| Error::from_reason(format!("Failed to map {}: {}", self.path.display(), e));
+        if self.file.metadata().map_err(failed)?.len() == 0 {
+            self.map = None;
+            return Ok(());
+        }
+        // The file is only written through this handle, by appending while no
+        // search runs
+        self.map = Some(map_file(&self.file).map_err(failed)?);
+        Ok(())
+    }
+
+    fn get(&self, node: u32, dimensions: usize) -> Vec<f32> {
+        let start = node as usize * dimensions * 4;
+        let bytes = self.map.as_ref().map_or(&[][..], |map| &map[start..start + dimensions * 4]);
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_ne_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+}
+
+/// A vector being searched for, with what speeds up its distances
+struct Query<'a> {
+    vector: Cow<'a, [f32]>,
+    /// For product-coded vectors, the sums per subvector and centroid
+    table: Option<Vec<f32>>,
+}
+
+/// A graph node with its distance from the vector being searched for
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then(self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct Node {
+    id: String,
+    metadata: HashMap<String, String>,
+    /// Neighbors on each layer from 0 up to the node's level
+    links: Vec<RwLock<Vec<u32>>>,
+    /// Removed or replaced; still used as a stepping stone when searching
+    deleted: bool,
+}
+
+/// The top-level node searches start from, and its level
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Entry {
+    node: u32,
+    level: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredNode {
+    id: String,
+    metadata: HashMap<String, String>,
+    links: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredStore<V> {
+    dimensions: u32,
+    metric: Metric,
+    quantization: Quantization,
+    m: usize,
+    ef_construction: usize,
+    entry: Option<Entry>,
+    nodes: Vec<StoredNode>,
+    vectors: V,
+}
+
+/// Approximate nearest-neighbor search over embeddings
+///
+/// Vectors are linked into a hierarchical navigable small world graph
+/// (HNSW): sparse upper layers route a search towards the query and the
+/// dense bottom layer refines it, so a search visits a few thousand vectors
+/// rather than all of them. Removed vectors are only marked as such and
+/// still take up memory and disk space.
+///
+/// With quantization the graph is built and searched over compact codes,
+/// a byte per component with 'int8' or per subvector with 'pq', and the
+/// best candidates are re-ranked against full-precision vectors read from
+/// disk
+#[napi]
+pub struct VectorStore {
+    storage_path: Option<PathBuf>,
+    dimensions: usize,
+    metric: Metric,
+    quantization: Quantization,
+    m: usize,
+    ef_construction: usize,
+    ef_search: usize,
+    rerank: Option<usize>,
+    nodes: Vec<Node>,
+    vectors: Vectors,
+    full: Option<FullVectors>,
+    /// Live node by id
+    ids: HashMap<String, u32>,
+    entry: Mutex<Option<Entry>>,
+}
+
+#[napi]
+impl VectorStore {
+    /// Create a store for vectors of `dimensions` components, loading
+    /// `storagePath` when it exists
+    #[napi(constructor)]
+    pub fn new(dimensions: u32, options: Option<VectorStoreOptions>) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        if dimensions == 0 {
+            return Err(Error::from_reason("Dimensions must be at least 1"));
+        }
+        let quantization = Quantization::parse(
+            options.quantization.as_deref().unwrap_or("none"),
+            options.pq_subvectors,
+            dimensions as usize,
+        )?;
+        if quantization != Quantization::None && options.storage_path.is_none() {
+            return Err(Error::from_reason("A quantized store needs a storage path"));
+        }
+        let mut store = Self {
+            storage_path: options.storage_path.map(PathBuf::from),
+            dimensions: dimensions as usize,
+            metric: Metric::parse(options.metric.as_deref().unwrap_or("cosine"))?,
+            quantization,
+            m: options.m.unwrap_or(16).max(2) as usize,
+            ef_construction: options.ef_construction.unwrap_or(200).max(1) as usize,
+            ef_search: options.ef_search.unwrap_or(64).max(1) as usize,
+            rerank: options.rerank.map(|rerank| rerank as usize),
+            nodes: Vec::new(),
+            vectors: match quantization {
+                Quantization::Int8 => Vectors::Int8 { codes: Vec::new(), scales: Vec::new() },
+                _ => Vectors::Full(Vec::new()),
+            },
+            full: None,
+            ids: HashMap::new(),
+            entry: Mutex::new(None),
+        };
+        let loaded = match store.storage_path.clone().filter(|path| path.exists()) {
+            Some(path) => store.load(&path)?,
+            None => false,
+        };
+        if let Some(path) = store.storage_path.as_ref().filter(|_| quantization != Quantization::None) {
+            let components = if loaded { store.nodes.len() * store.dimensions } else { 0 };
+            store.full = Some(FullVectors::open(&path.with_extension("vectors"), components)?);
+        }
+        Ok(store)
+    }
+
+    /// Add a vector, replacing any stored under `id`
+    #[napi]
+    pub fn add(&mut self, id: String, vector: Float32Array, metadata: Option<HashMap<String, String>>) -> Result<()> {
+        let node = self.push(id, &vector, metadata.unwrap_or_default())?;
+        self.prepare_links()?;
+        self.link(node);
+        Ok(())
+    }
+
+    /// Add many vectors, linking them into the graph on all cores
+    ///
+    /// Nothing is added when any vector has the wrong dimensions
+    #[napi]
+    pub fn add_batch(&mut self, items: Vec<VectorInput>) -> Result<()> {
+        let items = items
+            .into_iter()
+            .map(|item| (item.id, item.vector, item.metadata.unwrap_or_default()))
+            .collect();
+        self.insert_batch(items)
+    }
+
+    /// The `k` stored vectors nearest `query`, nearest first
+    ///
+    /// With a filter only vectors whose metadata has every key of the
+    /// filter with the same value are returned
+    #[napi]
+    pub fn search(&self, query: Float32Array, k: u32, filter: Option<HashMap<String, String>>) -> Result<Vec<VectorMatch>> {
+        self.search_vector(&query, k, filter)
+    }
+
+    /// Remove a vector, returning whether it was stored
+    #[napi]
+    pub fn remove(&mut self, id: String) -> bool {
+        let Some(node) = self.ids.remove(&id) else {
+            return false;
+        };
+        self.nodes[node as usize].deleted = true;
+        true
+    }
+
+    #[napi]
+    pub fn has(&self, id: String) -> bool {
+        self.ids.contains_key(&id)
+    }
+
+    /// Write the store to its storage path
+    #[napi]
+    pub fn save(&self) -> Result<()> {
+        let path = self
+            .storage_path
+            .as_ref()
+            .ok_or_else(|| Error::from_reason("Store has no storage path"))?;
+        let stored = StoredStore {
+            dimensions: self.dimensions as u32,
+            metric: self.metric,
+            quantization: self.quantization,
+            m: self.m,
+            ef_construction: self.ef_construction,
+            entry: *self.entry.lock().unwrap(),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| StoredNode {
+                    id: node.id.clone(),
+                    metadata: node.metadata.clone(),
+                    links: node.links.iter().map(|links| links.read().unwrap().clone()).collect(),
+                    deleted: node.deleted,
+                })
+                .collect(),
+            vectors: &self.vectors,
+        };
+        let failed = |e: std::io::Error| Error::from_reason(format!("Failed to write store {}: {}", path.display(), e));
+        if let Some(full) = &self.full {
+            full.file.sync_data().map_err(failed)?;
+        }
+        // The same bytes as the version followed by the store
+        let data = bincode::serialize(&(FORMAT_VERSION, &stored))
+            .map_err(|e| Error::from_reason(format!("Serialization error: {}", e)))?;
+        atomic_write(path, &data).map_err(failed)
+    }
+
+    /// Number of stored vectors
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.ids.len() as u32
+    }
+
+    /// Replace the empty store with a stored one, returning false for a
+    /// store from another format version, which is rebuilt from scratch
+    fn load(&mut self, path: &Path) -> Result<bool> {
+        let invalid = |e: bincode::Error| Error::from_reason(format!("Invalid store {}: {}", path.display(), e));
+        let file = File::open(path)
+            .map_err(|e| Error::from_reason(format!("Failed to read store {}: {}", path.display(), e)))?;
+        let mut reader = BufReader::new(file);
+        let version: u32 = bincode::deserialize_from(&mut reader).map_err(invalid)?;
+        if version != FORMAT_VERSION {
+            return Ok(false);
+        }
+        let stored: StoredStore<Vectors> = bincode::deserialize_from(&mut reader).map_err(invalid)?;
+        if stored.dimensions as usize != self.dimensions
+            || stored.metric != self.metric
+            || stored.quantization != self.quantization
+        {
+            return Err(Error::from_reason(format!(
+                "Store {} holds {:?} vectors of {} dimensions with {:?} quantization",
+                path.display(),
+                stored.metric,
+                stored.dimensions,
+                stored.quantization
+            )));
+        }
+        self.m = stored.m;
+        self.ef_construction = stored.ef_construction;
+        self.vectors = stored.vectors;
+        *self.entry.get_mut().unwrap() = stored.entry;
+        for (index, node) in stored.nodes.into_iter().enumerate() {
+            if !node.deleted {
+                self.ids.insert(node.id.clone(), index as u32);
+            }
+            self.nodes.push(Node {
+                id: node.id,
+                metadata: node.metadata,
+                links: node.links.into_iter().map(RwLock::new).collect(),
+                deleted: node.deleted,
+            });
+        }
+        Ok(true)
+    }
+
+    /// Check a vector's length, normalizing it for cosine similarity
+    /// Add vectors and link them, adding nothing when any vector has the
+    /// wrong dimensions
+    fn insert_batch<V: std::ops::Deref<Target = [f32]>>(
+        &mut self,
+        items: Vec<(String, V, HashMap<String, String>)>,
+    ) -> Result<()> {
+        for (_, vector, _) in &items {
+            self.check_dimensions(vector)?;
+        }
+        let first = self.nodes.len();
+        // Vectors pushed before a failed write are still linked, so every
+        // stored vector can be found
+        let pushed = items
+            .into_iter()
+            .try_for_each(|(id, vector, metadata)| self.push(id, &vector, metadata).map(|_| ()));
+        self.prepare_links()?;
+        // Replaced within the batch: linked anyway but never returned
+        (first..self.nodes.len()).into_par_iter().for_each(|node| self.link(node as u32));
+        pushed
+    }
+
+    fn search_vector(&self, query: &[f32], k: u32, filter: Option<HashMap<String, String>>) -> Result<Vec<VectorMatch>> {
+        let query = self.query(Cow::Owned(self.prepare(query)?));
+        let Some(entry) = *self.entry.lock().unwrap() else {
+            return Ok(Vec::new());
+        };
+        let k = k as usize;
+        let quantized = !matches!(self.vectors, Vectors::Full(_));
+        let wanted = if quantized { self.rerank.unwrap_or(k * 4).max(k) } else { k };
+        let mut nearest = Candidate {
+            distance: self.distance(&query, entry.node),
+            node: entry.node,
+        };
+        for layer in (1..=entry.level).rev() {
+            nearest = self.greedy_closest(&query, nearest, layer);
+        }
+        let accept = |node: u32| {
+            let node = &self.nodes[node as usize];
+            !node.deleted
+                && filter.iter().flatten().all(|(key, value)| node.metadata.get(key) == Some(value))
+        };
+        let mut found = self.search_layer(&query, nearest, self.ef_search.max(wanted), 0, accept);
+        found.truncate(wanted);
+        if quantized {
+            for candidate in &mut found {
+                candidate.distance = self.metric.distance(&query.vector, &self.full_vector(candidate.node));
+            }
+            found.sort();
+        }
+        Ok(found
+            .into_iter()
+            .take(k)
+            .map(|candidate| {
+                let node = &self.nodes[candidate.node as usize];
+                VectorMatch {
+                    id: node.id.clone(),
+                    distance: self.metric.reported(candidate.distance),
+                    metadata: node.metadata.clone(),
+                }
+            })
+            .collect())
+    }
+
+    fn check_dimensions(&self, vector: &[f32]) -> Result<()> {
+        if vector.len() != self.dimensions {
+            return Err(Error::from_reason(format!(
+                "Expected a vector of {} dimensions, got {}",
+                self.dimensions,
+                vector.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn prepare(&self, vector: &[f32]) -> Result<Vec<f32>> {
+        self.check_dimensions(vector)?;
+        let mut vector = vector.to_vec();
+        if self.metric == Metric::Cosine {
+            let norm = dot(&vector, &vector).sqrt();
+            if norm > 0.0 {
+                vector.iter_mut().for_each(|x| *x /= norm);
+            }
+        }
+        Ok(vector)
+    }
+
+    /// Store a vector as an unlinked node, retiring any it replaces
+    fn push(&mut self, id: String, vector: &[f32], metadata: HashMap<String, String>) -> Result<u32> {
+        let vector = self.prepare(vector)?;
+        if let Some(full) = &mut self.full {
+            full.append(&vector)?;
+        }
+        match &mut self.vectors {
+            Vectors::Full(data) => data.extend_from_slice(&vector),
+            Vectors::Int8 { codes, scales } => scales.push(int8_encode(&vector, codes)),
+            Vectors::Product { quantizer, codes } => codes.extend(quantizer.encode(&vector)),
+        }
+        // Levels come from the id rather than a random generator, so the
+        // same inserts always build the same layers
+        let uniform = (xxh3_64(id.as_bytes()) >> 11) as f64 / (1u64 << 53) as f64;
+        let level = (-(1.0 - uniform).ln() / (self.m as f64).ln()) as usize;
+        let node = self.nodes.len() as u32;
+        if let Some(old) = self.ids.insert(id.clone(), node) {
+            self.nodes[old as usize].deleted = true;
+        }
+        self.nodes.push(Node {
+            id,
+            metadata,
+            links: (0..=level).map(|_| RwLock::new(Vec::new())).collect(),
+            deleted: false,
+        });
+        Ok(node)
+    }
+
+    /// Make pushed vectors readable for linking, training product
+    /// quantization codebooks once there are enough vectors to train on
+    fn prepare_links(&mut self) -> Result<()> {
+        if let Some(full) = &mut self.full {
+            full.remap()?;
+        }
+        let Quantization::Product { subvectors } = self.quantization else {
+            return Ok(());
+        };
+        let Vectors::Full(data) = &self.vectors else {
+            return Ok(());
+        };
+        if self.nodes.len() < PQ_TRAINING_VECTORS {
+            return Ok(());
+        }
+        let dimensions = self.dimensions;
+        let step = self.nodes.len() / PQ_TRAINING_VECTORS;
+        let sample: Vec<f32> = data
+            .chunks_exact(dimensions)
+            .step_by(step)
+            .take(PQ_TRAINING_VECTORS)
+            .flatten()
+            .copied()
+            .collect();
+        let quantizer = ProductQuantizer::train(&sample, dimensions, subvectors);
+        let codes = data
+            .par_chunks_exact(dimensions)
+            .flat_map_iter(|vector| quantizer.encode(vector))
+            .collect();
+        self.vectors = Vectors::Product { quantizer, codes };
+        Ok(())
+    }
+
+    /// Connect a stored node into the graph; safe to run for several nodes
+    /// at once
+    fn link(&self, node: u32) {
+        let level = self.nodes[node as usize].links.len() - 1;
+        let entry = {
+            let mut entry = self.entry.lock().unwrap();
+            match *entry {
+                Some(entry) => entry,
+                None => {
+                    *entry = Some(Entry { node, level });
+                    return;
+                }
+            }
+        };
+        let query = self.query(self.full_vector(node));
+        let mut nearest = Candidate {
+            distance: self.distance(&query, entry.node),
+            node: entry.node,
+        };
+        for layer in (level + 1..=entry.level).rev() {
+            nearest = self.greedy_closest(&query, nearest, layer);
+        }
+        for layer in (0..=level.min(entry.level)).rev() {
+            let found = self.search_layer(&query, nearest, self.ef_construction, layer, |other| other != node);
+            let max_links = self.max_links(layer);
+            let neighbors = self.select_neighbors(&found, max_links);
+            *self.nodes[node as usize].links[layer].write().unwrap() = neighbors.clone();
+            for neighbor in neighbors {
+                let mut links = self.nodes[neighbor as usize].links[layer].write().unwrap();
+                if links.contains(&node) {
+                    continue;
+                }
+                links.push(node);
+                if links.len() > max_links {
+                    let base = self.query(self.stored_vector(neighbor));
+                    let mut candidates: Vec<Candidate> = links
+                        .iter()
+                        .map(|&other| Candidate { distance: self.distance(&base, other), node: other })
+                        .collect();
+                    candidates.sort();
+                    *links = self.select_neighbors(&candidates, max_links);
+                }
+            }
+            if let Some(closest) = found.first() {
+                nearest = *closest;
+            }
+        }
+        if level > entry.level {
+            let mut entry = self.entry.lock().unwrap();
+            // Another node may have raised the top level meanwhile
+            if entry.is_none_or(|entry| level > entry.level) {
+                *entry = Some(Entry { node, level });
+            }
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.m * 2
+        } else {
+            self.m
+        }
+    }
+
+    fn query<'a>(&self, vector: Cow<'a, [f32]>) -> Query<'a> {
+        let table = match &self.vectors {
+            Vectors::Product { quantizer, .. } => Some(quantizer.table(&vector, self.metric == Metric::Euclidean)),
+            _ => None,
+        };
+        Query { vector, table }
+    }
+
+    fn distance(&self, query: &Query, node: u32) -> f32 {
+        let index = node as usize;
+        let dimensions = self.dimensions;
+        match &self.vectors {
+            Vectors::Full(data) => self.metric.distance(&query.vector, &data[index * dimensions..][..dimensions]),
+            Vectors::Int8 { codes, scales } => {
+                self.metric
+                    .distance_int8(&query.vector, &codes[index * dimensions..][..dimensions], scales[index])
+            }
+            Vectors::Product { quantizer, codes } => {
+                let codes = &codes[index * quantizer.subvectors()..][..quantizer.subvectors()];
+                match &query.table {
+                    Some(table) => self.metric.distance_from_sum(quantizer.lookup(table, codes)),
+                    None => self.metric.distance(&query.vector, &quantizer.decode(codes)),
+                }
+            }
+        }
+    }
+
+    /// A node's vector as held in memory, decoded
+    fn stored_vector(&self, node: u32) -> Cow<'_, [f32]> {
+        let index = node as usize;
+        let dimensions = self.dimensions;
+        match &self.vectors {
+            Vectors::Full(data) => Cow::Borrowed(&data[index * dimensions..][..dimensions]),
+            Vectors::Int8 { codes, scales } => {
+                Cow::Owned(int8_decode(&codes[index * dimensions..][..dimensions], scales[index]))
+            }
+            Vectors::Product { quantizer, codes } => {
+                Cow::Owned(quantizer.decode(&codes[index * quantizer.subvectors()..][..quantizer.subvectors()]))
+            }
+        }
+    }
+
+    /// A node's vector at full precision, from memory or disk
+    fn full_vector(&self, node: u32) -> Cow<'_, [f32]> {
+        match (&self.vectors, &self.full) {
+            (Vectors::Full(_), _) | (_, None) => self.stored_vector(node),
+            (_, Some(full)) => Cow::Owned(full.get(node, self.dimensions)),
+        }
+    }
+
+    fn neighbors(&self, node: u32, layer: usize) -> Vec<u32> {
+        self.nodes[node as usize].links.get(layer).map_or_else(Vec::new, |links| links.read().unwrap().clone())
+    }
+
+    /// Walk towards the query on one layer until no neighbor is closer
+    fn greedy_closest(&self, query: &Query, mut nearest: Candidate, layer: usize) -> Candidate {
+        loop {
+            let mut improved = false;
+            for neighbor in self.neighbors(nearest.node, layer) {
+                let distance = self.distance(query, neighbor);
+                if distance < nearest.distance {
+                    nearest = Candidate { distance, node: neighbor };
+                    improved = true;
+                }
+            }
+            if !improved {
+                return nearest;
+            }
+        }
+    }
+
+    /// Up to `ef` accepted nodes near the query on one layer, nearest first
+    ///
+    /// Nodes that aren't accepted are still walked through, so a selective
+    /// filter widens the search rather than starving it
+    fn search_layer(
+        &self,
+        query: &Query,
+        start: Candidate,
+        ef: usize,
+        layer: usize,
+        accept: impl Fn(u32) -> bool,
+    ) -> Vec<Candidate> {
+        let mut visited = HashSet::from([start.node]);
+        let mut candidates = BinaryHeap::from([Reverse(start)]);
+        let mut results = BinaryHeap::new();
+        if accept(start.node) {
+            results.push(start);
+        }
+        while let Some(Reverse(current)) = candidates.pop() {
+            if results.len() >= ef && results.peek().is_some_and(|worst: &Candidate| current.distance > worst.distance) {
+                break;
+            }
+            for neighbor in self.neighbors(current.node, layer) {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let distance = self.distance(query, neighbor);
+                if results.len() < ef || results.peek().is_some_and(|worst| distance < worst.distance) {
+                    let candidate = Candidate { distance, node: neighbor };
+                    candidates.push(Reverse(candidate));
+                    if accept(neighbor) {
+                        results.push(candidate);
+                        if results.len() > ef {
+                            results.pop();
+                        }
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    /// Pick links from candidates sorted nearest first, skipping any closer
+    /// to an already picked link than to the base node so links spread out
+    fn select_neighbors(&self, candidates: &[Candidate], max_links: usize) -> Vec<u32> {
+        let mut selected: Vec<Candidate> = Vec::with_capacity(max_links);
+        for candidate in candidates {
+            if selected.len() >= max_links {
+                break;
+            }
+            let vector = Query { vector: self.stored_vector(candidate.node), table: None };
+            if selected.iter().all(|picked| self.distance(&vector, picked.node) > candidate.distance) {
+                selected.push(*candidate);
+            }
+        }
+        selected.into_iter().map(|candidate| candidate.node).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(id: &str, vector: Vec<f32>) -> (String, Vec<f32>, HashMap<String, String>) {
+        (id.to_string(), vector, HashMap::new())
+    }
+
+    #[test]
+    fn failed_batches_add_nothing() {
+        let mut store = VectorStore::new(2, None).unwrap();
+        let batch = vec![input("a", vec![1.0, 0.0]), input("b", vec![1.0, 0.0, 0.0])];
+        assert!(store.insert_batch(batch).is_err());
+        assert_eq!(store.size(), 0);
+        assert!(!store.has("a".to_string()));
+
+        store.insert_batch(vec![input("a", vec![1.0, 0.0])]).unwrap();
+        let found = store.search_vector(&[1.0, 0.0], 1, None).unwrap();
+        assert_eq!(found[0].id, "a");
+    }
+}
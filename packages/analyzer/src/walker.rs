@@ -0,0 +1,159 @@
+use ignore::overrides::OverrideBuilder;
+use ignore::{WalkBuilder, WalkState};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Options for `walk_workspace`
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalkOptions {
+    /// Skip files ignored by .gitignore, .ignore and the global git excludes
+    /// (default: true)
+    #[napi(js_name = "respectGitignore")]
+    pub respect_gitignore: Option<bool>,
+    /// Only files matching one of these globs, relative to the root
+    #[napi(js_name = "includeGlobs")]
+    pub include_globs: Option<Vec<String>>,
+    /// Skip files and directories matching any of these globs
+    #[napi(js_name = "excludeGlobs")]
+    pub exclude_globs: Option<Vec<String>>,
+    /// Skip files larger than this many bytes
+    #[napi(js_name = "maxFileSize")]
+    pub max_file_size: Option<f64>,
+}
+
+/// A file found by `walk_workspace`
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceFile {
+    pub path: String,
+    pub size: i64,
+    /// Modification time in ms since the epoch
+    pub modified: Option<f64>,
+    /// VS Code language ID guessed from the file name
+    #[napi(js_name = "languageId")]
+    pub language_id: Option<String>,
+}
+
+/// List the files under a workspace root on all cores
+///
+/// Follows git's ignore rules, including nested and parent .gitignore files,
+/// even outside a git repository. Hidden files are listed; `.git` is not.
+/// Results are sorted by path
+#[napi]
+pub fn walk_workspace(root: String, options: Option<WalkOptions>) -> Result<Vec<WorkspaceFile>> {
+    let options = options.unwrap_or_default();
+    let root_path = Path::new(&root);
+    if !root_path.is_dir() {
+        return Err(Error::from_reason(format!("Not a directory: {}", root)));
+    }
+    let respect_gitignore = options.respect_gitignore.unwrap_or(true);
+    let max_file_size = options.max_file_size.map(|size| size as u64);
+
+    let mut overrides = OverrideBuilder::new(root_path);
+    let includes = options.include_globs.iter().flatten().cloned();
+    let excludes = options.exclude_globs.iter().flatten().map(|glob| format!("!{}", glob));
+    for glob in includes.chain(excludes) {
+        overrides
+            .add(&glob)
+            .map_err(|e| Error::from_reason(format!("Invalid glob {}: {}", glob, e)))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| Error::from_reason(format!("Invalid globs: {}", e)))?;
+
+    let walker = WalkBuilder::new(root_path)
+        .hidden(false)
+        .require_git(false)
+        .git_ignore(respect_gitignore)
+        .git_global(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .ignore(respect_gitignore)
+        .parents(respect_gitignore)
+        .overrides(overrides)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build_parallel();
+
+    let files = Mutex::new(Vec::new());
+    walker.run(|| {
+        let files = &files;
+        Box::new(move |entry| {
+            let Ok(entry) = entry else { return WalkState::Continue };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                return WalkState::Continue;
+            }
+            let Ok(metadata) = entry.metadata() else { return WalkState::Continue };
+            if max_file_size.is_some_and(|max| metadata.len() > max) {
+                return WalkState::Continue;
+            }
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs_f64() * 1000.0);
+            let file = WorkspaceFile {
+                path: entry.path().to_string_lossy().into_owned(),
+                size: metadata.len() as i64,
+                modified,
+                language_id: language_from_path(entry.path()).map(String::from),
+            };
+            files.lock().unwrap().push(file);
+            WalkState::Continue
+        })
+    });
+
+    let mut files = files.into_inner().unwrap();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// VS Code language ID for a file name, from its extension or well-known name
+pub(crate) fn language_from_path(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    let language = match name {
+        "Dockerfile" => "dockerfile",
+        "Makefile" | "makefile" | "GNUmakefile" => "makefile",
+        "CMakeLists.txt" => "cmake",
+        _ => match name.rsplit_once('.')?.1.to_ascii_lowercase().as_str() {
+            "ts" | "mts" | "cts" => "typescript",
+            "tsx" => "typescriptreact",
+            "js" | "mjs" | "cjs" => "javascript",
+            "jsx" => "javascriptreact",
+            "py" | "pyi" => "python",
+            "rs" => "rust",
+            "go" => "go",
+            "java" => "java",
+            "kt" | "kts" => "kotlin",
+            "c" | "h" => "c",
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => "cpp",
+            "cs" => "csharp",
+            "rb" => "ruby",
+            "php" => "php",
+            "swift" => "swift",
+            "scala" => "scala",
+            "vue" => "vue",
+            "svelte" => "svelte",
+            "html" | "htm" => "html",
+            "css" => "css",
+            "scss" => "scss",
+            "less" => "less",
+            "json" => "json",
+            "jsonc" => "jsonc",
+            "yaml" | "yml" => "yaml",
+            "toml" => "toml",
+            "xml" => "xml",
+            "md" | "markdown" => "markdown",
+            "sql" => "sql",
+            "sh" | "bash" | "zsh" => "shellscript",
+            "ps1" => "powershell",
+            "lua" => "lua",
+            "dart" => "dart",
+            _ => return None,
+        },
+    };
+    Some(language)
+}
@@ -0,0 +1,97 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use napi::bindgen_prelude::*;
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A running watch; dropping it stops the watch
+pub(crate) type WorkspaceWatcher = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+/// A debounced change to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FileChange {
+    /// Created or written
+    Changed(PathBuf),
+    Removed(PathBuf),
+    Renamed(PathBuf, PathBuf),
+}
+
+/// Watch a directory tree, reporting changed files in batches once they
+/// have been quiet for `debounce`
+///
+/// Renames are paired up where the platform allows. Changes inside `.git`,
+/// and with `respect_gitignore` those matched by the root .gitignore, are
+/// left out; directories are never reported.
+pub(crate) fn watch_workspace(
+    root: &Path,
+    debounce: Duration,
+    respect_gitignore: bool,
+    mut on_changes: impl FnMut(Vec<FileChange>) + Send + 'static,
+) -> Result<WorkspaceWatcher> {
+    let gitignore = if respect_gitignore {
+        let mut builder = GitignoreBuilder::new(root);
+        // A missing or unreadable .gitignore just ignores nothing
+        builder.add(root.join(".gitignore"));
+        builder.build().unwrap_or_else(|_| Gitignore::empty())
+    } else {
+        Gitignore::empty()
+    };
+    let root_path = root.to_path_buf();
+    let skipped = move |path: &Path| {
+        path.strip_prefix(&root_path).map_or(true, |relative| relative.iter().any(|part| part == ".git"))
+            || gitignore.matched_path_or_any_parents(path, path.is_dir()).is_ignore()
+    };
+
+    let handler = move |result: DebounceEventResult| {
+        let Ok(events) = result else { return };
+        let mut changes: Vec<FileChange> = Vec::new();
+        for event in events {
+            let paths = &event.paths;
+            if paths.is_empty() {
+                continue;
+            }
+            let change = match event.kind {
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                    match (skipped(&paths[0]), skipped(&paths[1])) {
+                        (false, false) => FileChange::Renamed(paths[0].clone(), paths[1].clone()),
+                        (false, true) => FileChange::Removed(paths[0].clone()),
+                        (true, false) => FileChange::Changed(paths[1].clone()),
+                        (true, true) => continue,
+                    }
+                }
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+                    FileChange::Removed(paths[0].clone())
+                }
+                EventKind::Create(_)
+                | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any) => {
+                    FileChange::Changed(paths[0].clone())
+                }
+                _ => continue,
+            };
+            let path = match &change {
+                FileChange::Changed(path) | FileChange::Removed(path) | FileChange::Renamed(_, path) => path,
+            };
+            if matches!(change, FileChange::Changed(_)) && !path.is_file() {
+                continue;
+            }
+            if !matches!(change, FileChange::Renamed(..)) && skipped(path) {
+                continue;
+            }
+            if !changes.contains(&change) {
+                changes.push(change);
+            }
+        }
+        if !changes.is_empty() {
+            on_changes(changes);
+        }
+    };
+
+    let mut debouncer = new_debouncer(debounce, None, handler)
+        .map_err(|e| Error::from_reason(format!("Failed to start watcher: {}", e)))?;
+    debouncer
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(|e| Error::from_reason(format!("Failed to watch {}: {}", root.display(), e)))?;
+    Ok(debouncer)
+}